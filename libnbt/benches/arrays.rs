@@ -0,0 +1,75 @@
+//! Encoding and decoding of chunk sized NBT arrays, through `Tag` and typed structs.
+
+use std::fs::File;
+use std::io::Seek;
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use serde::{Deserialize, Serialize};
+
+use nbt::{from_slice, to_vec, to_writer, ByteArray, CompoundTag, IntArray, LongArray};
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Section {
+    #[serde(rename = "BlockStates")]
+    block_states: LongArray,
+    #[serde(rename = "Biomes")]
+    biomes: IntArray,
+    #[serde(rename = "BlockLight")]
+    block_light: ByteArray,
+}
+
+/// Array payloads of a 16 section chunk.
+fn sections() -> Vec<Section> {
+    (0..16)
+        .map(|y: i64| Section {
+            block_states: (0..4096i64).map(|i| (i * 0x0101_0101_0101) ^ y).collect(),
+            biomes: (0..1024).map(|i| i * 7).collect(),
+            block_light: (0..2048).map(|i| i as i8).collect(),
+        })
+        .collect()
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Chunk {
+    #[serde(rename = "Sections")]
+    sections: Vec<Section>,
+}
+
+fn arrays(c: &mut Criterion) {
+    let chunk = Chunk {
+        sections: sections(),
+    };
+    let buf = to_vec(&chunk).unwrap();
+    let tags: CompoundTag = from_slice(&buf).unwrap();
+
+    let mut group = c.benchmark_group("arrays");
+    group.throughput(Throughput::Bytes(buf.len() as u64));
+
+    group.bench_function("decode_typed", |b| {
+        b.iter(|| from_slice::<Chunk>(black_box(&buf)).unwrap())
+    });
+    group.bench_function("decode_tag", |b| {
+        b.iter(|| from_slice::<CompoundTag>(black_box(&buf)).unwrap())
+    });
+    group.bench_function("encode_typed", |b| {
+        b.iter(|| to_vec(black_box(&chunk)).unwrap())
+    });
+    group.bench_function("encode_tag", |b| b.iter(|| to_vec(black_box(&tags)).unwrap()));
+
+    // Unbuffered writers pay a syscall for every write
+    let path = std::env::temp_dir().join("libnbt_bench_arrays.nbt");
+    let mut file = File::create(&path).unwrap();
+    group.bench_function("encode_typed_file", |b| {
+        b.iter(|| {
+            file.rewind().unwrap();
+            to_writer(&mut file, black_box(&chunk)).unwrap()
+        })
+    });
+    drop(file);
+    let _ = std::fs::remove_file(path);
+
+    group.finish();
+}
+
+criterion_group!(benches, arrays);
+criterion_main!(benches);
@@ -0,0 +1,39 @@
+//! Reading and writing the generated fixtures in [`fixtures`].
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+
+use nbt::decode::{read_gzip_nbt, read_nbt};
+use nbt::encode::{write_gzip_nbt, write_nbt};
+
+mod fixtures;
+
+fn corpus(c: &mut Criterion) {
+    for (name, data) in fixtures::all() {
+        let mut raw = Vec::new();
+        write_nbt(&data, &mut raw).unwrap();
+        let mut gzip = Vec::new();
+        write_gzip_nbt(&data, &mut gzip).unwrap();
+
+        let mut group = c.benchmark_group(name);
+        group.throughput(Throughput::Bytes(raw.len() as u64));
+
+        group.bench_function("read_nbt", |b| {
+            b.iter(|| read_nbt(black_box(raw.as_slice())).unwrap())
+        });
+        group.bench_function("read_gzip_nbt", |b| {
+            b.iter(|| read_gzip_nbt(&mut black_box(gzip.as_slice())).unwrap())
+        });
+        group.bench_function("write_nbt", |b| {
+            let mut out = Vec::with_capacity(raw.len());
+            b.iter(|| {
+                out.clear();
+                write_nbt(black_box(&data), &mut out).unwrap()
+            })
+        });
+
+        group.finish();
+    }
+}
+
+criterion_group!(benches, corpus);
+criterion_main!(benches);
@@ -0,0 +1,309 @@
+//! Generated NBT fixtures for benchmarks.
+//!
+//! Shared by the libnbt and nbtc benchmarks and the `fixtures` example through
+//! `#[path]`, so nothing has to be downloaded. Generation is deterministic.
+
+use nbt::{CompoundTag, ListTag, Map, Tag};
+
+/// Every fixture together with its conventional file name.
+pub fn all() -> Vec<(&'static str, CompoundTag)> {
+    vec![
+        ("bigtest.nbt", bigtest()),
+        ("chunk.nbt", chunk()),
+        ("level.dat", level_dat()),
+    ]
+}
+
+/// Same layout and values as the well known `bigtest.nbt` test file.
+pub fn bigtest() -> CompoundTag {
+    let mut nested = Map::new();
+    nested.insert("egg".into(), Tag::Compound(named_value("Eggbert", 0.5)));
+    nested.insert("ham".into(), Tag::Compound(named_value("Hampus", 0.75)));
+
+    let mut compounds = ListTag::new();
+    for i in 0..2 {
+        let mut entry = Map::new();
+        entry.insert("created-on".into(), Tag::Long(1264099775885));
+        entry.insert("name".into(), string(&format!("Compound tag #{}", i)));
+        push(&mut compounds, Tag::Compound(entry));
+    }
+
+    let mut root = CompoundTag::named("Level".into());
+    root.push("longTest".into(), Tag::Long(i64::MAX));
+    root.push("shortTest".into(), Tag::Short(i16::MAX));
+    root.push(
+        "stringTest".into(),
+        string("HELLO WORLD THIS IS A TEST STRING ÅÄÖ!"),
+    );
+    root.push("floatTest".into(), Tag::Float(0.498_231_47));
+    root.push("intTest".into(), Tag::Int(i32::MAX));
+    root.push("nested compound test".into(), Tag::Compound(nested));
+    root.push("listTest (long)".into(), list((11..16).map(Tag::Long)));
+    root.push("listTest (compound)".into(), Tag::List(compounds));
+    root.push("byteTest".into(), Tag::Byte(i8::MAX));
+    root.push(
+        "byteArrayTest (the first 1000 values of (n*n*255+n*7)%100, starting with n=0 (0, 62, 34, 16, 8, ...))".into(),
+        Tag::ByteArray((0..1000).map(|n: i32| ((n * n * 255 + n * 7) % 100) as i8).collect()),
+    );
+    root.push("doubleTest".into(), Tag::Double(0.493_128_713_218_231_5));
+    root
+}
+
+/// Full 1.20 style chunk: 24 sections with block and biome palettes, heightmaps and
+/// block entities.
+pub fn chunk() -> CompoundTag {
+    let mut rng = Rng(0x5EED);
+
+    let mut sections = ListTag::new();
+    for y in -4..20 {
+        let mut block_states = Map::new();
+        let palette_len = 4 + rng.below(28) as usize;
+        block_states.insert(
+            "palette".into(),
+            list((0..palette_len).map(|i| Tag::Compound(block_state(&mut rng, i)))),
+        );
+        block_states.insert(
+            "data".into(),
+            Tag::LongArray((0..256).map(|_| rng.next() as i64).collect()),
+        );
+
+        let mut biomes = Map::new();
+        biomes.insert(
+            "palette".into(),
+            list(["minecraft:plains", "minecraft:river"].iter().map(|b| string(b))),
+        );
+        biomes.insert("data".into(), Tag::LongArray(vec![rng.next() as i64]));
+
+        let mut section = Map::new();
+        section.insert("Y".into(), Tag::Byte(y));
+        section.insert("block_states".into(), Tag::Compound(block_states));
+        section.insert("biomes".into(), Tag::Compound(biomes));
+        section.insert("BlockLight".into(), Tag::ByteArray(rng.bytes(2048)));
+        section.insert("SkyLight".into(), Tag::ByteArray(rng.bytes(2048)));
+        push(&mut sections, Tag::Compound(section));
+    }
+
+    let mut heightmaps = Map::new();
+    for name in ["MOTION_BLOCKING", "MOTION_BLOCKING_NO_LEAVES", "OCEAN_FLOOR", "WORLD_SURFACE"] {
+        heightmaps.insert(
+            name.into(),
+            Tag::LongArray((0..37).map(|_| rng.next() as i64).collect()),
+        );
+    }
+
+    let mut block_entities = ListTag::new();
+    for i in 0..12 {
+        let mut chest = Map::new();
+        chest.insert("id".into(), string("minecraft:chest"));
+        chest.insert("x".into(), Tag::Int(i * 2));
+        chest.insert("y".into(), Tag::Int(64));
+        chest.insert("z".into(), Tag::Int(-i));
+        chest.insert("keepPacked".into(), Tag::Byte(0));
+        chest.insert("Items".into(), items(&mut rng, 27));
+        push(&mut block_entities, Tag::Compound(chest));
+    }
+
+    let mut structures = Map::new();
+    structures.insert("References".into(), Tag::Compound(Map::new()));
+    structures.insert("starts".into(), Tag::Compound(Map::new()));
+
+    let mut root = CompoundTag::new();
+    root.push("DataVersion".into(), Tag::Int(3465));
+    root.push("xPos".into(), Tag::Int(-12));
+    root.push("zPos".into(), Tag::Int(31));
+    root.push("yPos".into(), Tag::Int(-4));
+    root.push("Status".into(), string("minecraft:full"));
+    root.push("LastUpdate".into(), Tag::Long(1_048_576));
+    root.push("InhabitedTime".into(), Tag::Long(20_000));
+    root.push("isLightOn".into(), Tag::Byte(1));
+    root.push("sections".into(), Tag::List(sections));
+    root.push("Heightmaps".into(), Tag::Compound(heightmaps));
+    root.push("block_entities".into(), Tag::List(block_entities));
+    root.push(
+        "entities".into(),
+        Tag::List(ListTag::with_type(nbt::consts::TAG_COMPOUND).unwrap()),
+    );
+    root.push("structures".into(), Tag::Compound(structures));
+    root.push(
+        "PostProcessing".into(),
+        list((0..24).map(|_| Tag::List(ListTag::with_type(nbt::consts::TAG_SHORT).unwrap()))),
+    );
+    root
+}
+
+/// Singleplayer `level.dat` with a well stocked player, game rules and data packs.
+pub fn level_dat() -> CompoundTag {
+    let mut rng = Rng(0x1E7E1);
+
+    let mut game_rules = Map::new();
+    for i in 0..64 {
+        let value = match i % 3 {
+            0 => "true".to_string(),
+            1 => "false".to_string(),
+            _ => rng.below(1000).to_string(),
+        };
+        game_rules.insert(format!("gameRule{}", i).into(), string(&value));
+    }
+
+    let mut abilities = Map::new();
+    for (name, value) in [("flying", 0), ("instabuild", 0), ("invulnerable", 0), ("mayfly", 0)] {
+        abilities.insert(name.into(), Tag::Byte(value));
+    }
+    abilities.insert("flySpeed".into(), Tag::Float(0.05));
+    abilities.insert("walkSpeed".into(), Tag::Float(0.1));
+
+    let mut player = Map::new();
+    player.insert("Health".into(), Tag::Float(20.0));
+    player.insert("foodLevel".into(), Tag::Int(20));
+    player.insert("XpLevel".into(), Tag::Int(30));
+    player.insert("Pos".into(), list([12.5, 64.0, -301.25].map(Tag::Double)));
+    player.insert("Rotation".into(), list([90.0, 0.0].map(Tag::Float)));
+    player.insert("UUID".into(), Tag::IntArray(vec![1, -2, 3, -4]));
+    player.insert("abilities".into(), Tag::Compound(abilities));
+    player.insert("Inventory".into(), items(&mut rng, 36));
+    player.insert("EnderItems".into(), items(&mut rng, 27));
+    player.insert(
+        "recipeBook".into(),
+        Tag::Compound({
+            let mut book = Map::new();
+            book.insert(
+                "recipes".into(),
+                list((0..800).map(|i| string(&format!("minecraft:recipe_{}", i)))),
+            );
+            book
+        }),
+    );
+
+    let mut dimensions = Map::new();
+    for dim in ["minecraft:overworld", "minecraft:the_nether", "minecraft:the_end"] {
+        let mut generator = Map::new();
+        generator.insert("type".into(), string("minecraft:noise"));
+        generator.insert("settings".into(), string(dim));
+        let mut dimension = Map::new();
+        dimension.insert("type".into(), string(dim));
+        dimension.insert("generator".into(), Tag::Compound(generator));
+        dimensions.insert(dim.into(), Tag::Compound(dimension));
+    }
+
+    let mut world_gen = Map::new();
+    world_gen.insert("seed".into(), Tag::Long(rng.next() as i64));
+    world_gen.insert("generate_features".into(), Tag::Byte(1));
+    world_gen.insert("bonus_chest".into(), Tag::Byte(0));
+    world_gen.insert("dimensions".into(), Tag::Compound(dimensions));
+
+    let mut data_packs = Map::new();
+    data_packs.insert(
+        "Enabled".into(),
+        list((0..40).map(|i| string(&format!("file/pack_{}.zip", i)))),
+    );
+    data_packs.insert("Disabled".into(), list([string("bundle")]));
+
+    let mut data = Map::new();
+    data.insert("DataVersion".into(), Tag::Int(3465));
+    data.insert("LevelName".into(), string("New World"));
+    data.insert("GameType".into(), Tag::Int(0));
+    data.insert("Difficulty".into(), Tag::Byte(2));
+    data.insert("hardcore".into(), Tag::Byte(0));
+    data.insert("Time".into(), Tag::Long(2_400_000));
+    data.insert("DayTime".into(), Tag::Long(6000));
+    data.insert("LastPlayed".into(), Tag::Long(1_700_000_000_000));
+    data.insert("SpawnX".into(), Tag::Int(0));
+    data.insert("SpawnY".into(), Tag::Int(64));
+    data.insert("SpawnZ".into(), Tag::Int(0));
+    data.insert("BorderSize".into(), Tag::Double(59_999_968.0));
+    data.insert("ServerBrands".into(), list([string("vanilla")]));
+    data.insert("GameRules".into(), Tag::Compound(game_rules));
+    data.insert("WorldGenSettings".into(), Tag::Compound(world_gen));
+    data.insert("DataPacks".into(), Tag::Compound(data_packs));
+    data.insert("Player".into(), Tag::Compound(player));
+
+    let mut root = CompoundTag::new();
+    root.push("Data".into(), Tag::Compound(data));
+    root
+}
+
+/// Stack of items with enchantments, as found in chests and player inventories.
+fn items(rng: &mut Rng, slots: i8) -> Tag {
+    list((0..slots).map(|slot| {
+        let mut enchantment = Map::new();
+        enchantment.insert("id".into(), string("minecraft:unbreaking"));
+        enchantment.insert("lvl".into(), Tag::Short(rng.below(3) as i16 + 1));
+
+        let mut tag = Map::new();
+        tag.insert("Damage".into(), Tag::Int(rng.below(250) as i32));
+        tag.insert("Enchantments".into(), list([Tag::Compound(enchantment)]));
+
+        let mut item = Map::new();
+        item.insert("Slot".into(), Tag::Byte(slot));
+        item.insert("id".into(), string(ITEMS[rng.below(ITEMS.len() as u64) as usize]));
+        item.insert("Count".into(), Tag::Byte(rng.below(64) as i8 + 1));
+        item.insert("tag".into(), Tag::Compound(tag));
+        Tag::Compound(item)
+    }))
+}
+
+fn block_state(rng: &mut Rng, index: usize) -> Map {
+    let mut state = Map::new();
+    state.insert("Name".into(), string(ITEMS[index % ITEMS.len()]));
+    if rng.below(2) == 0 {
+        let mut properties = Map::new();
+        properties.insert("facing".into(), string(["north", "south", "east", "west"][index % 4]));
+        properties.insert("waterlogged".into(), string("false"));
+        state.insert("Properties".into(), Tag::Compound(properties));
+    }
+    state
+}
+
+fn named_value(name: &str, value: f32) -> Map {
+    let mut map = Map::new();
+    map.insert("name".into(), string(name));
+    map.insert("value".into(), Tag::Float(value));
+    map
+}
+
+const ITEMS: [&str; 8] = [
+    "minecraft:stone",
+    "minecraft:dirt",
+    "minecraft:oak_planks",
+    "minecraft:iron_ingot",
+    "minecraft:diamond_pickaxe",
+    "minecraft:torch",
+    "minecraft:chest",
+    "minecraft:redstone",
+];
+
+fn string(text: &str) -> Tag {
+    Tag::String(text.into())
+}
+
+fn list<I: IntoIterator<Item = Tag>>(tags: I) -> Tag {
+    let mut list = ListTag::new();
+    for tag in tags {
+        push(&mut list, tag);
+    }
+    Tag::List(list)
+}
+
+fn push(list: &mut ListTag, tag: Tag) {
+    list.push(tag).expect("fixture lists hold a single tag type");
+}
+
+/// Xorshift generator, enough to make payloads look less uniform.
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, max: u64) -> u64 {
+        self.next() % max
+    }
+
+    fn bytes(&mut self, len: usize) -> Vec<i8> {
+        (0..len).map(|_| self.next() as i8).collect()
+    }
+}
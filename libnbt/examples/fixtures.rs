@@ -0,0 +1,34 @@
+//! Writes the benchmark fixtures to disk, e.g. to try them with `nbtc`.
+//!
+//! ```text
+//! cargo run -p nbt --example fixtures -- data
+//! ```
+//!
+//! `level.dat` and `bigtest.nbt` are gzip compressed like their originals, the chunk
+//! is stored uncompressed.
+
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::PathBuf;
+
+use nbt::encode::{write_gzip_nbt, write_nbt};
+
+#[path = "../benches/fixtures/mod.rs"]
+mod fixtures;
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = PathBuf::from(std::env::args().nth(1).unwrap_or_else(|| ".".into()));
+    std::fs::create_dir_all(&dir)?;
+
+    for (name, data) in fixtures::all() {
+        let path = dir.join(name);
+        let mut out = BufWriter::new(File::create(&path)?);
+        match name {
+            "chunk.nbt" => write_nbt(&data, &mut out)?,
+            _ => write_gzip_nbt(&data, &mut out)?,
+        }
+        println!("{}", path.display());
+    }
+
+    Ok(())
+}
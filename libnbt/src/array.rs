@@ -0,0 +1,177 @@
+//! Wrapper types for NBT arrays.
+//!
+//! A plain `Vec<i32>` is written as `TAG_List` of `TAG_Int`, these wrappers are
+//! written as `TAG_Int_Array` (and the byte/long counterparts) instead.
+
+use std::borrow::Cow;
+use std::fmt::Debug;
+use std::ops::{Deref, DerefMut};
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::consts::{LIBNBT_I32_ARRAY, LIBNBT_I64_ARRAY, LIBNBT_I8_ARRAY};
+
+/// Big-endian number stored in NBT arrays.
+pub trait ArrayElement: Copy + Debug + Serialize + private::Sealed + 'static {
+    const SIZE: usize;
+    /// Magic name arrays of this element are serialized with.
+    const ARRAY: &'static str;
+
+    fn from_be_slice(bytes: &[u8]) -> Self;
+
+    /// Payload of an array of `elements`, as written to NBT data.
+    fn to_be_bytes(elements: &[Self]) -> Cow<'_, [u8]>;
+}
+
+impl ArrayElement for i8 {
+    const SIZE: usize = 1;
+    const ARRAY: &'static str = LIBNBT_I8_ARRAY;
+
+    #[inline]
+    fn from_be_slice(bytes: &[u8]) -> Self {
+        bytes[0] as i8
+    }
+
+    #[inline]
+    fn to_be_bytes(elements: &[Self]) -> Cow<'_, [u8]> {
+        // SAFETY: `u8` and `i8` share size and alignment, every bit pattern is valid.
+        Cow::Borrowed(unsafe { std::slice::from_raw_parts(elements.as_ptr().cast(), elements.len()) })
+    }
+}
+
+impl ArrayElement for i32 {
+    const SIZE: usize = 4;
+    const ARRAY: &'static str = LIBNBT_I32_ARRAY;
+
+    #[inline]
+    fn from_be_slice(bytes: &[u8]) -> Self {
+        i32::from_be_bytes(bytes.try_into().unwrap())
+    }
+
+    fn to_be_bytes(elements: &[Self]) -> Cow<'_, [u8]> {
+        let mut bytes = Vec::with_capacity(elements.len() * Self::SIZE);
+        for element in elements {
+            bytes.extend_from_slice(&element.to_be_bytes());
+        }
+        Cow::Owned(bytes)
+    }
+}
+
+impl ArrayElement for i64 {
+    const SIZE: usize = 8;
+    const ARRAY: &'static str = LIBNBT_I64_ARRAY;
+
+    #[inline]
+    fn from_be_slice(bytes: &[u8]) -> Self {
+        i64::from_be_bytes(bytes.try_into().unwrap())
+    }
+
+    fn to_be_bytes(elements: &[Self]) -> Cow<'_, [u8]> {
+        let mut bytes = Vec::with_capacity(elements.len() * Self::SIZE);
+        for element in elements {
+            bytes.extend_from_slice(&element.to_be_bytes());
+        }
+        Cow::Owned(bytes)
+    }
+}
+
+mod private {
+    pub trait Sealed {}
+
+    impl Sealed for i8 {}
+    impl Sealed for i32 {}
+    impl Sealed for i64 {}
+}
+
+macro_rules! nbt_array {
+    ($(#[$meta:meta])* $name:ident, $elem:ty, $helper:ident) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+        pub struct $name(pub Vec<$elem>);
+
+        impl $name {
+            #[inline]
+            pub fn new() -> Self {
+                Self(Vec::new())
+            }
+
+            #[inline]
+            pub fn into_inner(self) -> Vec<$elem> {
+                self.0
+            }
+        }
+
+        impl Deref for $name {
+            type Target = Vec<$elem>;
+
+            #[inline]
+            fn deref(&self) -> &Self::Target {
+                &self.0
+            }
+        }
+
+        impl DerefMut for $name {
+            #[inline]
+            fn deref_mut(&mut self) -> &mut Self::Target {
+                &mut self.0
+            }
+        }
+
+        impl From<Vec<$elem>> for $name {
+            #[inline]
+            fn from(value: Vec<$elem>) -> Self {
+                Self(value)
+            }
+        }
+
+        impl From<$name> for Vec<$elem> {
+            #[inline]
+            fn from(value: $name) -> Self {
+                value.0
+            }
+        }
+
+        impl FromIterator<$elem> for $name {
+            fn from_iter<I: IntoIterator<Item = $elem>>(iter: I) -> Self {
+                Self(iter.into_iter().collect())
+            }
+        }
+
+        impl Serialize for $name {
+            fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+            where
+                S: Serializer,
+            {
+                crate::ser::bulk_array(&self.0, serializer)
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $name {
+            fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+            where
+                D: Deserializer<'de>,
+            {
+                crate::de::$helper(deserializer)
+            }
+        }
+    };
+}
+
+nbt_array!(
+    /// `Vec<i8>` (de)serialized as NBT `TAG_Byte_Array`.
+    ByteArray,
+    i8,
+    i8_array
+);
+nbt_array!(
+    /// `Vec<i32>` (de)serialized as NBT `TAG_Int_Array`.
+    IntArray,
+    i32,
+    i32_array
+);
+nbt_array!(
+    /// `Vec<i64>` (de)serialized as NBT `TAG_Long_Array`.
+    LongArray,
+    i64,
+    i64_array
+);
@@ -0,0 +1,368 @@
+//! Zero-copy NBT decoding from byte slices.
+//!
+//! [`read_nbt_ref`](crate::decode::read_nbt_ref) parses uncompressed NBT into a
+//! [`CompoundRef`]. Strings borrow from the input unless they need MUTF-8 conversion,
+//! and numeric arrays stay big-endian views that are only decoded when read.
+//! Compressed data has to be inflated into a buffer first.
+
+use std::borrow::Cow;
+use std::fmt::Debug;
+use std::io;
+use std::marker::PhantomData;
+
+pub use crate::array::ArrayElement;
+use crate::consts::*;
+use crate::err::{DecodeResult, TagDecodeError};
+use crate::raw;
+use crate::{CompoundTag, ListTag, Map, Tag};
+
+/// Most list elements reserved up front, lists that are larger grow as they're read.
+const LIST_PREALLOC: usize = 1 << 12;
+
+/// Borrowed counterpart of [`Tag`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum TagRef<'a> {
+    Byte(i8),
+    Short(i16),
+    Int(i32),
+    Long(i64),
+    Float(f32),
+    Double(f64),
+    ByteArray(&'a [i8]),
+    String(Cow<'a, str>),
+    List(ListRef<'a>),
+    Compound(CompoundRef<'a>),
+    IntArray(IntArrayRef<'a>),
+    LongArray(LongArrayRef<'a>),
+}
+
+impl TagRef<'_> {
+    /// Returns the NBT type id of this tag, as found in [`crate::consts`].
+    pub fn tag_type(&self) -> u8 {
+        match self {
+            Self::Byte(_) => TAG_BYTE,
+            Self::Short(_) => TAG_SHORT,
+            Self::Int(_) => TAG_INT,
+            Self::Long(_) => TAG_LONG,
+            Self::Float(_) => TAG_FLOAT,
+            Self::Double(_) => TAG_DOUBLE,
+            Self::ByteArray(_) => TAG_BYTE_ARRAY,
+            Self::String(_) => TAG_STRING,
+            Self::List(_) => TAG_LIST,
+            Self::Compound(_) => TAG_COMPOUND,
+            Self::IntArray(_) => TAG_INT_ARRAY,
+            Self::LongArray(_) => TAG_LONG_ARRAY,
+        }
+    }
+
+    /// Copies the tag into its owned form.
+    pub fn to_tag(&self) -> Tag {
+        match self {
+            Self::Byte(v) => Tag::Byte(*v),
+            Self::Short(v) => Tag::Short(*v),
+            Self::Int(v) => Tag::Int(*v),
+            Self::Long(v) => Tag::Long(*v),
+            Self::Float(v) => Tag::Float(*v),
+            Self::Double(v) => Tag::Double(*v),
+            Self::ByteArray(v) => Tag::ByteArray(v.to_vec()),
+            Self::String(v) => Tag::String(v.as_ref().into()),
+            Self::List(v) => Tag::List(v.to_list()),
+            Self::Compound(v) => Tag::Compound(v.to_map()),
+            Self::IntArray(v) => Tag::IntArray(v.to_vec()),
+            Self::LongArray(v) => Tag::LongArray(v.to_vec()),
+        }
+    }
+}
+
+/// Borrowed counterpart of [`CompoundTag`].
+///
+/// Entries are kept in file order, lookups scan them linearly.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CompoundRef<'a> {
+    pub name: Option<Cow<'a, str>>,
+    entries: Vec<(Cow<'a, str>, TagRef<'a>)>,
+}
+
+impl<'a> CompoundRef<'a> {
+    pub fn get(&self, key: &str) -> Option<&TagRef<'a>> {
+        self.entries
+            .iter()
+            .find(|(name, _)| name.as_ref() == key)
+            .map(|(_, tag)| tag)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &TagRef<'a>)> {
+        self.entries.iter().map(|(name, tag)| (name.as_ref(), tag))
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Copies the compound into its owned form.
+    pub fn to_compound(&self) -> CompoundTag {
+        let mut ctag = CompoundTag::with(self.to_map());
+        ctag.name = self.name.as_deref().map(Into::into);
+        ctag
+    }
+
+    fn to_map(&self) -> Map {
+        self.entries
+            .iter()
+            .map(|(name, tag)| (name.as_ref().into(), tag.to_tag()))
+            .collect()
+    }
+}
+
+/// Borrowed counterpart of [`ListTag`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ListRef<'a> {
+    element_type: u8,
+    elements: Vec<TagRef<'a>>,
+}
+
+impl<'a> ListRef<'a> {
+    #[inline]
+    pub fn element_type(&self) -> u8 {
+        self.element_type
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.elements.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.elements.is_empty()
+    }
+
+    pub fn get(&self, index: usize) -> Option<&TagRef<'a>> {
+        self.elements.get(index)
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, TagRef<'a>> {
+        self.elements.iter()
+    }
+
+    /// Copies the list into its owned form.
+    pub fn to_list(&self) -> ListTag {
+        let mut list =
+            ListTag::with_type(self.element_type).expect("element type checked while decoding");
+        for tag in &self.elements {
+            list.push(tag.to_tag())
+                .expect("element types checked while decoding");
+        }
+        list
+    }
+}
+
+/// Lazy view over a big-endian NBT array, elements are decoded on access.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct ArrayRef<'a, T> {
+    bytes: &'a [u8],
+    _elem: PhantomData<T>,
+}
+
+pub type IntArrayRef<'a> = ArrayRef<'a, i32>;
+pub type LongArrayRef<'a> = ArrayRef<'a, i64>;
+
+impl<'a, T: ArrayElement> ArrayRef<'a, T> {
+    #[inline]
+    fn new(bytes: &'a [u8]) -> Self {
+        Self {
+            bytes,
+            _elem: PhantomData,
+        }
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.bytes.len() / T::SIZE
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    pub fn get(&self, index: usize) -> Option<T> {
+        let start = index.checked_mul(T::SIZE)?;
+        self.bytes
+            .get(start..start.checked_add(T::SIZE)?)
+            .map(T::from_be_slice)
+    }
+
+    pub fn iter(&self) -> impl ExactSizeIterator<Item = T> + 'a {
+        self.bytes.chunks_exact(T::SIZE).map(T::from_be_slice)
+    }
+
+    /// Raw big-endian bytes of the array.
+    #[inline]
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.bytes
+    }
+
+    pub fn to_vec(&self) -> Vec<T> {
+        self.iter().collect()
+    }
+}
+
+impl<T: ArrayElement> Debug for ArrayRef<'_, T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+/// Cursor over uncompressed NBT bytes.
+pub(crate) struct SliceReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> SliceReader<'a> {
+    #[inline]
+    pub(crate) fn new(data: &'a [u8]) -> Self {
+        Self { data, pos: 0 }
+    }
+
+    pub(crate) fn read_root(&mut self) -> DecodeResult<CompoundRef<'a>> {
+        self.root().map_err(|err| err.at(self.pos as u64))
+    }
+
+    fn root(&mut self) -> DecodeResult<CompoundRef<'a>> {
+        if self.ubyte()? != TAG_COMPOUND {
+            return Err(TagDecodeError::RootMustBeCompound);
+        }
+
+        let name = self.string()?;
+        let mut root = self.compound()?;
+        root.name = if name.is_empty() { None } else { Some(name) };
+        Ok(root)
+    }
+
+    #[inline]
+    fn take(&mut self, len: usize) -> DecodeResult<&'a [u8]> {
+        let end = self.pos.checked_add(len).filter(|end| *end <= self.data.len());
+        match end {
+            Some(end) => {
+                let bytes = &self.data[self.pos..end];
+                self.pos = end;
+                Ok(bytes)
+            }
+            None => Err(io::Error::from(io::ErrorKind::UnexpectedEof).into()),
+        }
+    }
+
+    #[inline]
+    fn array<const N: usize>(&mut self) -> DecodeResult<[u8; N]> {
+        Ok(self.take(N)?.try_into().unwrap())
+    }
+
+    #[inline]
+    fn ubyte(&mut self) -> DecodeResult<u8> {
+        Ok(self.array::<1>()?[0])
+    }
+
+    #[inline]
+    fn length(&mut self) -> DecodeResult<usize> {
+        let len = i32::from_be_bytes(self.array()?);
+        usize::try_from(len).map_err(|_| TagDecodeError::InvalidLength(len))
+    }
+
+    #[inline]
+    fn string(&mut self) -> DecodeResult<Cow<'a, str>> {
+        let len = u16::from_be_bytes(self.array()?) as usize;
+        Ok(raw::decode_str(self.take(len)?)?)
+    }
+
+    #[inline]
+    fn remaining(&self) -> usize {
+        self.data.len() - self.pos
+    }
+
+    fn compound(&mut self) -> DecodeResult<CompoundRef<'a>> {
+        let mut entries = Vec::new();
+
+        loop {
+            let tag = self.ubyte()?;
+            if tag == TAG_END {
+                break;
+            }
+
+            let name = self.string()?;
+            let value = self
+                .payload(tag)
+                .map_err(|err| err.in_key(&name, self.pos as u64))?;
+            entries.push((name, value));
+        }
+
+        Ok(CompoundRef {
+            name: None,
+            entries,
+        })
+    }
+
+    fn payload(&mut self, tag: u8) -> DecodeResult<TagRef<'a>> {
+        let tag = match tag {
+            TAG_BYTE => TagRef::Byte(i8::from_be_bytes(self.array()?)),
+            TAG_SHORT => TagRef::Short(i16::from_be_bytes(self.array()?)),
+            TAG_INT => TagRef::Int(i32::from_be_bytes(self.array()?)),
+            TAG_LONG => TagRef::Long(i64::from_be_bytes(self.array()?)),
+            TAG_FLOAT => TagRef::Float(f32::from_be_bytes(self.array()?)),
+            TAG_DOUBLE => TagRef::Double(f64::from_be_bytes(self.array()?)),
+            TAG_BYTE_ARRAY => {
+                let len = self.length()?;
+                let bytes = self.take(len)?;
+                // SAFETY: `u8` and `i8` share size and alignment, every bit pattern is valid.
+                let bytes = unsafe { std::slice::from_raw_parts(bytes.as_ptr().cast(), len) };
+                TagRef::ByteArray(bytes)
+            }
+            TAG_STRING => TagRef::String(self.string()?),
+            TAG_LIST => {
+                let element_type = self.ubyte()?;
+                let len = self.length()?;
+                if element_type > TAG_LONG_ARRAY || (len > 0 && element_type == TAG_END) {
+                    return Err(TagDecodeError::UnknownTagType {
+                        tag_type_id: element_type,
+                    });
+                }
+
+                // Don't trust `len` beyond the elements the data left can hold
+                let fit = self.remaining() / raw::min_payload_size(element_type) as usize;
+                let mut elements = Vec::with_capacity(len.min(fit).min(LIST_PREALLOC));
+                for index in 0..len {
+                    let element = self
+                        .payload(element_type)
+                        .map_err(|err| err.in_index(index, self.pos as u64))?;
+                    elements.push(element);
+                }
+
+                TagRef::List(ListRef {
+                    element_type,
+                    elements,
+                })
+            }
+            TAG_COMPOUND => TagRef::Compound(self.compound()?),
+            TAG_INT_ARRAY => {
+                let len = self.length()?;
+                let bytes = self.take(len.saturating_mul(i32::SIZE))?;
+                TagRef::IntArray(ArrayRef::new(bytes))
+            }
+            TAG_LONG_ARRAY => {
+                let len = self.length()?;
+                let bytes = self.take(len.saturating_mul(i64::SIZE))?;
+                TagRef::LongArray(ArrayRef::new(bytes))
+            }
+            tag_type_id => return Err(TagDecodeError::UnknownTagType { tag_type_id }),
+        };
+
+        Ok(tag)
+    }
+}
@@ -1,16 +1,26 @@
-pub(crate) const TAG_END: u8 = 0;
-pub(crate) const TAG_BYTE: u8 = 1;
-pub(crate) const TAG_SHORT: u8 = 2;
-pub(crate) const TAG_INT: u8 = 3;
-pub(crate) const TAG_LONG: u8 = 4;
-pub(crate) const TAG_FLOAT: u8 = 5;
-pub(crate) const TAG_DOUBLE: u8 = 6;
-pub(crate) const TAG_BYTE_ARRAY: u8 = 7;
-pub(crate) const TAG_STRING: u8 = 8;
-pub(crate) const TAG_LIST: u8 = 9;
-pub(crate) const TAG_COMPOUND: u8 = 10;
-pub(crate) const TAG_INT_ARRAY: u8 = 11;
-pub(crate) const TAG_LONG_ARRAY: u8 = 12;
+pub const TAG_END: u8 = 0;
+pub const TAG_BYTE: u8 = 1;
+pub const TAG_SHORT: u8 = 2;
+pub const TAG_INT: u8 = 3;
+pub const TAG_LONG: u8 = 4;
+pub const TAG_FLOAT: u8 = 5;
+pub const TAG_DOUBLE: u8 = 6;
+pub const TAG_BYTE_ARRAY: u8 = 7;
+pub const TAG_STRING: u8 = 8;
+pub const TAG_LIST: u8 = 9;
+pub const TAG_COMPOUND: u8 = 10;
+pub const TAG_INT_ARRAY: u8 = 11;
+pub const TAG_LONG_ARRAY: u8 = 12;
+
+/// Magic name used to (de)serialize [`ListTag`](crate::ListTag) together with its element type.
+pub(crate) const LIBNBT_LIST: &str = "__libnbt_list__";
+/// Magic name used to deserialize [`Tag`](crate::Tag) by its on-disk type id.
+pub(crate) const LIBNBT_TAG: &str = "__libnbt_tag__";
+
+/// Magic names used to tell NBT arrays apart from lists, see [`crate::ser::i8_array`].
+pub(crate) const LIBNBT_I8_ARRAY: &str = "__libnbt_i8_array__";
+pub(crate) const LIBNBT_I32_ARRAY: &str = "__libnbt_i32_array__";
+pub(crate) const LIBNBT_I64_ARRAY: &str = "__libnbt_i64_array__";
 
 pub(crate) const TAGS: [&str; 13] = [
     "TAG_END",
@@ -21,11 +31,11 @@ pub(crate) const TAGS: [&str; 13] = [
     "TAG_FLOAT",
     "TAG_DOUBLE",
     "TAG_BYTE_ARRAY",
-    "TAG_INT_ARRAY",
-    "TAG_LONG_ARRAY",
     "TAG_STRING",
     "TAG_LIST",
     "TAG_COMPOUND",
+    "TAG_INT_ARRAY",
+    "TAG_LONG_ARRAY",
 ];
 
 macro_rules! tag_name {
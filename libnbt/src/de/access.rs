@@ -1,7 +1,8 @@
 use std::io::{self, Read};
 
-use serde::de;
+use serde::de::{self, IntoDeserializer};
 
+use crate::consts::*;
 use crate::err::TagDecodeError as DecodeErr;
 use crate::raw;
 use super::dec::{Decoder, InnerDecoder};
@@ -10,11 +11,17 @@ use super::dec::{Decoder, InnerDecoder};
 pub(super) struct MapDecoder<'a, R: Read + 'a> {
     outer: &'a mut Decoder<R>,
     tag: Option<u8>,
+    /// Key of the current entry, for error paths.
+    key: String,
 }
 
 impl<'a, R: Read> MapDecoder<'a, R> {
     pub(super) fn new(outer: &'a mut Decoder<R>) -> Self {
-        MapDecoder { outer, tag: None }
+        MapDecoder {
+            outer,
+            tag: None,
+            key: String::new(),
+        }
     }
 }
 
@@ -35,13 +42,12 @@ impl<'de: 'a, 'a, R: Read + 'a> de::MapAccess<'de> for MapDecoder<'a, R> {
         // Keep track of the tag so that we can decode the field correctly.
         self.tag = Some(tag as u8);
 
-        // TODO: Enforce that keys must be String. This is a bit of a hack.
-        let mut de = InnerDecoder {
-            outer: self.outer,
-            tag: 0x08,
-        };
+        self.key = raw::read_string(&mut self.outer.reader)?.unwrap_or_default();
+        let key = seed
+            .deserialize(IntoDeserializer::<DecodeErr>::into_deserializer(self.key.as_str()))
+            .map_err(|err| err.in_key(&self.key, self.outer.offset()))?;
 
-        Ok(Some(seed.deserialize(&mut de)?))
+        Ok(Some(key))
     }
 
     fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value, Self::Error>
@@ -55,56 +61,205 @@ impl<'de: 'a, 'a, R: Read + 'a> de::MapAccess<'de> for MapDecoder<'a, R> {
             },
             None => unimplemented!(),
         };
-        Ok(seed.deserialize(&mut de)?)
+        seed.deserialize(&mut de)
+            .map_err(|err| err.in_key(&self.key, self.outer.offset()))
     }
 }
+
+/// Decoder for the type id of a tag or list, exposed as an enum variant.
 ///
-/// Decoder for list-like types.
-pub(super) struct SeqDecoder<'a, R: Read> {
+/// Lets [`crate::Tag`] and [`crate::ListTag`] learn the on-disk type, even when the
+/// payload is empty.
+pub(super) struct TypeIdAccess<'a, R: Read> {
     outer: &'a mut Decoder<R>,
     tag: u8,
     length: i32,
-    current: i32,
 }
 
-impl<'a, R: Read> SeqDecoder<'a, R>
-{
-    pub(super) fn list(outer: &'a mut Decoder<R>) -> io::Result<Self> {
-        let tag = raw::read_ubyte(&mut outer.reader)?;
-        let length = raw::read_int(&mut outer.reader)?;
-        Ok(SeqDecoder {
+impl<'a, R: Read> TypeIdAccess<'a, R> {
+    pub(super) fn tag(outer: &'a mut Decoder<R>, tag: u8) -> Self {
+        TypeIdAccess {
             outer,
             tag,
-            length,
-            current: 0,
-        })
+            length: 0,
+        }
     }
 
-    pub(super) fn byte_array(outer: &'a mut Decoder<R>) -> io::Result<Self> {
+    pub(super) fn list(outer: &'a mut Decoder<R>) -> io::Result<Self> {
+        let tag = raw::read_ubyte(&mut outer.reader)?;
         let length = raw::read_int(&mut outer.reader)?;
-        Ok(SeqDecoder {
-            outer,
-            tag: 0x01,
-            length,
-            current: 0,
-        })
+        Ok(TypeIdAccess { outer, tag, length })
     }
+}
 
-    pub(super) fn int_array(outer: &'a mut Decoder<R>) -> io::Result<Self> {
-        let length = raw::read_int(&mut outer.reader)?;
-        Ok(SeqDecoder {
-            outer,
-            tag: 0x03,
-            length,
+impl<'de, 'a, R: Read + 'a> de::EnumAccess<'de> for TypeIdAccess<'a, R> {
+    type Error = DecodeErr;
+    type Variant = Self;
+
+    fn variant_seed<V>(self, seed: V) -> Result<(V::Value, Self::Variant), Self::Error>
+    where
+        V: de::DeserializeSeed<'de>,
+    {
+        let tag = seed.deserialize(IntoDeserializer::<DecodeErr>::into_deserializer(self.tag))?;
+        Ok((tag, self))
+    }
+}
+
+impl<'de, 'a, R: Read + 'a> de::VariantAccess<'de> for TypeIdAccess<'a, R> {
+    type Error = DecodeErr;
+
+    fn unit_variant(self) -> Result<(), Self::Error> {
+        Err(de::Error::custom("tag type id must carry a payload"))
+    }
+
+    fn newtype_variant_seed<T>(self, seed: T) -> Result<T::Value, Self::Error>
+    where
+        T: de::DeserializeSeed<'de>,
+    {
+        let mut de = InnerDecoder {
+            outer: self.outer,
+            tag: self.tag,
+        };
+        seed.deserialize(&mut de)
+    }
+
+    fn tuple_variant<V>(self, _len: usize, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        visitor.visit_seq(SeqDecoder {
+            outer: self.outer,
+            tag: self.tag,
+            length: self.length,
             current: 0,
         })
     }
 
-    pub(super) fn long_array(outer: &'a mut Decoder<R>) -> io::Result<Self> {
+    fn struct_variant<V>(
+        self,
+        _fields: &'static [&'static str],
+        _visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        Err(de::Error::custom("tag type id must carry a payload"))
+    }
+}
+
+/// Decoder for externally tagged enums, stored as a compound with a single key.
+pub(super) struct VariantDecoder<'a, R: Read> {
+    outer: &'a mut Decoder<R>,
+    tag: u8,
+}
+
+impl<'a, R: Read> VariantDecoder<'a, R> {
+    pub(super) fn new(outer: &'a mut Decoder<R>) -> Self {
+        VariantDecoder { outer, tag: TAG_END }
+    }
+
+    /// Reads the end of the enclosing compound, which must not hold another key.
+    fn end(self) -> Result<(), DecodeErr> {
+        match raw::read_ubyte(&mut self.outer.reader)? {
+            TAG_END => Ok(()),
+            found => Err(DecodeErr::TagMismatch {
+                found,
+                expected: TAG_END,
+            }),
+        }
+    }
+
+    fn expect(&self, expected: u8) -> Result<(), DecodeErr> {
+        if self.tag != expected {
+            return Err(DecodeErr::TagMismatch {
+                found: self.tag,
+                expected,
+            });
+        }
+        Ok(())
+    }
+}
+
+impl<'de, 'a, R: Read + 'a> de::EnumAccess<'de> for VariantDecoder<'a, R> {
+    type Error = DecodeErr;
+    type Variant = Self;
+
+    fn variant_seed<V>(mut self, seed: V) -> Result<(V::Value, Self::Variant), Self::Error>
+    where
+        V: de::DeserializeSeed<'de>,
+    {
+        let (tag, name) = raw::read_header(&mut self.outer.reader)?;
+        if tag == TAG_END {
+            return Err(de::Error::custom("enum compound holds no variant"));
+        }
+
+        self.tag = tag;
+        let name: String = name.unwrap_or_default().into();
+        let variant = seed.deserialize(IntoDeserializer::<DecodeErr>::into_deserializer(name))?;
+        Ok((variant, self))
+    }
+}
+
+impl<'de, 'a, R: Read + 'a> de::VariantAccess<'de> for VariantDecoder<'a, R> {
+    type Error = DecodeErr;
+
+    fn unit_variant(self) -> Result<(), Self::Error> {
+        Err(de::Error::custom("unit variants are stored as TAG_String"))
+    }
+
+    fn newtype_variant_seed<T>(self, seed: T) -> Result<T::Value, Self::Error>
+    where
+        T: de::DeserializeSeed<'de>,
+    {
+        let mut de = InnerDecoder {
+            outer: &mut *self.outer,
+            tag: self.tag,
+        };
+        let value = seed.deserialize(&mut de)?;
+        self.end()?;
+        Ok(value)
+    }
+
+    fn tuple_variant<V>(self, _len: usize, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.expect(TAG_LIST)?;
+        let value = visitor.visit_seq(SeqDecoder::list(&mut *self.outer)?)?;
+        self.end()?;
+        Ok(value)
+    }
+
+    fn struct_variant<V>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.expect(TAG_COMPOUND)?;
+        let value = visitor.visit_map(MapDecoder::new(&mut *self.outer))?;
+        self.end()?;
+        Ok(value)
+    }
+}
+
+/// Decoder for list-like types.
+pub(super) struct SeqDecoder<'a, R: Read> {
+    outer: &'a mut Decoder<R>,
+    tag: u8,
+    length: i32,
+    current: i32,
+}
+
+impl<'a, R: Read> SeqDecoder<'a, R> {
+    pub(super) fn list(outer: &'a mut Decoder<R>) -> io::Result<Self> {
+        let tag = raw::read_ubyte(&mut outer.reader)?;
         let length = raw::read_int(&mut outer.reader)?;
         Ok(SeqDecoder {
             outer,
-            tag: 0x04,
+            tag,
             length,
             current: 0,
         })
@@ -126,7 +281,9 @@ impl<'de: 'a, 'a, R: io::Read + 'a> de::SeqAccess<'de> for SeqDecoder<'a, R> {
             outer: self.outer,
             tag: self.tag,
         };
-        let value = seed.deserialize(&mut de)?;
+        let value = seed
+            .deserialize(&mut de)
+            .map_err(|err| err.in_index(self.current as usize, self.outer.offset()))?;
 
         self.current += 1;
 
@@ -138,3 +295,66 @@ impl<'de: 'a, 'a, R: io::Read + 'a> de::SeqAccess<'de> for SeqDecoder<'a, R> {
         Some(self.length as usize)
     }
 }
+
+/// Decoder for NBT arrays, element by element out of a payload read in one go.
+pub(super) struct ArrayDecoder {
+    inner: Decoder<io::Cursor<Vec<u8>>>,
+    tag: u8,
+    length: usize,
+    current: usize,
+    /// Offset of the array end in the outer stream.
+    offset: u64,
+}
+
+impl ArrayDecoder {
+    pub(super) fn new<R: Read>(outer: &mut Decoder<R>, array_type: u8) -> Result<Self, DecodeErr> {
+        let (tag, bytes) = match array_type {
+            TAG_BYTE_ARRAY => (TAG_BYTE, raw::read_array_bytes::<i8, _>(&mut outer.reader)?),
+            TAG_INT_ARRAY => (TAG_INT, raw::read_array_bytes::<i32, _>(&mut outer.reader)?),
+            _ => (TAG_LONG, raw::read_array_bytes::<i64, _>(&mut outer.reader)?),
+        };
+
+        let length = match tag {
+            TAG_BYTE => bytes.len(),
+            TAG_INT => bytes.len() / 4,
+            _ => bytes.len() / 8,
+        };
+
+        Ok(ArrayDecoder {
+            inner: Decoder::new(io::Cursor::new(bytes)).map_unsigned(outer.unsigned),
+            tag,
+            length,
+            current: 0,
+            offset: outer.offset(),
+        })
+    }
+}
+
+impl<'de> de::SeqAccess<'de> for ArrayDecoder {
+    type Error = DecodeErr;
+
+    fn next_element_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, DecodeErr>
+    where
+        K: de::DeserializeSeed<'de>,
+    {
+        if self.current == self.length {
+            return Ok(None);
+        }
+
+        let mut de = InnerDecoder {
+            outer: &mut self.inner,
+            tag: self.tag,
+        };
+        let value = seed
+            .deserialize(&mut de)
+            .map_err(|err| err.in_index(self.current, self.offset))?;
+
+        self.current += 1;
+
+        Ok(Some(value))
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.length - self.current)
+    }
+}
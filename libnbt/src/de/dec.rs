@@ -1,27 +1,59 @@
 use std::io::Read;
 
-use serde::de::{self, Deserializer};
+use serde::de::{self, Deserializer, IntoDeserializer};
 use serde::forward_to_deserialize_any;
 
-use crate::consts::{TAG_BYTE, TAG_COMPOUND};
+use crate::consts::*;
 use crate::err::TagDecodeError as DecodeErr;
-use crate::raw;
+use crate::raw::{self, CountingReader};
 
-use super::access::{MapDecoder, SeqDecoder};
+use super::access::{ArrayDecoder, MapDecoder, SeqDecoder, TypeIdAccess, VariantDecoder};
 
-pub(crate) struct Decoder<R> {
-    pub(super) reader: R,
+/// Decode data from NBT format.
+///
+/// Implements `serde::Deserializer` to deserialize objects from NBT data. Most users
+/// want [`crate::from_reader`] instead.
+pub struct Decoder<R> {
+    pub(super) reader: CountingReader<R>,
+    pub(super) unsigned: bool,
 }
 
 impl<R: Read> Decoder<R> {
-    pub(crate) fn new(reader: R) -> Self {
-        Self { reader }
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader: CountingReader::new(reader),
+            unsigned: false,
+        }
+    }
+
+    /// Bytes read so far.
+    pub fn offset(&self) -> u64 {
+        self.reader.position()
+    }
+
+    /// Opts into reading unsigned integers from signed tags of the same width by
+    /// reinterpreting their bits, the reverse of [`crate::Encoder::map_unsigned`].
+    ///
+    /// Values from wider tags must still fit, or decoding fails.
+    pub fn map_unsigned(mut self, enabled: bool) -> Self {
+        self.unsigned = enabled;
+        self
+    }
+
+    /// Unwraps the underlying reader.
+    pub fn into_inner(self) -> R {
+        self.reader.into_inner()
     }
 }
 
-impl<'de, 'a, R: Read> Deserializer<'de> for &'a mut Decoder<R> {
+impl<'de, R: Read> Deserializer<'de> for &mut Decoder<R> {
     type Error = DecodeErr;
 
+    #[inline]
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+
     fn deserialize_map<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: de::Visitor<'de>,
@@ -68,6 +100,24 @@ impl<'de, 'a, R: Read> Deserializer<'de> for &'a mut Decoder<R> {
         visitor.visit_newtype_struct(self)
     }
 
+    /// Root enums are externally tagged, so the root compound holds a single variant.
+    fn deserialize_enum<V>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        let (tag, _) = raw::read_header(&mut self.reader)?;
+
+        match tag {
+            TAG_COMPOUND => visitor.visit_enum(VariantDecoder::new(self)),
+            _ => Err(DecodeErr::RootMustBeCompound),
+        }
+    }
+
     fn deserialize_any<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
     where
         V: de::Visitor<'de>,
@@ -78,7 +128,7 @@ impl<'de, 'a, R: Read> Deserializer<'de> for &'a mut Decoder<R> {
     forward_to_deserialize_any! {
         bool i8 i16 i32 i64 u8 u16 u32 u64 f32 f64 char str
         string bytes byte_buf unit seq tuple_struct tuple
-        option enum identifier ignored_any
+        option identifier ignored_any
     }
 }
 
@@ -91,6 +141,11 @@ pub(super) struct InnerDecoder<'a, R: Read + 'a> {
 impl<'a, 'de, R: Read> de::Deserializer<'de> for &'a mut InnerDecoder<'a, R> {
     type Error = DecodeErr;
 
+    #[inline]
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+
     fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: de::Visitor<'de>,
@@ -104,14 +159,14 @@ impl<'a, 'de, R: Read> de::Deserializer<'de> for &'a mut InnerDecoder<'a, R> {
             0x04 => visitor.visit_i64(raw::read_long(&mut outer.reader)?),
             0x05 => visitor.visit_f32(raw::read_float(&mut outer.reader)?),
             0x06 => visitor.visit_f64(raw::read_double(&mut outer.reader)?),
-            0x07 => visitor.visit_seq(SeqDecoder::byte_array(outer)?),
+            0x07 => visitor.visit_seq(ArrayDecoder::new(outer, TAG_BYTE_ARRAY)?),
             0x08 => {
                 visitor.visit_string(raw::read_string(&mut outer.reader)?.unwrap_or("".to_string()))
             }
             0x09 => visitor.visit_seq(SeqDecoder::list(outer)?),
             0x0a => visitor.visit_map(MapDecoder::new(outer)),
-            0x0b => visitor.visit_seq(SeqDecoder::int_array(outer)?),
-            0x0c => visitor.visit_seq(SeqDecoder::long_array(outer)?),
+            0x0b => visitor.visit_seq(ArrayDecoder::new(outer, TAG_INT_ARRAY)?),
+            0x0c => visitor.visit_seq(ArrayDecoder::new(outer, TAG_LONG_ARRAY)?),
             tag_type_id => Err(DecodeErr::UnknownTagType { tag_type_id }),
         }
     }
@@ -135,7 +190,57 @@ impl<'a, 'de, R: Read> de::Deserializer<'de> for &'a mut InnerDecoder<'a, R> {
         }
     }
 
-    /// Interpret missing values as None.
+    fn deserialize_u8<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self.tag {
+            TAG_BYTE if self.outer.unsigned => {
+                visitor.visit_u8(raw::read_byte(&mut self.outer.reader)? as u8)
+            }
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_u16<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self.tag {
+            TAG_SHORT if self.outer.unsigned => {
+                visitor.visit_u16(raw::read_short(&mut self.outer.reader)? as u16)
+            }
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_u32<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self.tag {
+            TAG_INT if self.outer.unsigned => {
+                visitor.visit_u32(raw::read_int(&mut self.outer.reader)? as u32)
+            }
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_u64<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self.tag {
+            TAG_LONG if self.outer.unsigned => {
+                visitor.visit_u64(raw::read_long(&mut self.outer.reader)? as u64)
+            }
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    /// NBT has no null, so a present value is always `Some`.
+    ///
+    /// `None` fields are left out when encoding, serde then reads the missing key as `None`.
     fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: de::Visitor<'de>,
@@ -165,8 +270,88 @@ impl<'a, 'de, R: Read> de::Deserializer<'de> for &'a mut InnerDecoder<'a, R> {
         visitor.visit_newtype_struct(self)
     }
 
+    /// Deserialize [`crate::Tag`] and [`crate::ListTag`] along with their type ids.
+    ///
+    /// Other enums are externally tagged: unit variants are read from `TAG_String`,
+    /// the rest from a compound holding the variant name as its only key.
+    fn deserialize_enum<V>(
+        self,
+        name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        match name {
+            LIBNBT_TAG => visitor.visit_enum(TypeIdAccess::tag(self.outer, self.tag)),
+            LIBNBT_LIST if self.tag == TAG_LIST => {
+                visitor.visit_enum(TypeIdAccess::list(self.outer)?)
+            }
+            LIBNBT_LIST => Err(DecodeErr::TagMismatch {
+                found: self.tag,
+                expected: TAG_LIST,
+            }),
+            _ if self.tag == TAG_STRING => {
+                let variant = raw::read_string(&mut self.outer.reader)?.unwrap_or_default();
+                visitor.visit_enum(IntoDeserializer::<DecodeErr>::into_deserializer(variant))
+            }
+            _ if self.tag == TAG_COMPOUND => visitor.visit_enum(VariantDecoder::new(self.outer)),
+            _ => Err(DecodeErr::TagMismatch {
+                found: self.tag,
+                expected: TAG_COMPOUND,
+            }),
+        }
+    }
+
+    /// Deserialize NBT arrays, refusing to read lists in their place.
+    ///
+    /// Only the array helpers in [`crate::de`] use these names, and their visitor
+    /// decodes the bytes.
+    fn deserialize_tuple_struct<V>(
+        self,
+        name: &'static str,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        let expected = match name {
+            LIBNBT_I8_ARRAY => TAG_BYTE_ARRAY,
+            LIBNBT_I32_ARRAY => TAG_INT_ARRAY,
+            LIBNBT_I64_ARRAY => TAG_LONG_ARRAY,
+            _ => return self.deserialize_any(visitor),
+        };
+
+        if self.tag != expected {
+            return Err(DecodeErr::TagMismatch {
+                found: self.tag,
+                expected,
+            });
+        }
+
+        // Array helpers take the whole payload as big-endian bytes
+        let reader = &mut self.outer.reader;
+        let bytes = match expected {
+            TAG_BYTE_ARRAY => raw::read_array_bytes::<i8, _>(reader)?,
+            TAG_INT_ARRAY => raw::read_array_bytes::<i32, _>(reader)?,
+            _ => raw::read_array_bytes::<i64, _>(reader)?,
+        };
+        visitor.visit_byte_buf(bytes)
+    }
+
+    /// Skipped values are read past without being decoded.
+    fn deserialize_ignored_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        raw::skip_payload(&mut self.outer.reader, self.tag)?;
+        visitor.visit_unit()
+    }
+
     forward_to_deserialize_any! {
-        u8 u16 u32 u64 i8 i16 i32 i64 f32 f64 char str string bytes byte_buf seq
-        map tuple_struct struct tuple enum identifier ignored_any
+        i8 i16 i32 i64 f32 f64 char str string bytes byte_buf seq
+        map struct tuple identifier
     }
 }
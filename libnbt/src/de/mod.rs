@@ -1,3 +1,46 @@
+//! Serde deserializer for NBT and `deserialize_with` helpers for NBT arrays.
+
+use serde::Deserializer;
+
+use crate::consts::{LIBNBT_I32_ARRAY, LIBNBT_I64_ARRAY, LIBNBT_I8_ARRAY};
+use visit::ArrayVisitor;
+
 pub(crate) mod dec;
 pub(crate) mod visit;
 pub(crate) mod access;
+
+pub use dec::Decoder;
+
+/// Deserializes NBT `TAG_Byte_Array` into any collection of `i8`.
+///
+/// Meant for `#[serde(deserialize_with = "nbt::de::i8_array")]`, NBT lists are
+/// rejected. Pair with [`crate::ser::i8_array`] for writing.
+pub fn i8_array<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: FromIterator<i8>,
+{
+    deserializer.deserialize_tuple_struct(LIBNBT_I8_ARRAY, 0, ArrayVisitor::new())
+}
+
+/// Deserializes NBT `TAG_Int_Array` into any collection of `i32`.
+///
+/// See [`i8_array`] for details.
+pub fn i32_array<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: FromIterator<i32>,
+{
+    deserializer.deserialize_tuple_struct(LIBNBT_I32_ARRAY, 0, ArrayVisitor::new())
+}
+
+/// Deserializes NBT `TAG_Long_Array` into any collection of `i64`.
+///
+/// See [`i8_array`] for details.
+pub fn i64_array<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: FromIterator<i64>,
+{
+    deserializer.deserialize_tuple_struct(LIBNBT_I64_ARRAY, 0, ArrayVisitor::new())
+}
@@ -1,6 +1,11 @@
-use serde::de::Visitor;
+use std::marker::PhantomData;
 
-use crate::{Map, Tag};
+use serde::de::{DeserializeSeed, EnumAccess, Error, VariantAccess, Visitor};
+use serde::{Deserialize, Deserializer};
+
+use crate::array::ArrayElement;
+use crate::consts::*;
+use crate::{ListTag, Map, Tag};
 
 pub(crate) struct TagVisitor;
 
@@ -53,6 +58,13 @@ impl<'de> Visitor<'de> for TagVisitor {
         Ok(Tag::Double(v))
     }
 
+    fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        Ok(Tag::String(v.into()))
+    }
+
     fn visit_string<E>(self, v: String) -> Result<Self::Value, E>
     where
         E: serde::de::Error,
@@ -72,15 +84,158 @@ impl<'de> Visitor<'de> for TagVisitor {
         Ok(Tag::Compound(buf))
     }
 
-    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    fn visit_seq<A>(self, seq: A) -> Result<Self::Value, A::Error>
         where
             A: serde::de::SeqAccess<'de>, {
-        let mut buf = Vec::with_capacity(seq.size_hint().unwrap());
+        ListTagVisitor { element_type: TAG_END }
+            .visit_seq(seq)
+            .map(Tag::List)
+    }
+
+    /// NBT decoder hands out tags as variants keyed by their type id.
+    fn visit_enum<A>(self, data: A) -> Result<Self::Value, A::Error>
+    where
+        A: EnumAccess<'de>,
+    {
+        let (tag_type, variant) = data.variant::<u8>()?;
+
+        match tag_type {
+            TAG_BYTE => variant.newtype_variant().map(Tag::Byte),
+            TAG_SHORT => variant.newtype_variant().map(Tag::Short),
+            TAG_INT => variant.newtype_variant().map(Tag::Int),
+            TAG_LONG => variant.newtype_variant().map(Tag::Long),
+            TAG_FLOAT => variant.newtype_variant().map(Tag::Float),
+            TAG_DOUBLE => variant.newtype_variant().map(Tag::Double),
+            TAG_BYTE_ARRAY => variant
+                .newtype_variant_seed(ArraySeed::new(LIBNBT_I8_ARRAY))
+                .map(Tag::ByteArray),
+            TAG_STRING => variant.newtype_variant().map(Tag::String),
+            TAG_LIST => variant.newtype_variant().map(Tag::List),
+            TAG_COMPOUND => variant.newtype_variant().map(Tag::Compound),
+            TAG_INT_ARRAY => variant
+                .newtype_variant_seed(ArraySeed::new(LIBNBT_I32_ARRAY))
+                .map(Tag::IntArray),
+            TAG_LONG_ARRAY => variant
+                .newtype_variant_seed(ArraySeed::new(LIBNBT_I64_ARRAY))
+                .map(Tag::LongArray),
+            tag_type_id => Err(A::Error::custom(format_args!(
+                "unknown tag type: {:#}",
+                tag_type_id
+            ))),
+        }
+    }
+}
+
+pub(crate) struct ListTagVisitor {
+    pub(crate) element_type: u8,
+}
+
+impl<'de> Visitor<'de> for ListTagVisitor {
+    type Value = ListTag;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(formatter, "a list of tags sharing one type")
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: serde::de::SeqAccess<'de>,
+    {
+        let mut list = ListTag::with_type(self.element_type).map_err(A::Error::custom)?;
 
         while let Some(el) = seq.next_element::<Tag>()? {
+            list.push(el).map_err(A::Error::custom)?;
+        }
+
+        Ok(list)
+    }
+
+    /// NBT decoder hands out lists as a tuple variant keyed by their element type.
+    fn visit_enum<A>(self, data: A) -> Result<Self::Value, A::Error>
+    where
+        A: EnumAccess<'de>,
+    {
+        let (element_type, variant) = data.variant::<u8>()?;
+        variant.tuple_variant(0, ListTagVisitor { element_type })
+    }
+}
+
+/// Visitor collecting NBT array elements of type `E` into `T`.
+///
+/// The NBT decoder hands over the whole payload as big-endian bytes, other formats
+/// visit a sequence.
+pub(crate) struct ArrayVisitor<T, E>(PhantomData<(T, E)>);
+
+impl<T, E> ArrayVisitor<T, E> {
+    pub(crate) fn new() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<'de, T, E> Visitor<'de> for ArrayVisitor<T, E>
+where
+    T: FromIterator<E>,
+    E: Deserialize<'de> + ArrayElement,
+{
+    type Value = T;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(formatter, "an NBT array")
+    }
+
+    fn visit_bytes<Er>(self, v: &[u8]) -> Result<Self::Value, Er>
+    where
+        Er: Error,
+    {
+        Ok(v.chunks_exact(E::SIZE).map(E::from_be_slice).collect())
+    }
+
+    fn visit_byte_buf<Er>(self, v: Vec<u8>) -> Result<Self::Value, Er>
+    where
+        Er: Error,
+    {
+        self.visit_bytes(&v)
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: serde::de::SeqAccess<'de>,
+    {
+        let mut buf = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+
+        while let Some(el) = seq.next_element::<E>()? {
             buf.push(el);
         }
 
-        Ok(buf.into())
+        Ok(buf.into_iter().collect())
+    }
+}
+
+/// Deserializes an NBT array through its magic name, see [`ArrayVisitor`].
+pub(crate) struct ArraySeed<E> {
+    name: &'static str,
+    _elem: PhantomData<E>,
+}
+
+impl<E> ArraySeed<E> {
+    pub(crate) fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            _elem: PhantomData,
+        }
+    }
+}
+
+impl<'de, E> DeserializeSeed<'de> for ArraySeed<E>
+where
+    E: Deserialize<'de> + ArrayElement,
+{
+    type Value = Vec<E>;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_tuple_struct(self.name, 0, ArrayVisitor::new())
     }
 }
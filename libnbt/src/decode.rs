@@ -1,20 +1,69 @@
 use std::io::Read;
 
-use crate::de::dec::Decoder;
-use crate::err::TagDecodeError;
+use serde::de::DeserializeOwned;
+
+use crate::borrow::{CompoundRef, SliceReader};
+use crate::de::Decoder;
+use crate::err::{DecodeResult, TagDecodeError};
 use crate::CompoundTag;
 
-pub fn read_nbt<R: Read>(reader: R) -> Result<CompoundTag, TagDecodeError> {
+/// Deserializes an instance of `T` from uncompressed NBT data.
+///
+/// See the [crate level docs](crate) for how NBT tags map onto Rust types.
+#[inline]
+pub fn from_reader<T, R>(reader: R) -> DecodeResult<T>
+where
+    T: DeserializeOwned,
+    R: Read,
+{
     let mut decoder = Decoder::new(reader);
-    serde::Deserialize::deserialize(&mut decoder)
+    T::deserialize(&mut decoder).map_err(|err| err.at(decoder.offset()))
+}
+
+/// Deserializes an instance of `T` from gzip compressed NBT data.
+#[inline]
+pub fn from_gzip_reader<T, R>(reader: R) -> DecodeResult<T>
+where
+    T: DeserializeOwned,
+    R: Read,
+{
+    from_reader(flate2::read::GzDecoder::new(reader))
+}
+
+/// Deserializes an instance of `T` from zlib compressed NBT data.
+#[inline]
+pub fn from_zlib_reader<T, R>(reader: R) -> DecodeResult<T>
+where
+    T: DeserializeOwned,
+    R: Read,
+{
+    from_reader(flate2::read::ZlibDecoder::new(reader))
+}
+
+/// Deserializes an instance of `T` from uncompressed NBT bytes.
+#[inline]
+pub fn from_slice<T>(data: &[u8]) -> DecodeResult<T>
+where
+    T: DeserializeOwned,
+{
+    from_reader(data)
+}
+
+pub fn read_nbt<R: Read>(reader: R) -> Result<CompoundTag, TagDecodeError> {
+    from_reader(reader)
 }
 
 pub fn read_gzip_nbt<R: Read>(reader: &mut R) -> Result<CompoundTag, TagDecodeError> {
-    let mut greader = flate2::read::GzDecoder::new(reader);
-    read_nbt(&mut greader)
+    from_gzip_reader(reader)
 }
 
 pub fn read_zlib_nbt<R: Read>(reader: &mut R) -> Result<CompoundTag, TagDecodeError> {
-    let mut greader = flate2::read::ZlibDecoder::new(reader);
-    read_nbt(&mut greader)
+    from_zlib_reader(reader)
+}
+
+/// Reads uncompressed NBT without copying strings and arrays out of `data`.
+///
+/// See [`crate::borrow`] for the borrowed tag model.
+pub fn read_nbt_ref(data: &[u8]) -> Result<CompoundRef<'_>, TagDecodeError> {
+    SliceReader::new(data).read_root()
 }
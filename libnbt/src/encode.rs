@@ -5,23 +5,69 @@ use flate2::write::{GzEncoder, ZlibEncoder};
 use flate2::Compression;
 use serde::Serialize;
 
-use crate::err::TagEncodeError;
+use crate::err::{EncodeResult, TagEncodeError};
 use crate::ser::Encoder;
 use crate::CompoundTag;
 
+/// Serializes `value` as uncompressed NBT into `dst`.
+///
+/// `value` must serialize as a struct or map, see the [crate level docs](crate) for
+/// how Rust types map onto NBT.
 #[inline]
-pub fn write_nbt<'a, 'b: 'a, W: Write + Debug>(nbt_root: &'a CompoundTag, dst: W) -> Result<(), TagEncodeError> {
+pub fn to_writer<T, W>(dst: W, value: &T) -> EncodeResult<()>
+where
+    T: ?Sized + Serialize,
+    W: Write + Debug,
+{
     let mut encoder = Encoder::new(dst);
-    nbt_root.serialize(&mut encoder)
+    value.serialize(&mut encoder)
+}
+
+/// Serializes `value` as gzip compressed NBT into `dst`.
+pub fn to_gzip_writer<T, W>(dst: W, value: &T) -> EncodeResult<()>
+where
+    T: ?Sized + Serialize,
+    W: Write + Debug,
+{
+    let mut encoder = Encoder::new(GzEncoder::new(dst, Compression::default()));
+    value.serialize(&mut encoder)?;
+    encoder.into_inner().finish()?;
+    Ok(())
+}
+
+/// Serializes `value` as zlib compressed NBT into `dst`.
+pub fn to_zlib_writer<T, W>(dst: W, value: &T) -> EncodeResult<()>
+where
+    T: ?Sized + Serialize,
+    W: Write + Debug,
+{
+    let mut encoder = Encoder::new(ZlibEncoder::new(dst, Compression::default()));
+    value.serialize(&mut encoder)?;
+    encoder.into_inner().finish()?;
+    Ok(())
+}
+
+/// Serializes `value` as uncompressed NBT into a new buffer.
+#[inline]
+pub fn to_vec<T>(value: &T) -> EncodeResult<Vec<u8>>
+where
+    T: ?Sized + Serialize,
+{
+    let mut buf = Vec::with_capacity(128);
+    to_writer(&mut buf, value)?;
+    Ok(buf)
+}
+
+#[inline]
+pub fn write_nbt<W: Write + Debug>(nbt_root: &CompoundTag, dst: W) -> Result<(), TagEncodeError> {
+    to_writer(dst, nbt_root)
 }
 
 #[inline]
 pub fn write_gzip_nbt<W: Write + Debug>(nbt_root: &CompoundTag, dst: W) -> Result<(), TagEncodeError> {
-    let mut encoder = Encoder::new(GzEncoder::new(dst, Compression::default()));
-    nbt_root.serialize(&mut encoder)
+    to_gzip_writer(dst, nbt_root)
 }
 
 pub fn write_zlib_nbt<W: Write + Debug>(nbt_root: &CompoundTag, dst: W) -> Result<(), TagEncodeError> {
-    let mut encoder = Encoder::new(ZlibEncoder::new(dst, Compression::default()));
-    nbt_root.serialize(&mut encoder)
+    to_zlib_writer(dst, nbt_root)
 }
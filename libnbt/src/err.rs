@@ -1,12 +1,30 @@
 use std::error::Error as StdErr;
 use std::fmt::Display;
 use std::io;
+use std::num::{ParseFloatError, ParseIntError};
 use std::str::Utf8Error;
 use std::string::FromUtf8Error;
 
 pub type DecodeResult<T> = Result<T, TagDecodeError>;
 pub type EncodeResult<T> = Result<T, TagEncodeError>;
 
+/// Tag path of `path` inside the compound entry `key`, e.g. `Inventory[0].id`.
+pub fn join_key(key: &str, path: &str) -> String {
+    match path.chars().next() {
+        None => key.to_string(),
+        Some('[') => format!("{}{}", key, path),
+        Some(_) => format!("{}.{}", key, path),
+    }
+}
+
+/// Tag path of `path` inside the list or array element at `index`, e.g. `[0].id`.
+pub fn join_index(index: usize, path: &str) -> String {
+    match path.chars().next() {
+        None | Some('[') => format!("[{}]{}", index, path),
+        Some(_) => format!("[{}].{}", index, path),
+    }
+}
+
 #[derive(Debug)]
 pub enum TagDecodeError {
     /// Contains element of found tag that isn't of type TAG_Compound
@@ -23,6 +41,17 @@ pub enum TagDecodeError {
         expected: u8,
     },
     NonBooleanByte(i8),
+    /// Negative length of a list, array or string
+    InvalidLength(i32),
+    /// Any of the other errors, with the location it was found at
+    Context {
+        /// Bytes read from the decompressed stream when decoding failed
+        offset: u64,
+        /// Path of the tag being decoded, e.g. `Level.Sections[3].Palette`. Empty for
+        /// the root compound.
+        path: String,
+        source: Box<TagDecodeError>,
+    },
 }
 
 #[derive(Debug)]
@@ -31,11 +60,135 @@ pub enum TagEncodeError {
     Serde(String),
     RootMustBeCompound,
     UnrepresentableType(&'static str),
+    /// Map key that isn't a string, compound keys are
     NonStringMapKey,
+    /// List element of another tag type than the first one
+    TagMismatch {
+        found: u8,
+        expected: u8,
+    },
+}
+
+/// Errors from building a [`ListTag`](crate::ListTag) by hand.
+#[derive(Debug, PartialEq, Eq)]
+pub enum ListTagError {
+    /// Element type id isn't a known NBT tag type
+    UnknownTagType(u8),
+    /// Pushed tag doesn't match the element type of the list
+    TypeMismatch {
+        found: u8,
+        expected: u8,
+    },
+}
+
+/// Text that isn't a float or double, see [`crate::float::parse_float`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FloatTextError {
+    Decimal(ParseFloatError),
+    /// Bits after the `0x` prefix that aren't hex digits or are too many
+    Bits(ParseIntError),
+}
+
+/// Errors from reading typed JSON, see [`crate::json`].
+#[cfg(feature = "json")]
+#[derive(Debug)]
+pub enum JsonError {
+    Json(serde_json::Error),
+    RootMustBeCompound,
+    /// Value that isn't a `{"type": ..., "value": ...}` object
+    NotTyped,
+    /// Type name that isn't an NBT tag type
+    UnknownType(String),
+    /// Value that doesn't fit the type it was given with, holds the type name
+    InvalidValue(&'static str),
+    /// List element of another type than the list
+    List(ListTagError),
+    /// Any of the other errors, with the tag it was found in
+    Context {
+        /// Path of the tag, e.g. `Level.Sections[3].Palette`. Empty for the root
+        /// compound.
+        path: String,
+        source: Box<JsonError>,
+    },
+}
+
+impl TagDecodeError {
+    /// Byte offset in the decompressed stream the error was found at, if known.
+    pub fn offset(&self) -> Option<u64> {
+        match self {
+            Self::Context { offset, .. } => Some(*offset),
+            _ => None,
+        }
+    }
+
+    /// Path of the tag that failed to decode, if known. See [`TagDecodeError::Context`].
+    pub fn path(&self) -> Option<&str> {
+        match self {
+            Self::Context { path, .. } => Some(path),
+            _ => None,
+        }
+    }
+
+    /// The error without its location.
+    pub fn inner(&self) -> &TagDecodeError {
+        match self {
+            Self::Context { source, .. } => source,
+            _ => self,
+        }
+    }
+
+    /// Attaches `offset` and an empty path, unless the error is located already.
+    pub(crate) fn at(self, offset: u64) -> Self {
+        match self {
+            Self::Context { .. } => self,
+            _ => Self::Context {
+                offset,
+                path: String::new(),
+                source: Box::new(self),
+            },
+        }
+    }
+
+    /// Prefixes the path with the compound key the error was found under.
+    pub(crate) fn in_key(self, key: &str, offset: u64) -> Self {
+        self.prefix(offset, |path| join_key(key, path))
+    }
+
+    /// Prefixes the path with the list or array index the error was found at.
+    pub(crate) fn in_index(self, index: usize, offset: u64) -> Self {
+        self.prefix(offset, |path| join_index(index, path))
+    }
+
+    fn prefix(self, offset: u64, prefix: impl FnOnce(&str) -> String) -> Self {
+        match self.at(offset) {
+            Self::Context {
+                offset,
+                path,
+                source,
+            } => Self::Context {
+                offset,
+                path: prefix(&path),
+                source,
+            },
+            _ => unreachable!("located by `at`"),
+        }
+    }
 }
 
 impl Display for TagDecodeError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Self::Context {
+            offset,
+            path,
+            source,
+        } = self
+        {
+            return match path.as_str() {
+                "" => write!(f, "{} (at byte {})", source, offset),
+                path => write!(f, "{} (at byte {}, in `{}`)", source, offset, path),
+            };
+        }
+
         write!(f, "libnbt: ")?;
         match self {
             Self::IOError(io_error) => write!(f, "IO ERROR: {}", io_error),
@@ -49,9 +202,75 @@ impl Display for TagDecodeError {
             Self::TextDecodeErr(txt) => write!(f, "Error while parsing text: {}", txt),
             Self::StringDecodeErr(txt) => write!(f, "Error while parsing text: {}", txt),
             Self::NonBooleanByte(b) => write!(f, "Non boolean byte found: {:x}", b),
+            Self::InvalidLength(len) => write!(f, "Invalid length found: {}", len),
             Self::TagMismatch { found, expected } => {
                 write!(f, "Was exptecting {:x} but found {:x}", expected, found)
             }
+            Self::Context { .. } => unreachable!("handled above"),
+        }
+    }
+}
+
+#[cfg(feature = "json")]
+impl JsonError {
+    /// Path of the tag the error was found in, if known. See [`JsonError::Context`].
+    pub fn path(&self) -> Option<&str> {
+        match self {
+            Self::Context { path, .. } => Some(path),
+            _ => None,
+        }
+    }
+
+    /// The error without its location.
+    pub fn inner(&self) -> &JsonError {
+        match self {
+            Self::Context { source, .. } => source,
+            _ => self,
+        }
+    }
+
+    /// Prefixes the path with the compound key the error was found under.
+    pub(crate) fn in_key(self, key: &str) -> Self {
+        self.prefix(|path| join_key(key, path))
+    }
+
+    /// Prefixes the path with the list or array index the error was found at.
+    pub(crate) fn in_index(self, index: usize) -> Self {
+        self.prefix(|path| join_index(index, path))
+    }
+
+    fn prefix(self, prefix: impl FnOnce(&str) -> String) -> Self {
+        match self {
+            Self::Context { path, source } => Self::Context {
+                path: prefix(&path),
+                source,
+            },
+            _ => Self::Context {
+                path: prefix(""),
+                source: Box::new(self),
+            },
+        }
+    }
+}
+
+#[cfg(feature = "json")]
+impl Display for JsonError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Self::Context { path, source } = self {
+            return write!(f, "{} (in `{}`)", source, path);
+        }
+
+        write!(f, "libnbt: ")?;
+        match self {
+            Self::Json(json_err) => write!(f, "JSON ERROR: {}", json_err),
+            Self::RootMustBeCompound => {
+                write!(f, "The initial TAG must of type TAG_Compound",)
+            }
+            Self::NotTyped => write!(f, "Expected an object with \"type\" and \"value\""),
+            Self::UnknownType(name) => write!(f, "Unknown tag type: {}", name),
+            Self::InvalidValue(name) => write!(f, "Value doesn't fit type: {}", name),
+            Self::List(list_err) => write!(f, "{}", list_err),
+            Self::Context { .. } => unreachable!("handled above"),
         }
     }
 }
@@ -66,7 +285,34 @@ impl Display for TagEncodeError {
                 write!(f, "The initial TAG must of type TAG_Compound",)
             }
             Self::UnrepresentableType(txt) => write!(f, "Found unrepresentable type: {}", txt),
-            Self::NonStringMapKey => todo!(),
+            Self::NonStringMapKey => write!(f, "Compound keys must be strings"),
+            Self::TagMismatch { found, expected } => {
+                write!(f, "List holds {:x} but found {:x}", expected, found)
+            }
+        }
+    }
+}
+
+impl Display for ListTagError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "libnbt: ")?;
+        match self {
+            Self::UnknownTagType(tag_type_id) => {
+                write!(f, "Unknown list element type: {:#}", tag_type_id)
+            }
+            Self::TypeMismatch { found, expected } => {
+                write!(f, "List holds {:x} but found {:x}", expected, found)
+            }
+        }
+    }
+}
+
+impl Display for FloatTextError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "libnbt: ")?;
+        match self {
+            Self::Decimal(float_err) => write!(f, "Invalid float: {}", float_err),
+            Self::Bits(int_err) => write!(f, "Invalid float bits: {}", int_err),
         }
     }
 }
@@ -105,6 +351,7 @@ impl StdErr for TagDecodeError {
     fn source(&self) -> Option<&(dyn StdErr + 'static)> {
         match self {
             Self::IOError(e) => e.source(),
+            Self::Context { source, .. } => Some(source.as_ref()),
             _ => None,
         }
     }
@@ -119,6 +366,38 @@ impl StdErr for TagEncodeError {
     }
 }
 
+impl StdErr for ListTagError {}
+
+impl StdErr for FloatTextError {
+    fn source(&self) -> Option<&(dyn StdErr + 'static)> {
+        match self {
+            Self::Decimal(e) => Some(e),
+            Self::Bits(e) => Some(e),
+        }
+    }
+}
+
+#[cfg(feature = "json")]
+impl StdErr for JsonError {
+    fn source(&self) -> Option<&(dyn StdErr + 'static)> {
+        match self {
+            Self::Json(e) => Some(e),
+            Self::List(e) => Some(e),
+            Self::Context { source, .. } => Some(source.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+impl From<ListTagError> for TagDecodeError {
+    fn from(value: ListTagError) -> Self {
+        match value {
+            ListTagError::UnknownTagType(tag_type_id) => Self::UnknownTagType { tag_type_id },
+            ListTagError::TypeMismatch { found, expected } => Self::TagMismatch { found, expected },
+        }
+    }
+}
+
 impl From<Utf8Error> for TagDecodeError {
     fn from(value: Utf8Error) -> Self {
         Self::TextDecodeErr(value)
@@ -0,0 +1,132 @@
+//! Text forms of `TAG_Float` and `TAG_Double` that read back to the same bits.
+//!
+//! Decimals keep every value but NaNs: text can only tell the usual NaN apart from
+//! numbers, so other NaNs are given as their bits, e.g. `0x7fc00001`. Text formats of
+//! NBT data, like typed JSON, write floats and doubles with these functions.
+
+use std::fmt::Debug;
+use std::num::{ParseFloatError, ParseIntError};
+use std::str::FromStr;
+
+use crate::err::FloatTextError;
+
+/// Prefix marking text as the bit pattern of a float or double.
+pub const BITS_PREFIX: &str = "0x";
+
+/// How [`format_float`] writes floats and doubles.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum FloatFormat {
+    /// Shortest decimal reading back to the same value, e.g. `1.5`, `-0.0`, `1e300`,
+    /// `NaN` or `-Infinity`. NaNs other than the usual one are written as bits.
+    #[default]
+    Decimal,
+    /// Exact bit patterns in hex, `0x` followed by 8 digits for floats and 16 for
+    /// doubles.
+    Bits,
+}
+
+/// `f32` or `f64`, the payload of `TAG_Float` and `TAG_Double`.
+pub trait NbtFloat: Copy + Debug + FromStr<Err = ParseFloatError> + private::Sealed {
+    /// NaN written as plain `NaN`, any other NaN is written as bits.
+    const NAN: Self;
+
+    fn is_nan(self) -> bool;
+    fn is_infinite(self) -> bool;
+    fn is_sign_negative(self) -> bool;
+    fn same_bits(self, other: Self) -> bool;
+    fn to_hex(self) -> String;
+    fn from_hex(hex: &str) -> Result<Self, ParseIntError>;
+}
+
+macro_rules! impl_nbt_float {
+    ($float:ty, $bits:ty, $width:literal) => {
+        impl NbtFloat for $float {
+            const NAN: Self = <$float>::NAN;
+
+            #[inline]
+            fn is_nan(self) -> bool {
+                <$float>::is_nan(self)
+            }
+
+            #[inline]
+            fn is_infinite(self) -> bool {
+                <$float>::is_infinite(self)
+            }
+
+            #[inline]
+            fn is_sign_negative(self) -> bool {
+                <$float>::is_sign_negative(self)
+            }
+
+            #[inline]
+            fn same_bits(self, other: Self) -> bool {
+                self.to_bits() == other.to_bits()
+            }
+
+            #[inline]
+            fn to_hex(self) -> String {
+                format!("{}{:0width$x}", BITS_PREFIX, self.to_bits(), width = $width)
+            }
+
+            #[inline]
+            fn from_hex(hex: &str) -> Result<Self, ParseIntError> {
+                <$bits>::from_str_radix(hex, 16).map(<$float>::from_bits)
+            }
+        }
+    };
+}
+
+impl_nbt_float!(f32, u32, 8);
+impl_nbt_float!(f64, u64, 16);
+
+mod private {
+    pub trait Sealed {}
+
+    impl Sealed for f32 {}
+    impl Sealed for f64 {}
+}
+
+/// Bits of `value` as text if it's a NaN other than the usual one, which no decimal
+/// reads back to.
+#[inline]
+pub fn nan_bits<F: NbtFloat>(value: F) -> Option<String> {
+    match value.is_nan() && !value.same_bits(F::NAN) {
+        true => Some(value.to_hex()),
+        false => None,
+    }
+}
+
+/// Formats `value` so that [`parse_float`] returns the exact same bits.
+pub fn format_float<F: NbtFloat>(value: F, format: FloatFormat) -> String {
+    match format {
+        FloatFormat::Bits => value.to_hex(),
+        FloatFormat::Decimal if value.is_nan() => nan_bits(value).unwrap_or_else(|| "NaN".to_string()),
+        FloatFormat::Decimal if value.is_infinite() => match value.is_sign_negative() {
+            true => "-Infinity".to_string(),
+            false => "Infinity".to_string(),
+        },
+        // Debug output is the shortest text parsing back to the same value
+        FloatFormat::Decimal => format!("{:?}", value),
+    }
+}
+
+/// Parses decimals, `NaN`, `Infinity` and bit patterns written by [`format_float`].
+/// Decimals are read like [`f32::from_str`], so `inf` and `-inf` are taken as well.
+pub fn parse_float<F: NbtFloat>(text: &str) -> Result<F, FloatTextError> {
+    match text.strip_prefix(BITS_PREFIX) {
+        Some(hex) => F::from_hex(hex).map_err(FloatTextError::Bits),
+        None => text.parse().map_err(FloatTextError::Decimal),
+    }
+}
+
+/// `value` as a double, the shortest decimal if it reads back to the same float.
+///
+/// For formats whose numbers are all doubles, `0.1f32` is written as `0.1` instead of
+/// `0.10000000149011612`.
+pub fn short_float(value: f32) -> f64 {
+    let short: f64 = format!("{:?}", value).parse().unwrap_or(value as f64);
+    match (short as f32).to_bits() == value.to_bits() {
+        true => short,
+        false => value as f64,
+    }
+}
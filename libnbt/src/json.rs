@@ -0,0 +1,259 @@
+//! JSON forms of NBT data, behind the `json` feature.
+//!
+//! Two modes are available, see [`JsonMode`]:
+//!
+//! - **typed** keeps every tag type, so it can be turned back into the exact same NBT.
+//!   Each tag is an object giving its type along with its value:
+//!
+//!   ```json
+//!   {"type": "compound", "value": {
+//!       "HP": {"type": "short", "value": 20},
+//!       "Pos": {"type": "list", "element_type": "double", "value": [
+//!           {"type": "double", "value": 1.5}
+//!       ]},
+//!       "UUID": {"type": "int_array", "value": [1, 2, 3, 4]}
+//!   }}
+//!   ```
+//!
+//!   Floats and doubles that JSON numbers can't hold are strings, as
+//!   [`format_float`](crate::float::format_float) writes them: `"NaN"`, `"Infinity"`,
+//!   `"-Infinity"`, or `"0x"` followed by the bits in hex for NaNs other than the usual
+//!   one.
+//! - **plain** maps compounds onto objects, lists and arrays onto arrays and every
+//!   number onto a JSON number. Types are lost and non finite floats become `null`,
+//!   it is meant to be read by other programs, not turned back into NBT.
+
+use std::io::{Read, Write};
+
+use serde_json::{Map, Value};
+
+use crate::consts::*;
+use crate::err::JsonError;
+use crate::float::{format_float, parse_float, short_float, FloatFormat};
+use crate::{CompoundTag, ListTag, Tag};
+
+/// Type names used by the typed mode, indexed by tag type id.
+const TYPE_NAMES: [&str; 13] = [
+    "end",
+    "byte",
+    "short",
+    "int",
+    "long",
+    "float",
+    "double",
+    "byte_array",
+    "string",
+    "list",
+    "compound",
+    "int_array",
+    "long_array",
+];
+
+const TYPE_KEY: &str = "type";
+const VALUE_KEY: &str = "value";
+const ELEMENT_TYPE_KEY: &str = "element_type";
+
+/// How NBT data is written as JSON.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum JsonMode {
+    /// Every tag along with its type, reads back to the same NBT
+    #[default]
+    Typed,
+    /// Natural JSON without tag types
+    Plain,
+}
+
+/// Converts `root` into JSON.
+pub fn to_value(root: &CompoundTag, mode: JsonMode) -> Value {
+    let root = compound(root.iter(), mode);
+    match mode {
+        JsonMode::Typed => typed(TAG_COMPOUND, root),
+        JsonMode::Plain => root,
+    }
+}
+
+/// Writes `root` as indented JSON.
+pub fn to_writer<W: Write>(writer: W, root: &CompoundTag, mode: JsonMode) -> Result<(), JsonError> {
+    serde_json::to_writer_pretty(writer, &to_value(root, mode)).map_err(JsonError::Json)
+}
+
+/// Reads a compound from typed JSON, as written in [`JsonMode::Typed`].
+///
+/// # Errors
+///
+/// Errors about a tag are wrapped in [`JsonError::Context`], giving the path of the
+/// tag, e.g. `Level.Sections[3].Palette`.
+pub fn from_value(value: &Value) -> Result<CompoundTag, JsonError> {
+    match tag_from(value)? {
+        Tag::Compound(map) => Ok(CompoundTag::with(map)),
+        _ => Err(JsonError::RootMustBeCompound),
+    }
+}
+
+/// Reads a compound from typed JSON, see [`from_value`].
+pub fn from_reader<R: Read>(reader: R) -> Result<CompoundTag, JsonError> {
+    let value: Value = serde_json::from_reader(reader).map_err(JsonError::Json)?;
+    from_value(&value)
+}
+
+fn tag_to(tag: &Tag, mode: JsonMode) -> Value {
+    let value = match tag {
+        Tag::Byte(v) => Value::from(*v),
+        Tag::Short(v) => Value::from(*v),
+        Tag::Int(v) => Value::from(*v),
+        Tag::Long(v) => Value::from(*v),
+        Tag::Float(v) => float_to(*v, mode),
+        Tag::Double(v) => double_to(*v, mode),
+        Tag::String(v) => Value::from(v.as_ref()),
+        Tag::ByteArray(v) => Value::from(v.as_slice()),
+        Tag::IntArray(v) => Value::from(v.as_slice()),
+        Tag::LongArray(v) => Value::from(v.as_slice()),
+        Tag::List(list) => {
+            let elements = list.into_iter().map(|tag| tag_to(tag, mode)).collect();
+            if mode == JsonMode::Typed {
+                // Empty lists have no element to give their type away
+                return list_typed(list, Value::Array(elements));
+            }
+            Value::Array(elements)
+        }
+        Tag::Compound(map) => compound(map.iter(), mode),
+    };
+
+    match mode {
+        JsonMode::Typed => typed(tag.tag_type(), value),
+        JsonMode::Plain => value,
+    }
+}
+
+fn compound<'a>(entries: impl Iterator<Item = (&'a Box<str>, &'a Tag)>, mode: JsonMode) -> Value {
+    let map = entries
+        .map(|(key, tag)| (key.to_string(), tag_to(tag, mode)))
+        .collect::<Map<_, _>>();
+    Value::Object(map)
+}
+
+fn typed(tag_type: u8, value: Value) -> Value {
+    let mut map = Map::with_capacity(2);
+    map.insert(TYPE_KEY.into(), TYPE_NAMES[tag_type as usize].into());
+    map.insert(VALUE_KEY.into(), value);
+    Value::Object(map)
+}
+
+fn list_typed(list: &ListTag, elements: Value) -> Value {
+    let mut map = Map::with_capacity(3);
+    map.insert(TYPE_KEY.into(), TYPE_NAMES[TAG_LIST as usize].into());
+    map.insert(
+        ELEMENT_TYPE_KEY.into(),
+        TYPE_NAMES[list.element_type() as usize].into(),
+    );
+    map.insert(VALUE_KEY.into(), elements);
+    Value::Object(map)
+}
+
+fn float_to(value: f32, mode: JsonMode) -> Value {
+    match mode {
+        // JSON numbers are doubles, prefer the shortest decimal if it reads back exactly
+        _ if value.is_finite() => Value::from(short_float(value)),
+        JsonMode::Plain => Value::Null,
+        JsonMode::Typed => format_float(value, FloatFormat::Decimal).into(),
+    }
+}
+
+fn double_to(value: f64, mode: JsonMode) -> Value {
+    match mode {
+        _ if value.is_finite() => Value::from(value),
+        JsonMode::Plain => Value::Null,
+        JsonMode::Typed => format_float(value, FloatFormat::Decimal).into(),
+    }
+}
+
+fn tag_from(value: &Value) -> Result<Tag, JsonError> {
+    let object = value.as_object().ok_or(JsonError::NotTyped)?;
+    let type_name = object
+        .get(TYPE_KEY)
+        .and_then(Value::as_str)
+        .ok_or(JsonError::NotTyped)?;
+    let value = object.get(VALUE_KEY).ok_or(JsonError::NotTyped)?;
+
+    let tag_type = type_id(type_name).ok_or_else(|| JsonError::UnknownType(type_name.to_string()))?;
+    let type_name = TYPE_NAMES[tag_type as usize];
+    let invalid = || JsonError::InvalidValue(type_name);
+
+    let tag = match tag_type {
+        TAG_BYTE => Tag::Byte(int_from(value).ok_or_else(invalid)?),
+        TAG_SHORT => Tag::Short(int_from(value).ok_or_else(invalid)?),
+        TAG_INT => Tag::Int(int_from(value).ok_or_else(invalid)?),
+        TAG_LONG => Tag::Long(int_from(value).ok_or_else(invalid)?),
+        TAG_FLOAT => Tag::Float(float_from(value).ok_or_else(invalid)?),
+        TAG_DOUBLE => Tag::Double(double_from(value).ok_or_else(invalid)?),
+        TAG_STRING => Tag::String(value.as_str().ok_or_else(invalid)?.into()),
+        TAG_BYTE_ARRAY => Tag::ByteArray(array_from(value, type_name)?),
+        TAG_INT_ARRAY => Tag::IntArray(array_from(value, type_name)?),
+        TAG_LONG_ARRAY => Tag::LongArray(array_from(value, type_name)?),
+        TAG_LIST => {
+            let mut list = match object.get(ELEMENT_TYPE_KEY).map(|v| v.as_str()) {
+                // Lists written without elements have no type
+                Some(Some("end")) | None => ListTag::new(),
+                Some(Some(name)) => {
+                    let id = type_id(name).ok_or_else(|| JsonError::UnknownType(name.to_string()))?;
+                    ListTag::with_type(id).map_err(JsonError::List)?
+                }
+                Some(None) => return Err(invalid()),
+            };
+            let elements = value.as_array().ok_or_else(invalid)?;
+            for (index, element) in elements.iter().enumerate() {
+                tag_from(element)
+                    .and_then(|tag| list.push(tag).map_err(JsonError::List))
+                    .map_err(|err| err.in_index(index))?;
+            }
+            Tag::List(list)
+        }
+        _ => {
+            let entries = value.as_object().ok_or_else(invalid)?;
+            let mut map = crate::Map::with_capacity(entries.len());
+            for (key, entry) in entries {
+                let tag = tag_from(entry).map_err(|err| err.in_key(key))?;
+                map.insert(key.as_str().into(), tag);
+            }
+            Tag::Compound(map)
+        }
+    };
+
+    Ok(tag)
+}
+
+/// Tag type id of a type name, `end` isn't a valid tag type.
+fn type_id(name: &str) -> Option<u8> {
+    (TAG_BYTE..=TAG_LONG_ARRAY).find(|id| TYPE_NAMES[*id as usize] == name)
+}
+
+fn int_from<T: TryFrom<i64>>(value: &Value) -> Option<T> {
+    value.as_i64().and_then(|v| T::try_from(v).ok())
+}
+
+fn array_from<T: TryFrom<i64>>(value: &Value, type_name: &'static str) -> Result<Vec<T>, JsonError> {
+    let elements = value.as_array().ok_or(JsonError::InvalidValue(type_name))?;
+    elements
+        .iter()
+        .enumerate()
+        .map(|(index, element)| {
+            int_from(element).ok_or_else(|| JsonError::InvalidValue(type_name).in_index(index))
+        })
+        .collect()
+}
+
+fn float_from(value: &Value) -> Option<f32> {
+    match value {
+        Value::Number(number) => number.as_f64().map(|v| v as f32),
+        Value::String(text) => parse_float(text).ok(),
+        _ => None,
+    }
+}
+
+fn double_from(value: &Value) -> Option<f64> {
+    match value {
+        Value::Number(number) => number.as_f64(),
+        Value::String(text) => parse_float(text).ok(),
+        _ => None,
+    }
+}
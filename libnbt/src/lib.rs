@@ -1,21 +1,93 @@
+//! Minecraft NBT (Named Binary Tag) reading and writing.
+//!
+//! Data can be handled through the dynamic [`CompoundTag`]/[`Tag`] model, or with any
+//! type implementing serde's `Serialize`/`Deserialize`. The `json` feature adds
+//! [`json`], converting compounds to and from JSON.
+//!
+//! # Serde data model
+//!
+//! Rust types are mapped onto NBT tags as follows:
+//!
+//! | Rust                                   | NBT                                  |
+//! |----------------------------------------|--------------------------------------|
+//! | `bool`                                 | `TAG_Byte` (`0` or `1`)              |
+//! | `i8`                                   | `TAG_Byte`                           |
+//! | `i16`                                  | `TAG_Short`                          |
+//! | `i32`                                  | `TAG_Int`                            |
+//! | `i64`                                  | `TAG_Long`                           |
+//! | `f32`                                  | `TAG_Float`                          |
+//! | `f64`                                  | `TAG_Double`                         |
+//! | `String`, `&str`, `Box<str>`           | `TAG_String`                         |
+//! | `Vec<T>`, slices, other sequences      | `TAG_List` of the tag for `T`        |
+//! | [`ByteArray`], [`ser::i8_array`]       | `TAG_Byte_Array`                     |
+//! | [`IntArray`], [`ser::i32_array`]       | `TAG_Int_Array`                      |
+//! | [`LongArray`], [`ser::i64_array`]      | `TAG_Long_Array`                     |
+//! | structs, maps with string keys         | `TAG_Compound`                       |
+//! | newtype structs                        | the tag of the wrapped value         |
+//! | unit enum variants                     | `TAG_String` holding the variant name|
+//! | newtype/tuple/struct enum variants     | `TAG_Compound` with the variant name as its only key |
+//! | `Option<T>` holding `Some`             | the tag of `T`                       |
+//! | `Option<T>` holding `None`             | key left out of the compound         |
+//!
+//! The root value must be a struct or map, as NBT data always starts with a
+//! `TAG_Compound`. Enums are externally tagged, a tuple variant is stored as a list so
+//! its fields must share one type. The elements of any list must, unit variants are
+//! strings and other variants compounds, so a `Vec` can't mix them. Lists that do fail
+//! with [`TagEncodeError::TagMismatch`](err::TagEncodeError::TagMismatch).
+//! `None` can't be stored inside lists or as the value of a newtype variant, and neither
+//! `None` there nor tuples have an NBT counterpart, both fail with
+//! [`TagEncodeError::UnrepresentableType`](err::TagEncodeError::UnrepresentableType).
+//!
+//! Unsigned integers and `char` fail the same way, unless opted into with
+//! [`Encoder::map_unsigned`] and [`Decoder::map_unsigned`]. Then `u8`, `u16`, `u32`
+//! and `u64` are stored as `TAG_Byte`, `TAG_Short`, `TAG_Int` and `TAG_Long` with the
+//! same bits, and `char` as `TAG_String`.
+//!
+//! Sequences of `i8`, `i32` and `i64` are lists by default. Use the array wrappers, or
+//! the `serialize_with`/`deserialize_with` helpers, to get NBT arrays instead:
+//!
+//! ```
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct Section {
+//!     #[serde(rename = "BlockStates")]
+//!     block_states: nbt::LongArray,
+//!     #[serde(serialize_with = "nbt::ser::i8_array", deserialize_with = "nbt::de::i8_array")]
+//!     light: Vec<i8>,
+//! }
+//! ```
+
 use core::fmt::Display;
 
 use indexmap::map::Iter;
 use indexmap::IndexMap;
 use serde::{Deserialize, Serialize};
 
-use crate::consts::tag_name;
-use crate::err::TagDecodeError;
+use crate::err::{ListTagError, TagDecodeError};
 
-mod consts;
-mod de;
+mod array;
+pub mod borrow;
+pub mod consts;
+pub mod de;
 pub mod decode;
 pub mod encode;
 pub mod err;
+pub mod float;
+#[cfg(feature = "json")]
+pub mod json;
 #[macro_use]
 mod macros;
 mod raw;
-mod ser;
+pub mod ser;
+pub mod stream;
+pub mod tree;
+
+pub use array::{ByteArray, IntArray, LongArray};
+pub use de::Decoder;
+pub use decode::{from_gzip_reader, from_reader, from_slice, from_zlib_reader};
+pub use encode::{to_gzip_writer, to_vec, to_writer, to_zlib_writer};
+pub use ser::Encoder;
 
 pub type Map = IndexMap<Box<str>, Tag>;
 
@@ -28,11 +100,14 @@ pub enum Tag {
     Long(i64),
     Float(f32),
     Double(f64),
+    #[serde(serialize_with = "ser::bulk_array")]
     ByteArray(Vec<i8>),
     String(Box<str>),
-    List(Vec<Self>),
+    List(ListTag),
     Compound(Map),
+    #[serde(serialize_with = "ser::bulk_array")]
     IntArray(Vec<i32>),
+    #[serde(serialize_with = "ser::bulk_array")]
     LongArray(Vec<i64>),
 }
 
@@ -55,44 +130,23 @@ impl Tag {
             ttype => Err(TagDecodeError::UnknownTagType { tag_type_id: ttype }),
         }
     }
-}
 
-impl From<Vec<Tag>> for Tag {
-    fn from(value: Vec<Tag>) -> Self {
-        let tag = match value.get(0) {
-            Some(t) => t,
-            _ => return Self::List(vec![]),
-        };
-
-        match tag {
-            Self::Byte(_) => Self::ByteArray(
-                value
-                    .into_iter()
-                    .map(|val| match val {
-                        Self::Byte(v) => v,
-                        _ => unreachable!(),
-                    })
-                    .collect(),
-            ),
-            Self::Int(_) => Self::IntArray(
-                value
-                    .into_iter()
-                    .map(|val| match val {
-                        Self::Int(v) => v,
-                        _ => unreachable!(),
-                    })
-                    .collect(),
-            ),
-            Self::Long(_) => Self::LongArray(
-                value
-                    .into_iter()
-                    .map(|val| match val {
-                        Self::Long(v) => v,
-                        _ => unreachable!(),
-                    })
-                    .collect(),
-            ),
-            _ => Tag::List(value)
+    /// Returns the NBT type id of this tag, as found in [`consts`].
+    pub fn tag_type(&self) -> u8 {
+        use crate::consts::*;
+        match self {
+            Self::Byte(_) => TAG_BYTE,
+            Self::Short(_) => TAG_SHORT,
+            Self::Int(_) => TAG_INT,
+            Self::Long(_) => TAG_LONG,
+            Self::Float(_) => TAG_FLOAT,
+            Self::Double(_) => TAG_DOUBLE,
+            Self::ByteArray(_) => TAG_BYTE_ARRAY,
+            Self::IntArray(_) => TAG_INT_ARRAY,
+            Self::LongArray(_) => TAG_LONG_ARRAY,
+            Self::String(_) => TAG_STRING,
+            Self::List(_) => TAG_LIST,
+            Self::Compound(_) => TAG_COMPOUND,
         }
     }
 }
@@ -100,24 +154,198 @@ impl From<Vec<Tag>> for Tag {
 impl Display for Tag {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         use crate::consts::*;
-        match self {
-            Self::Byte(_) => write!(f, "{}", tag_name![TAG_BYTE]),
-            Self::Short(_) => write!(f, "{}", tag_name![TAG_SHORT]),
-            Self::Int(_) => write!(f, "{}", tag_name![TAG_INT]),
-            Self::Long(_) => write!(f, "{}", tag_name![TAG_LONG]),
-            Self::Float(_) => write!(f, "{}", tag_name![TAG_FLOAT]),
-            Self::Double(_) => write!(f, "{}", tag_name![TAG_DOUBLE]),
-            Self::ByteArray(_) => write!(f, "{}", tag_name![TAG_BYTE_ARRAY]),
-            Self::IntArray(_) => write!(f, "{}", tag_name![TAG_INT_ARRAY]),
-            Self::LongArray(_) => write!(f, "{}", tag_name![TAG_LONG_ARRAY]),
-            Self::String(_) => write!(f, "{}", tag_name![TAG_STRING]),
-            Self::List(_) => write!(f, "{}", tag_name![TAG_LIST]),
-            Self::Compound(_) => write!(f, "{}", tag_name![TAG_COMPOUND]),
+        write!(f, "{}", tag_name![self.tag_type()])
+    }
+}
+
+/// Homogeneous NBT list.
+///
+/// Keeps track of its element type, so that every element has the same type and
+/// empty lists are written with the right type id.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ListTag {
+    element_type: u8,
+    elements: Vec<Tag>,
+}
+
+impl ListTag {
+    /// Creates an empty list, element type is taken from the first pushed tag.
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            element_type: consts::TAG_END,
+            elements: Vec::new(),
+        }
+    }
+
+    /// Creates an empty list that only accepts tags of `element_type`.
+    pub fn with_type(element_type: u8) -> Result<Self, ListTagError> {
+        if element_type > consts::TAG_LONG_ARRAY {
+            return Err(ListTagError::UnknownTagType(element_type));
+        }
+
+        Ok(Self {
+            element_type,
+            elements: Vec::new(),
+        })
+    }
+
+    /// Appends `tag` to the list, unless its type differs from the list's element type.
+    pub fn push(&mut self, tag: Tag) -> Result<(), ListTagError> {
+        self.insert(self.elements.len(), tag)
+    }
+
+    #[inline]
+    pub fn element_type(&self) -> u8 {
+        self.element_type
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.elements.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.elements.is_empty()
+    }
+
+    pub fn get(&self, index: usize) -> Option<&Tag> {
+        self.elements.get(index)
+    }
+
+    /// Changes the element at `index` in place through `f`, e.g. the entries of a
+    /// compound element. `None` if there is no such element.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `f` leaves a tag of another type than the list holds, use
+    /// [`ListTag::set`] to replace elements.
+    pub fn update<R>(&mut self, index: usize, f: impl FnOnce(&mut Tag) -> R) -> Option<R> {
+        let element = self.elements.get_mut(index)?;
+        let res = f(element);
+        assert_eq!(element.tag_type(), self.element_type, "list element changed its type");
+        Some(res)
+    }
+
+    /// Replaces the element at `index`, returning the old one.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn set(&mut self, index: usize, tag: Tag) -> Result<Tag, ListTagError> {
+        self.check_type(&tag)?;
+        Ok(std::mem::replace(&mut self.elements[index], tag))
+    }
+
+    /// Inserts `tag` at `index`, shifting the elements after it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index > len`.
+    pub fn insert(&mut self, index: usize, tag: Tag) -> Result<(), ListTagError> {
+        self.check_type(&tag)?;
+        self.element_type = tag.tag_type();
+
+        self.elements.insert(index, tag);
+        Ok(())
+    }
+
+    /// Removes the element at `index`. The list keeps its element type when emptied.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn remove(&mut self, index: usize) -> Tag {
+        self.elements.remove(index)
+    }
+
+    /// Swaps two elements.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `a` or `b` are out of bounds.
+    #[inline]
+    pub fn swap(&mut self, a: usize, b: usize) {
+        self.elements.swap(a, b)
+    }
+
+    /// Errors if `tag` can't be put in the list.
+    fn check_type(&self, tag: &Tag) -> Result<(), ListTagError> {
+        let tag_type = tag.tag_type();
+        if self.element_type == consts::TAG_END && self.elements.is_empty() {
+            return Ok(());
+        }
+        if self.element_type != tag_type {
+            return Err(ListTagError::TypeMismatch {
+                found: tag_type,
+                expected: self.element_type,
+            });
         }
+
+        Ok(())
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, Tag> {
+        self.elements.iter()
+    }
+
+    #[inline]
+    pub fn as_slice(&self) -> &[Tag] {
+        &self.elements
     }
 }
 
-#[derive(Default, Debug, Clone)]
+impl Default for ListTag {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TryFrom<Vec<Tag>> for ListTag {
+    type Error = ListTagError;
+
+    fn try_from(value: Vec<Tag>) -> Result<Self, Self::Error> {
+        let element_type = value.first().map_or(consts::TAG_END, Tag::tag_type);
+        if let Some(tag) = value.iter().find(|tag| tag.tag_type() != element_type) {
+            return Err(ListTagError::TypeMismatch {
+                found: tag.tag_type(),
+                expected: element_type,
+            });
+        }
+
+        Ok(Self {
+            element_type,
+            elements: value,
+        })
+    }
+}
+
+impl From<ListTag> for Vec<Tag> {
+    fn from(value: ListTag) -> Self {
+        value.elements
+    }
+}
+
+impl<'a> IntoIterator for &'a ListTag {
+    type Item = &'a Tag;
+    type IntoIter = std::slice::Iter<'a, Tag>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.elements.iter()
+    }
+}
+
+impl IntoIterator for ListTag {
+    type Item = Tag;
+    type IntoIter = std::vec::IntoIter<Tag>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.elements.into_iter()
+    }
+}
+
+#[derive(Default, Debug, Clone, PartialEq)]
 pub struct CompoundTag {
     pub name: Option<Box<str>>,
     pub(crate) map: Map,
@@ -150,13 +378,38 @@ impl CompoundTag {
         self.map.insert(key, value);
     }
 
-    pub fn iter(&self) -> Iter<Box<str>, Tag> {
+    pub fn iter(&self) -> Iter<'_, Box<str>, Tag> {
         self.map.iter()
     }
 
     pub fn get(&self, key: &str) -> Option<&Tag> {
         self.map.get(key)
     }
+
+    pub fn get_mut(&mut self, key: &str) -> Option<&mut Tag> {
+        self.map.get_mut(key)
+    }
+
+    /// Removes the entry with `key`, keeping the order of the others.
+    pub fn remove(&mut self, key: &str) -> Option<Tag> {
+        self.map.shift_remove(key)
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// Takes the entries out of the compound, dropping its name.
+    #[inline]
+    pub fn into_map(self) -> Map {
+        self.map
+    }
 }
 
 impl Serialize for CompoundTag {
@@ -189,6 +442,59 @@ impl<'de> Deserialize<'de> for Tag {
     where
         D: serde::Deserializer<'de>,
     {
-        deserializer.deserialize_any(crate::de::visit::TagVisitor)
+        use crate::de::visit::TagVisitor;
+
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_any(TagVisitor)
+        } else {
+            deserializer.deserialize_enum(consts::LIBNBT_TAG, &[], TagVisitor)
+        }
+    }
+}
+
+impl Serialize for ListTag {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        use serde::ser::{SerializeSeq, SerializeTupleVariant};
+
+        if serializer.is_human_readable() {
+            let mut state = serializer.serialize_seq(Some(self.len()))?;
+            for tag in &self.elements {
+                state.serialize_element(tag)?;
+            }
+            return state.end();
+        }
+
+        // Element type travels as the variant index, so empty lists keep it too.
+        let mut state = serializer.serialize_tuple_variant(
+            consts::LIBNBT_LIST,
+            self.element_type as u32,
+            consts::LIBNBT_LIST,
+            self.len(),
+        )?;
+        for tag in &self.elements {
+            state.serialize_field(tag)?;
+        }
+        state.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for ListTag {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        use crate::de::visit::ListTagVisitor;
+
+        let visitor = ListTagVisitor {
+            element_type: consts::TAG_END,
+        };
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_seq(visitor)
+        } else {
+            deserializer.deserialize_enum(consts::LIBNBT_LIST, &[], visitor)
+        }
     }
 }
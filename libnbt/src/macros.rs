@@ -14,8 +14,8 @@ macro_rules! return_expr_for_serialized_types_method {
     };
     ($rtrn:expr, $func:ident($($arg:ty),*), where: $where:path) => {
         #[inline]
-        fn $func<__T: ?Sized>(self, $(_: $arg,)*) -> ::std::result::Result<Self::Ok, Self::Error>
-        where __T: $where
+        fn $func<__T>(self, $(_: $arg,)*) -> ::std::result::Result<Self::Ok, Self::Error>
+        where __T: ?Sized + $where
         {
             $rtrn
         }
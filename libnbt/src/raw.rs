@@ -1,8 +1,44 @@
 use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
+use std::borrow::Cow;
 use std::io::{self, Read, Write};
+use std::str::Utf8Error;
 
+use crate::array::ArrayElement;
 use crate::err::{TagDecodeError, TagEncodeError};
-use crate::consts::TAG_END;
+use crate::consts::*;
+
+/// Reader keeping count of the bytes read through it, for error offsets.
+#[derive(Debug)]
+pub struct CountingReader<R> {
+    inner: R,
+    position: u64,
+}
+
+impl<R> CountingReader<R> {
+    #[inline]
+    pub fn new(inner: R) -> Self {
+        Self { inner, position: 0 }
+    }
+
+    #[inline]
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+
+    #[inline]
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R: Read> Read for CountingReader<R> {
+    #[inline]
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        self.position += read as u64;
+        Ok(read)
+    }
+}
 
 #[inline]
 pub fn read_header<R: Read>(src: &mut R) -> Result<(u8, Option<Box<str>>), TagDecodeError> {
@@ -52,7 +88,7 @@ pub fn read_double<R: Read>(src: &mut R) -> io::Result<f64> {
 
 #[inline]
 pub fn read_string<R: Read>(src: &mut R) -> Result<Option<String>, TagDecodeError> {
-    let len = src.read_i16::<BigEndian>()? as usize;
+    let len = src.read_u16::<BigEndian>()? as usize;
 
     if len == 0 {
         return Ok(None);
@@ -60,7 +96,156 @@ pub fn read_string<R: Read>(src: &mut R) -> Result<Option<String>, TagDecodeErro
 
     let mut buf = vec![0; len];
     src.read_exact(&mut buf)?;
-    Ok(Some(String::from_utf8(buf)?))
+    match String::from_utf8(buf) {
+        Ok(text) => Ok(Some(text)),
+        Err(err) => match decode_mutf8(err.as_bytes()) {
+            Some(text) => Ok(Some(text)),
+            None => Err(err.into()),
+        },
+    }
+}
+
+/// Decodes NBT string bytes, borrowing them when they are plain UTF-8.
+///
+/// Java writes strings as modified UTF-8, which only differs from UTF-8 for `\0`
+/// and characters outside the BMP. Those are converted into an owned string.
+#[inline]
+pub fn decode_str(bytes: &[u8]) -> Result<Cow<'_, str>, Utf8Error> {
+    match std::str::from_utf8(bytes) {
+        Ok(text) => Ok(Cow::Borrowed(text)),
+        Err(err) => decode_mutf8(bytes).map(Cow::Owned).ok_or(err),
+    }
+}
+
+/// Decodes Java's modified UTF-8, `None` when `bytes` are malformed.
+fn decode_mutf8(bytes: &[u8]) -> Option<String> {
+    let mut units: Vec<u16> = Vec::with_capacity(bytes.len());
+    let mut iter = bytes.iter().copied();
+
+    while let Some(byte) = iter.next() {
+        let unit = match byte {
+            0x00..=0x7F => byte as u16,
+            0xC0..=0xDF => {
+                let b2 = iter.next().filter(|b| b & 0xC0 == 0x80)?;
+                ((byte as u16 & 0x1F) << 6) | (b2 as u16 & 0x3F)
+            }
+            0xE0..=0xEF => {
+                let b2 = iter.next().filter(|b| b & 0xC0 == 0x80)?;
+                let b3 = iter.next().filter(|b| b & 0xC0 == 0x80)?;
+                ((byte as u16 & 0x0F) << 12) | ((b2 as u16 & 0x3F) << 6) | (b3 as u16 & 0x3F)
+            }
+            _ => return None,
+        };
+        units.push(unit);
+    }
+
+    String::from_utf16(&units).ok()
+}
+
+/// Most bytes reserved up front for an array payload. Larger arrays grow as data arrives,
+/// so a corrupt length can't trigger a huge allocation.
+const ARRAY_PREALLOC: u64 = 1 << 20;
+
+/// Reads the length prefixed payload of an array of `T` in one go.
+///
+/// Elements are left as big-endian bytes.
+pub fn read_array_bytes<T: ArrayElement, R: Read>(src: &mut R) -> Result<Vec<u8>, TagDecodeError> {
+    let size = read_length(src)? * T::SIZE as u64;
+    let mut buf = Vec::with_capacity(size.min(ARRAY_PREALLOC) as usize);
+    src.take(size).read_to_end(&mut buf)?;
+    if (buf.len() as u64) < size {
+        return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+    }
+
+    Ok(buf)
+}
+
+/// Reads past the payload of `tag` without decoding it.
+///
+/// Only type ids and lengths are read, everything else is discarded in bulk.
+pub fn skip_payload<R: Read>(src: &mut R, tag: u8) -> Result<(), TagDecodeError> {
+    if let Some(size) = payload_size(tag) {
+        return skip(src, size);
+    }
+
+    match tag {
+        TAG_STRING => {
+            let len = src.read_u16::<BigEndian>()?;
+            skip(src, len as u64)
+        }
+        TAG_BYTE_ARRAY => {
+            let len = read_length(src)?;
+            skip(src, len)
+        }
+        TAG_INT_ARRAY => {
+            let len = read_length(src)?;
+            skip(src, len * 4)
+        }
+        TAG_LONG_ARRAY => {
+            let len = read_length(src)?;
+            skip(src, len * 8)
+        }
+        TAG_LIST => {
+            let element_type = read_ubyte(src)?;
+            let len = read_length(src)?;
+            match payload_size(element_type) {
+                Some(size) => skip(src, len * size),
+                None => (0..len).try_for_each(|_| skip_payload(src, element_type)),
+            }
+        }
+        TAG_COMPOUND => loop {
+            let tag = read_ubyte(src)?;
+            if tag == TAG_END {
+                return Ok(());
+            }
+
+            let name_len = src.read_u16::<BigEndian>()?;
+            skip(src, name_len as u64)?;
+            skip_payload(src, tag)?;
+        },
+        _ => Err(TagDecodeError::UnknownTagType { tag_type_id: tag }),
+    }
+}
+
+/// Byte size of fixed width payloads.
+#[inline]
+fn payload_size(tag: u8) -> Option<u64> {
+    match tag {
+        TAG_BYTE => Some(1),
+        TAG_SHORT => Some(2),
+        TAG_INT | TAG_FLOAT => Some(4),
+        TAG_LONG | TAG_DOUBLE => Some(8),
+        _ => None,
+    }
+}
+
+/// Fewest bytes a payload of `tag` takes, for instance the length prefix of an array.
+#[inline]
+pub(crate) fn min_payload_size(tag: u8) -> u64 {
+    match tag {
+        TAG_STRING => 2,
+        TAG_BYTE_ARRAY | TAG_INT_ARRAY | TAG_LONG_ARRAY => 4,
+        TAG_LIST => 5,
+        // The end tag of an empty compound
+        TAG_COMPOUND => 1,
+        tag => payload_size(tag).unwrap_or(1),
+    }
+}
+
+#[inline]
+fn read_length<R: Read>(src: &mut R) -> Result<u64, TagDecodeError> {
+    let len = read_int(src)?;
+    u64::try_from(len).map_err(|_| TagDecodeError::InvalidLength(len))
+}
+
+#[inline]
+fn skip<R: Read>(src: &mut R, len: u64) -> Result<(), TagDecodeError> {
+    let skipped = io::copy(&mut src.take(len), &mut io::sink())?;
+    if skipped < len {
+        return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+    }
+
+    Ok(())
 }
 
 #[inline]
@@ -1,27 +1,39 @@
-use core::f64;
+//! Serde serializer for NBT and `serialize_with` helpers for NBT arrays.
+
 use std::fmt::Debug;
-use std::i16;
 use std::io::Write;
 
 use serde::ser;
 
+use crate::array::ArrayElement;
 use crate::consts::*;
 use crate::err::TagEncodeError;
 use crate::raw;
 
+/// Bytes of array elements gathered before they're written out.
+const ARRAY_BUFFER_LEN: usize = 8 * 1024;
+
 /// Encode data to NBT format.
 ///
-/// Implements `serde::Serialize` to serialize objects into NBT data.
+/// Implements `serde::Serializer` to serialize objects into NBT data. Most users want
+/// [`crate::to_writer`] instead.
 #[derive(Debug)]
-pub(crate) struct Encoder<W: Write> {
+pub struct Encoder<W: Write> {
     writer: W,
+    unsigned: bool,
 }
 
+/// Serializer state for NBT compounds, lists and arrays.
 #[derive(Debug)]
-pub(crate) struct Compound<'a, W: Write> {
+pub struct Compound<'a, W: Write> {
     outer: &'a mut Encoder<W>,
     length: i32,
-    sigil: bool,
+    /// Type of the list elements, set by the first element unless known up front.
+    element_type: Option<u8>,
+    /// Wrapped in the single-key compound of an enum variant, closed on `end`.
+    enclosed: bool,
+    /// Array elements waiting to be written in bulk.
+    buffer: Vec<u8>,
 }
 
 struct TagEncoder<'a, W: Write, K> {
@@ -31,17 +43,38 @@ struct TagEncoder<'a, W: Write, K> {
 
 struct InnerEncoder<'a, W: Write> {
     outer: &'a mut Encoder<W>,
+    /// Compound entries may be left out when `None`, list elements may not.
+    optional: bool,
 }
 
 struct MapKeyEncoder<'a, W: Write> {
     outer: &'a mut Encoder<W>,
 }
 
-impl<'a, W: Write + Debug> Encoder<W> {
+impl<W: Write + Debug> Encoder<W> {
     /// Create encoder
     #[inline]
-    pub(crate) fn new(writer: W) -> Self {
-        Self { writer }
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer,
+            unsigned: false,
+        }
+    }
+
+    /// Opts into writing `u8`, `u16`, `u32` and `u64` as `TAG_Byte`, `TAG_Short`,
+    /// `TAG_Int` and `TAG_Long` with the same bits, and `char` as `TAG_String`.
+    ///
+    /// Read them back with [`crate::Decoder::map_unsigned`].
+    #[inline]
+    pub fn map_unsigned(mut self, enabled: bool) -> Self {
+        self.unsigned = enabled;
+        self
+    }
+
+    /// Unwraps the underlying writer.
+    #[inline]
+    pub fn into_inner(self) -> W {
+        self.writer
     }
 
     #[inline]
@@ -55,34 +88,169 @@ impl<'a, W: Write + Debug> Encoder<W> {
     }
 }
 
+impl<W: Write> Encoder<W> {
+    /// Tag type `value` is written as, `None` for `None`.
+    fn tag_type_of<T>(&self, value: &T) -> Result<Option<u8>, TagEncodeError>
+    where
+        T: ?Sized + ser::Serialize,
+    {
+        // Only the header of an unnamed tag is written, which is its type
+        let mut header = [TAG_END; 1];
+        let mut probe = Encoder {
+            writer: &mut header[..],
+            unsigned: self.unsigned,
+        };
+        match value.serialize(&mut TagEncoder::from_outer(&mut probe, Option::<String>::None)) {
+            Ok(()) if probe.writer.is_empty() => Ok(Some(header[0])),
+            Ok(()) => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+
+    #[inline]
+    fn check_unsigned(&self, ttype: &'static str) -> Result<(), TagEncodeError> {
+        match self.unsigned {
+            true => Ok(()),
+            false => Err(TagEncodeError::UnrepresentableType(ttype)),
+        }
+    }
+}
+
 impl<'a, W: Write> Compound<'a, W> {
     #[inline]
     fn from_outer(outer: &'a mut Encoder<W>) -> Self {
         Self {
             outer,
             length: 0,
-            sigil: false,
+            element_type: None,
+            enclosed: false,
+            buffer: Vec::new(),
         }
     }
 
+    /// Starts the single-key compound of an externally tagged enum variant.
+    #[inline]
+    fn for_struct_variant(
+        outer: &'a mut Encoder<W>,
+        variant: &'static str,
+    ) -> Result<Self, TagEncodeError> {
+        raw::write_ubyte(&mut outer.writer, TAG_COMPOUND)?;
+        raw::write_str(&mut outer.writer, variant)?;
+
+        Ok(Self {
+            outer,
+            length: 0,
+            element_type: None,
+            enclosed: true,
+            buffer: Vec::new(),
+        })
+    }
+
+    /// Writes the single-key compound of a newtype variant. Unlike struct fields its value
+    /// can't be left out, as the key is all that tells the variant.
+    fn write_newtype_variant<T>(
+        outer: &'a mut Encoder<W>,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<(), TagEncodeError>
+    where
+        W: Debug,
+        T: ?Sized + ser::Serialize,
+    {
+        value.serialize(&mut TagEncoder::from_outer(outer, Some(variant)))?;
+        value.serialize(&mut InnerEncoder::from_outer(outer))?;
+        raw::close_nbt(&mut outer.writer)
+    }
+
+    /// Same as [`Compound::for_struct_variant`], but holding a list of tuple fields.
     #[inline]
-    fn for_seq(
+    fn for_tuple_variant(
         outer: &'a mut Encoder<W>,
+        variant: &'static str,
         length: i32,
-        array: bool,
     ) -> Result<Self, TagEncodeError> {
-        if length == 0 || array {
-            if !array {
-                raw::write_ubyte(&mut outer.writer, TAG_END)?;
-            }
+        raw::write_ubyte(&mut outer.writer, TAG_LIST)?;
+        raw::write_str(&mut outer.writer, variant)?;
+
+        let mut state = Self::for_seq(outer, length)?;
+        state.enclosed = true;
+        Ok(state)
+    }
 
+    /// Starts a list whose element type is already known, so empty lists keep it.
+    #[inline]
+    fn for_typed_list(
+        outer: &'a mut Encoder<W>,
+        element_type: u8,
+        length: i32,
+    ) -> Result<Self, TagEncodeError> {
+        raw::write_ubyte(&mut outer.writer, element_type)?;
+        raw::write_int(&mut outer.writer, length)?;
+
+        Ok(Self {
+            outer,
+            length,
+            element_type: Some(element_type),
+            enclosed: false,
+            buffer: Vec::new(),
+        })
+    }
+
+    #[inline]
+    fn for_seq(outer: &'a mut Encoder<W>, length: i32) -> Result<Self, TagEncodeError> {
+        if length == 0 {
+            raw::write_ubyte(&mut outer.writer, TAG_END)?;
             raw::write_int(&mut outer.writer, length)?;
         }
 
         Ok(Self {
             outer,
             length,
-            sigil: false,
+            element_type: None,
+            enclosed: false,
+            buffer: Vec::new(),
+        })
+    }
+
+    /// Writes a list element, after the list header for the first one. Every element must
+    /// have the tag type of the first, lists can't mix them.
+    fn list_element<T>(&mut self, value: &T) -> Result<(), TagEncodeError>
+    where
+        W: Debug,
+        T: ?Sized + ser::Serialize,
+    {
+        if let Some(found) = self.outer.tag_type_of(value)? {
+            match self.element_type {
+                None => {
+                    raw::write_ubyte(&mut self.outer.writer, found)?;
+                    raw::write_int(&mut self.outer.writer, self.length)?;
+                    self.element_type = Some(found);
+                }
+                Some(expected) if expected != found => {
+                    return Err(TagEncodeError::TagMismatch { found, expected })
+                }
+                Some(_) => {}
+            }
+        }
+        value.serialize(&mut InnerEncoder::from_outer(self.outer))
+    }
+
+    /// Starts an NBT array holding elements of `width` bytes.
+    #[inline]
+    fn for_array(
+        outer: &'a mut Encoder<W>,
+        length: i32,
+        width: usize,
+    ) -> Result<Self, TagEncodeError> {
+        raw::write_int(&mut outer.writer, length)?;
+
+        let size = (length.max(0) as usize).saturating_mul(width);
+        Ok(Self {
+            outer,
+            length,
+            element_type: None,
+            enclosed: false,
+            buffer: Vec::with_capacity(size.min(ARRAY_BUFFER_LEN)),
         })
     }
 }
@@ -109,7 +277,19 @@ where
 impl<'a, W: Write> InnerEncoder<'a, W> {
     #[inline]
     pub fn from_outer(outer: &'a mut Encoder<W>) -> Self {
-        Self { outer }
+        Self {
+            outer,
+            optional: false,
+        }
+    }
+
+    /// Encoder for the value of a compound entry, which is left out when `None`.
+    #[inline]
+    pub fn for_entry(outer: &'a mut Encoder<W>) -> Self {
+        Self {
+            outer,
+            optional: true,
+        }
     }
 }
 
@@ -126,11 +306,16 @@ impl<'a, W: Write + Debug> serde::Serializer for &'a mut Encoder<W> {
     type SerializeSeq = serde::ser::Impossible<(), TagEncodeError>;
     type SerializeTuple = serde::ser::Impossible<(), TagEncodeError>;
     type SerializeTupleStruct = serde::ser::Impossible<(), TagEncodeError>;
-    type SerializeTupleVariant = serde::ser::Impossible<(), TagEncodeError>;
-    type SerializeStructVariant = serde::ser::Impossible<(), TagEncodeError>;
+    type SerializeTupleVariant = Compound<'a, W>;
+    type SerializeStructVariant = Compound<'a, W>;
     type SerializeMap = Compound<'a, W>;
     type SerializeStruct = Compound<'a, W>;
 
+    #[inline]
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+
     #[inline]
     fn serialize_unit_struct(self, _name: &'static str) -> Result<(), TagEncodeError> {
         self.write_header(TAG_COMPOUND, None)
@@ -164,10 +349,53 @@ impl<'a, W: Write + Debug> serde::Serializer for &'a mut Encoder<W> {
         Ok(Compound::from_outer(self))
     }
 
+    #[inline]
+    fn serialize_newtype_variant<T>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok, Self::Error>
+    where
+        T: ?Sized + ser::Serialize,
+    {
+        self.write_header(TAG_COMPOUND, None)?;
+        Compound::write_newtype_variant(self, variant, value)
+    }
+
+    #[inline]
+    fn serialize_tuple_variant(
+        self,
+        name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleVariant, Self::Error> {
+        if name == LIBNBT_LIST {
+            return Err(TagEncodeError::RootMustBeCompound);
+        }
+
+        self.write_header(TAG_COMPOUND, None)?;
+        Compound::for_tuple_variant(self, variant, len as i32)
+    }
+
+    #[inline]
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, Self::Error> {
+        self.write_header(TAG_COMPOUND, None)?;
+        Compound::for_struct_variant(self, variant)
+    }
+
     return_expr_for_serialized_types!(
         Err(TagEncodeError::RootMustBeCompound); bool i8 i16 i32 i64 u8 u16 u32 u64
-        f32 f64 char str bytes none some unit unit_variant newtype_variant
-        seq tuple tuple_struct tuple_variant struct_variant
+        f32 f64 char str bytes none some unit unit_variant
+        seq tuple tuple_struct
     );
 }
 
@@ -179,15 +407,7 @@ impl<'a, W: Write + Debug> ser::SerializeSeq for Compound<'a, W> {
     where
         T: ?Sized + ser::Serialize,
     {
-        if !self.sigil {
-            value.serialize(&mut TagEncoder::from_outer(
-                self.outer,
-                Option::<String>::None,
-            ))?;
-            raw::write_int(&mut self.outer.writer, self.length)?;
-            self.sigil = true;
-        }
-        value.serialize(&mut InnerEncoder::from_outer(self.outer))
+        self.list_element(value)
     }
 
     fn end(self) -> Result<Self::Ok, Self::Error> {
@@ -195,7 +415,9 @@ impl<'a, W: Write + Debug> ser::SerializeSeq for Compound<'a, W> {
     }
 }
 
-impl<'a, 'b, W: Write + Debug> ser::SerializeTupleStruct for Compound<'a, W> {
+/// Only NBT arrays are written as tuple structs. Their elements are gathered into a
+/// buffer, so unbuffered writers don't see a write per element.
+impl<'a, W: Write + Debug> ser::SerializeTupleStruct for Compound<'a, W> {
     type Ok = ();
     type Error = TagEncodeError;
 
@@ -203,10 +425,57 @@ impl<'a, 'b, W: Write + Debug> ser::SerializeTupleStruct for Compound<'a, W> {
     where
         T: ?Sized + ser::Serialize,
     {
-        value.serialize(&mut InnerEncoder::from_outer(self.outer))
+        value.serialize(ArrayElementEncoder {
+            buffer: &mut self.buffer,
+        })?;
+
+        if self.buffer.len() >= ARRAY_BUFFER_LEN {
+            self.outer.writer.write_all(&self.buffer)?;
+            self.buffer.clear();
+        }
+        Ok(())
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        self.outer.writer.write_all(&self.buffer).map_err(From::from)
+    }
+}
+
+impl<'a, W: Write + Debug> ser::SerializeTupleVariant for Compound<'a, W> {
+    type Ok = ();
+    type Error = TagEncodeError;
+
+    fn serialize_field<T>(&mut self, value: &T) -> Result<Self::Ok, Self::Error>
+    where
+        T: ?Sized + ser::Serialize,
+    {
+        self.list_element(value)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        if self.enclosed {
+            raw::close_nbt(&mut self.outer.writer)?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a, W: Write + Debug> ser::SerializeStructVariant for Compound<'a, W> {
+    type Ok = ();
+    type Error = TagEncodeError;
+
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<Self::Ok, Self::Error>
+    where
+        T: ?Sized + ser::Serialize,
+    {
+        ser::SerializeStruct::serialize_field(self, key, value)
     }
 
     fn end(self) -> Result<Self::Ok, Self::Error> {
+        raw::close_nbt(&mut self.outer.writer)?;
+        if self.enclosed {
+            raw::close_nbt(&mut self.outer.writer)?;
+        }
         Ok(())
     }
 }
@@ -219,8 +488,9 @@ impl<'a, W: Write + Debug> ser::SerializeStruct for Compound<'a, W> {
     where
         T: ?Sized + ser::Serialize,
     {
+        // `None` writes neither the header nor the payload, leaving the key out.
         value.serialize(&mut TagEncoder::from_outer(self.outer, Some(key)))?;
-        value.serialize(&mut InnerEncoder::from_outer(self.outer))
+        value.serialize(&mut InnerEncoder::for_entry(self.outer))
     }
 
     fn end(self) -> Result<Self::Ok, Self::Error> {
@@ -238,8 +508,9 @@ impl<'a, W: Write + Debug> ser::SerializeMap for Compound<'a, W> {
         K: ?Sized + ser::Serialize,
         V: ?Sized + ser::Serialize,
     {
+        // `None` writes neither the header nor the payload, leaving the key out.
         value.serialize(&mut TagEncoder::from_outer(self.outer, Some(key)))?;
-        value.serialize(&mut InnerEncoder::from_outer(self.outer))
+        value.serialize(&mut InnerEncoder::for_entry(self.outer))
     }
 
     #[inline]
@@ -247,20 +518,32 @@ impl<'a, W: Write + Debug> ser::SerializeMap for Compound<'a, W> {
         raw::close_nbt(&mut self.outer.writer)
     }
 
-    #[inline]
-    fn serialize_key<T>(&mut self, _key: &T) -> Result<(), Self::Error>
+    /// The tag type comes before the key, so the key is held in `buffer` until its
+    /// value is given.
+    fn serialize_key<T>(&mut self, key: &T) -> Result<(), Self::Error>
     where
         T: ?Sized + ser::Serialize,
     {
-        unimplemented!()
+        let mut encoder = Encoder {
+            writer: Vec::new(),
+            unsigned: self.outer.unsigned,
+        };
+        key.serialize(&mut MapKeyEncoder::from_outer(&mut encoder))?;
+        self.buffer = encoder.writer;
+        Ok(())
     }
 
-    #[inline]
-    fn serialize_value<T>(&mut self, _value: &T) -> Result<(), Self::Error>
+    fn serialize_value<T>(&mut self, value: &T) -> Result<(), Self::Error>
     where
         T: ?Sized + ser::Serialize,
     {
-        unimplemented!()
+        let key = std::mem::take(&mut self.buffer);
+        // Past the length written ahead of the key
+        let key = key
+            .get(2..)
+            .and_then(|key| std::str::from_utf8(key).ok())
+            .ok_or_else(|| <TagEncodeError as ser::Error>::custom("map value without a key"))?;
+        self.serialize_entry(key, value)
     }
 }
 
@@ -271,9 +554,14 @@ impl<'a, W: Write + Debug> serde::Serializer for &'a mut InnerEncoder<'a, W> {
     type SerializeMap = Compound<'a, W>;
     type SerializeStruct = Compound<'a, W>;
     type SerializeTupleStruct = Compound<'a, W>;
+    type SerializeTupleVariant = Compound<'a, W>;
+    type SerializeStructVariant = Compound<'a, W>;
     type SerializeTuple = ser::Impossible<(), TagEncodeError>;
-    type SerializeTupleVariant = ser::Impossible<(), TagEncodeError>;
-    type SerializeStructVariant = ser::Impossible<(), TagEncodeError>;
+
+    #[inline]
+    fn is_human_readable(&self) -> bool {
+        false
+    }
 
     #[inline]
     fn serialize_bool(self, v: bool) -> Result<Self::Ok, Self::Error> {
@@ -300,6 +588,36 @@ impl<'a, W: Write + Debug> serde::Serializer for &'a mut InnerEncoder<'a, W> {
         raw::write_long(&mut self.outer.writer, v)
     }
 
+    #[inline]
+    fn serialize_u8(self, v: u8) -> Result<Self::Ok, Self::Error> {
+        self.outer.check_unsigned("u8")?;
+        self.serialize_i8(v as i8)
+    }
+
+    #[inline]
+    fn serialize_u16(self, v: u16) -> Result<Self::Ok, Self::Error> {
+        self.outer.check_unsigned("u16")?;
+        self.serialize_i16(v as i16)
+    }
+
+    #[inline]
+    fn serialize_u32(self, v: u32) -> Result<Self::Ok, Self::Error> {
+        self.outer.check_unsigned("u32")?;
+        self.serialize_i32(v as i32)
+    }
+
+    #[inline]
+    fn serialize_u64(self, v: u64) -> Result<Self::Ok, Self::Error> {
+        self.outer.check_unsigned("u64")?;
+        self.serialize_i64(v as i64)
+    }
+
+    #[inline]
+    fn serialize_char(self, v: char) -> Result<Self::Ok, Self::Error> {
+        self.outer.check_unsigned("char")?;
+        self.serialize_str(v.encode_utf8(&mut [0; 4]))
+    }
+
     #[inline]
     fn serialize_f32(self, v: f32) -> Result<Self::Ok, Self::Error> {
         raw::write_float(&mut self.outer.writer, v)
@@ -355,13 +673,22 @@ impl<'a, W: Write + Debug> serde::Serializer for &'a mut InnerEncoder<'a, W> {
     #[inline]
     fn serialize_newtype_struct<T>(
         self,
-        _name: &'static str,
+        name: &'static str,
         value: &T,
     ) -> Result<Self::Ok, Self::Error>
     where
         T: ?Sized + ser::Serialize,
     {
-        value.serialize(self)
+        let width = match name {
+            LIBNBT_I8_ARRAY => 1,
+            LIBNBT_I32_ARRAY => 4,
+            LIBNBT_I64_ARRAY => 8,
+            _ => return value.serialize(self),
+        };
+        value.serialize(ArrayPayloadEncoder {
+            outer: self.outer,
+            width,
+        })
     }
 
     #[inline]
@@ -371,9 +698,9 @@ impl<'a, W: Write + Debug> serde::Serializer for &'a mut InnerEncoder<'a, W> {
         len: usize,
     ) -> Result<Self::SerializeTupleStruct, Self::Error> {
         match name {
-            LIBNT_I8_ARRAY | LIBNT_I32_ARRAY | LIBNT_I64_ARRAY => {
-                Compound::for_seq(self.outer, len as i32, true)
-            }
+            LIBNBT_I8_ARRAY => Compound::for_array(self.outer, len as i32, 1),
+            LIBNBT_I32_ARRAY => Compound::for_array(self.outer, len as i32, 4),
+            LIBNBT_I64_ARRAY => Compound::for_array(self.outer, len as i32, 8),
             _ => Err(TagEncodeError::UnrepresentableType(stringify!(
                 tuple_struct
             ))),
@@ -383,17 +710,61 @@ impl<'a, W: Write + Debug> serde::Serializer for &'a mut InnerEncoder<'a, W> {
     #[inline]
     fn serialize_seq(self, len: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
         match len {
-            Some(l) => Compound::for_seq(self.outer, l as i32, false),
+            Some(l) => Compound::for_seq(self.outer, l as i32),
             None => Err(TagEncodeError::UnrepresentableType("unsized list")),
         }
     }
 
+    #[inline]
+    fn serialize_tuple_variant(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleVariant, Self::Error> {
+        match name {
+            LIBNBT_LIST => Compound::for_typed_list(self.outer, variant_index as u8, len as i32),
+            _ => Compound::for_tuple_variant(self.outer, variant, len as i32),
+        }
+    }
+
+    /// Externally tagged, written as a compound holding the variant name as its only key.
+    #[inline]
+    fn serialize_newtype_variant<T>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok, Self::Error>
+    where
+        T: ?Sized + ser::Serialize,
+    {
+        Compound::write_newtype_variant(self.outer, variant, value)
+    }
+
+    #[inline]
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, Self::Error> {
+        Compound::for_struct_variant(self.outer, variant)
+    }
+
     return_expr_for_serialized_types!(Err(TagEncodeError::UnrepresentableType("u8")); bytes);
-    return_expr_for_serialized_types!(Ok(()); none);
-    unrepresentable!(
-        u8 u16 u32 u64 char unit newtype_variant
-        tuple tuple_variant struct_variant
-    );
+    #[inline]
+    fn serialize_none(self) -> Result<Self::Ok, Self::Error> {
+        match self.optional {
+            true => Ok(()),
+            false => Err(TagEncodeError::UnrepresentableType("none outside of a compound")),
+        }
+    }
+
+    unrepresentable!(unit tuple);
 }
 
 impl<'a, W, K> serde::Serializer for &'a mut TagEncoder<'a, W, K>
@@ -407,9 +778,14 @@ where
     type SerializeTupleStruct = NoOp;
     type SerializeMap = NoOp;
     type SerializeStruct = NoOp;
+    type SerializeTupleVariant = NoOp;
+    type SerializeStructVariant = NoOp;
     type SerializeTuple = ser::Impossible<Self::Ok, Self::Error>;
-    type SerializeTupleVariant = ser::Impossible<Self::Ok, Self::Error>;
-    type SerializeStructVariant = ser::Impossible<Self::Ok, Self::Error>;
+
+    #[inline]
+    fn is_human_readable(&self) -> bool {
+        false
+    }
 
     #[inline]
     fn serialize_bool(self, v: bool) -> Result<Self::Ok, Self::Error> {
@@ -436,6 +812,36 @@ where
         self.write_header(TAG_LONG)
     }
 
+    #[inline]
+    fn serialize_u8(self, v: u8) -> Result<Self::Ok, Self::Error> {
+        self.outer.check_unsigned("u8")?;
+        self.serialize_i8(v as i8)
+    }
+
+    #[inline]
+    fn serialize_u16(self, v: u16) -> Result<Self::Ok, Self::Error> {
+        self.outer.check_unsigned("u16")?;
+        self.serialize_i16(v as i16)
+    }
+
+    #[inline]
+    fn serialize_u32(self, v: u32) -> Result<Self::Ok, Self::Error> {
+        self.outer.check_unsigned("u32")?;
+        self.serialize_i32(v as i32)
+    }
+
+    #[inline]
+    fn serialize_u64(self, v: u64) -> Result<Self::Ok, Self::Error> {
+        self.outer.check_unsigned("u64")?;
+        self.serialize_i64(v as i64)
+    }
+
+    #[inline]
+    fn serialize_char(self, v: char) -> Result<Self::Ok, Self::Error> {
+        self.outer.check_unsigned("char")?;
+        self.serialize_str(v.encode_utf8(&mut [0; 4]))
+    }
+
     #[inline]
     fn serialize_f32(self, _v: f32) -> Result<Self::Ok, Self::Error> {
         self.write_header(TAG_FLOAT)
@@ -474,9 +880,9 @@ where
         _len: usize,
     ) -> Result<Self::SerializeTupleStruct, Self::Error> {
         match name {
-            LIBNT_I8_ARRAY => self.write_header(TAG_BYTE_ARRAY)?,
-            LIBNT_I32_ARRAY => self.write_header(TAG_INT_ARRAY)?,
-            LIBNT_I64_ARRAY => self.write_header(TAG_LONG_ARRAY)?,
+            LIBNBT_I8_ARRAY => self.write_header(TAG_BYTE_ARRAY)?,
+            LIBNBT_I32_ARRAY => self.write_header(TAG_INT_ARRAY)?,
+            LIBNBT_I64_ARRAY => self.write_header(TAG_LONG_ARRAY)?,
             _ => return Err(TagEncodeError::UnrepresentableType("tuple struct")),
         }
 
@@ -520,13 +926,60 @@ where
     #[inline]
     fn serialize_newtype_struct<T>(
         self,
-        _name: &'static str,
+        name: &'static str,
         value: &T,
     ) -> Result<Self::Ok, Self::Error>
     where
         T: ?Sized + ser::Serialize,
     {
-        value.serialize(self)
+        match name {
+            LIBNBT_I8_ARRAY => self.write_header(TAG_BYTE_ARRAY),
+            LIBNBT_I32_ARRAY => self.write_header(TAG_INT_ARRAY),
+            LIBNBT_I64_ARRAY => self.write_header(TAG_LONG_ARRAY),
+            _ => value.serialize(self),
+        }
+    }
+
+    #[inline]
+    fn serialize_tuple_variant(
+        self,
+        name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, Self::Error> {
+        match name {
+            LIBNBT_LIST => self.write_header(TAG_LIST)?,
+            _ => self.write_header(TAG_COMPOUND)?,
+        }
+
+        Ok(NoOp)
+    }
+
+    #[inline]
+    fn serialize_newtype_variant<T>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<Self::Ok, Self::Error>
+    where
+        T: ?Sized + ser::Serialize,
+    {
+        self.write_header(TAG_COMPOUND)
+    }
+
+    #[inline]
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, Self::Error> {
+        self.write_header(TAG_COMPOUND)?;
+        Ok(NoOp)
     }
 
     #[inline]
@@ -534,11 +987,9 @@ where
         Err(TagEncodeError::UnrepresentableType("u8"))
     }
 
+    // No header for `None`, the inner encoder then decides whether it may be left out.
     return_expr_for_serialized_types!(Ok(()); none);
-    unrepresentable!(
-        u8 u16 u32 u64 char unit newtype_variant
-        tuple tuple_variant struct_variant
-    );
+    unrepresentable!(unit tuple);
 }
 
 impl<'a, W: Write> serde::Serializer for &'a mut MapKeyEncoder<'a, W> {
@@ -552,6 +1003,11 @@ impl<'a, W: Write> serde::Serializer for &'a mut MapKeyEncoder<'a, W> {
     type SerializeTupleStruct = ser::Impossible<Self::Ok, Self::Error>;
     type SerializeTupleVariant = ser::Impossible<Self::Ok, Self::Error>;
 
+    #[inline]
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+
     fn serialize_some<T>(self, value: &T) -> Result<Self::Ok, Self::Error>
     where
         T: ?Sized + ser::Serialize,
@@ -571,6 +1027,90 @@ impl<'a, W: Write> serde::Serializer for &'a mut MapKeyEncoder<'a, W> {
     );
 }
 
+/// Writes the payload of an NBT array handed over as one buffer, see [`bulk_array`].
+struct ArrayPayloadEncoder<'a, W: Write> {
+    outer: &'a mut Encoder<W>,
+    /// Bytes of every element
+    width: usize,
+}
+
+impl<W: Write> serde::Serializer for ArrayPayloadEncoder<'_, W> {
+    type Ok = ();
+    type Error = TagEncodeError;
+    type SerializeSeq = ser::Impossible<Self::Ok, Self::Error>;
+    type SerializeMap = ser::Impossible<Self::Ok, Self::Error>;
+    type SerializeStruct = ser::Impossible<Self::Ok, Self::Error>;
+    type SerializeStructVariant = ser::Impossible<Self::Ok, Self::Error>;
+    type SerializeTuple = ser::Impossible<Self::Ok, Self::Error>;
+    type SerializeTupleStruct = ser::Impossible<Self::Ok, Self::Error>;
+    type SerializeTupleVariant = ser::Impossible<Self::Ok, Self::Error>;
+
+    #[inline]
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+
+    #[inline]
+    fn serialize_bytes(self, v: &[u8]) -> Result<Self::Ok, Self::Error> {
+        let length = i32::try_from(v.len() / self.width)
+            .map_err(|_| TagEncodeError::UnrepresentableType("array longer than i32::MAX"))?;
+        raw::write_int(&mut self.outer.writer, length)?;
+        self.outer.writer.write_all(v).map_err(From::from)
+    }
+
+    unrepresentable!(
+        bool i8 i16 i32 i64 u8 u16 u32 u64 f32 f64 char str none some unit unit_struct
+        unit_variant newtype_struct newtype_variant seq tuple tuple_struct tuple_variant
+        map struct struct_variant
+    );
+}
+
+/// Appends NBT array elements to the buffer of a [`Compound`].
+struct ArrayElementEncoder<'a> {
+    buffer: &'a mut Vec<u8>,
+}
+
+impl serde::Serializer for ArrayElementEncoder<'_> {
+    type Ok = ();
+    type Error = TagEncodeError;
+    type SerializeSeq = ser::Impossible<Self::Ok, Self::Error>;
+    type SerializeMap = ser::Impossible<Self::Ok, Self::Error>;
+    type SerializeStruct = ser::Impossible<Self::Ok, Self::Error>;
+    type SerializeStructVariant = ser::Impossible<Self::Ok, Self::Error>;
+    type SerializeTuple = ser::Impossible<Self::Ok, Self::Error>;
+    type SerializeTupleStruct = ser::Impossible<Self::Ok, Self::Error>;
+    type SerializeTupleVariant = ser::Impossible<Self::Ok, Self::Error>;
+
+    #[inline]
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+
+    #[inline]
+    fn serialize_i8(self, v: i8) -> Result<Self::Ok, Self::Error> {
+        self.buffer.push(v as u8);
+        Ok(())
+    }
+
+    #[inline]
+    fn serialize_i32(self, v: i32) -> Result<Self::Ok, Self::Error> {
+        self.buffer.extend_from_slice(&v.to_be_bytes());
+        Ok(())
+    }
+
+    #[inline]
+    fn serialize_i64(self, v: i64) -> Result<Self::Ok, Self::Error> {
+        self.buffer.extend_from_slice(&v.to_be_bytes());
+        Ok(())
+    }
+
+    unrepresentable!(
+        bool i16 u8 u16 u32 u64 f32 f64 char str bytes none some unit unit_struct
+        unit_variant newtype_struct newtype_variant seq tuple tuple_struct tuple_variant
+        map struct struct_variant
+    );
+}
+
 struct NoOp;
 
 impl ser::SerializeSeq for NoOp {
@@ -662,10 +1202,78 @@ impl ser::SerializeTupleStruct for NoOp {
     }
 }
 
-const LIBNT_I8_ARRAY: &str = "__libnbt_i8_array__";
-const LIBNT_I32_ARRAY: &str = "__libnbt_i32_array__";
-const LIBNT_I64_ARRAY: &str = "__libnbt_i64_array__";
+impl ser::SerializeTupleVariant for NoOp {
+    type Ok = ();
+    type Error = TagEncodeError;
+
+    #[inline]
+    fn serialize_field<T>(&mut self, _value: &T) -> Result<(), Self::Error>
+    where
+        T: ?Sized + ser::Serialize,
+    {
+        Ok(())
+    }
+
+    #[inline]
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        Ok(())
+    }
+}
+
+impl ser::SerializeStructVariant for NoOp {
+    type Ok = ();
+    type Error = TagEncodeError;
+
+    #[inline]
+    fn serialize_field<T>(&mut self, _key: &'static str, _value: &T) -> Result<(), Self::Error>
+    where
+        T: ?Sized + ser::Serialize,
+    {
+        Ok(())
+    }
 
+    #[inline]
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        Ok(())
+    }
+}
+
+/// Serializes the NBT array `Tag` and the array wrappers hold.
+///
+/// The NBT encoder is handed the payload as one big-endian buffer, which it writes at
+/// once. Human readable formats get the elements one by one, like from [`i8_array`].
+pub(crate) fn bulk_array<T, S>(array: &[T], serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: ArrayElement,
+    S: ser::Serializer,
+{
+    use serde::ser::SerializeTupleStruct;
+
+    if serializer.is_human_readable() {
+        let mut state = serializer.serialize_tuple_struct(T::ARRAY, array.len())?;
+        for element in array {
+            state.serialize_field(element)?;
+        }
+        return state.end();
+    }
+
+    serializer.serialize_newtype_struct(T::ARRAY, &ArrayBytes(array))
+}
+
+/// Elements of an NBT array as they are written, see [`bulk_array`].
+struct ArrayBytes<'a, T>(&'a [T]);
+
+impl<T: ArrayElement> ser::Serialize for ArrayBytes<'_, T> {
+    #[inline]
+    fn serialize<S: ser::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_bytes(&T::to_be_bytes(self.0))
+    }
+}
+
+/// Serializes a collection of `i8` as NBT `TAG_Byte_Array`.
+///
+/// Meant for `#[serde(serialize_with = "nbt::ser::i8_array")]`, the collection must
+/// report an exact length. Pair with [`crate::de::i8_array`] for reading.
 pub fn i8_array<T, S>(array: T, serial: S) -> Result<S::Ok, S::Error>
 where
     T: IntoIterator,
@@ -675,6 +1283,9 @@ where
     array_serializer!("i8_array", array, serial)
 }
 
+/// Serializes a collection of `i32` as NBT `TAG_Int_Array`.
+///
+/// See [`i8_array`] for details.
 pub fn i32_array<T, S>(array: T, serial: S) -> Result<S::Ok, S::Error>
 where
     T: IntoIterator,
@@ -684,6 +1295,9 @@ where
     array_serializer!("i32_array", array, serial)
 }
 
+/// Serializes a collection of `i64` as NBT `TAG_Long_Array`.
+///
+/// See [`i8_array`] for details.
 pub fn i64_array<T, S>(array: T, serial: S) -> Result<S::Ok, S::Error>
 where
     T: IntoIterator,
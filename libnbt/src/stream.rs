@@ -0,0 +1,631 @@
+//! Streaming NBT reading and writing.
+//!
+//! [`EventReader`] pulls [`Event`]s out of uncompressed NBT one at a time without
+//! building a [`CompoundTag`](crate::CompoundTag), and [`EventWriter`] turns the same
+//! events back into NBT. Memory use only depends on nesting depth and
+//! [`ARRAY_CHUNK_LEN`], so both can handle files of any size.
+//!
+//! A document is a root [`Event::StartCompound`] followed by its entries and a closing
+//! [`Event::End`]. Each compound entry is a [`Event::Key`] followed by one value:
+//!
+//! * [`Event::Scalar`] for numbers and strings,
+//! * [`Event::StartCompound`] then entries then [`Event::End`],
+//! * [`Event::StartList`] then `len` values then [`Event::End`],
+//! * [`Event::StartArray`] then [`Event::ArrayChunk`]s then [`Event::End`].
+//!
+//! List elements are values without a preceding key.
+//!
+//! Values that aren't needed can be passed over with [`EventReader::skip_value`], and
+//! [`find_path`] decodes a single nested tag while skipping everything around it.
+
+use std::io::{self, Read, Write};
+
+use crate::consts::*;
+use crate::err::{DecodeResult, EncodeResult, TagDecodeError, TagEncodeError};
+use crate::raw::{self, CountingReader};
+use crate::{ListTag, Map, Tag};
+
+/// Maximum number of elements [`EventReader`] puts into a single [`ArrayChunk`].
+pub const ARRAY_CHUNK_LEN: usize = 4096;
+
+/// Single step of an NBT document.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Event {
+    /// Opens a compound. Only the root compound carries a name.
+    StartCompound(Option<Box<str>>),
+    /// Name of the next compound entry.
+    Key(Box<str>),
+    /// Number or string value, never an array, list or compound.
+    Scalar(Tag),
+    /// Opens a list holding `len` elements of the given type id.
+    StartList(u8, usize),
+    /// Opens an array of the given type id holding `len` elements.
+    StartArray(u8, usize),
+    /// Next part of the array opened by [`Event::StartArray`].
+    ArrayChunk(ArrayChunk),
+    /// Closes the innermost compound, list or array.
+    End,
+}
+
+/// Part of a byte, int or long array.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ArrayChunk {
+    Byte(Vec<i8>),
+    Int(Vec<i32>),
+    Long(Vec<i64>),
+}
+
+impl ArrayChunk {
+    /// Returns the array type id the chunk belongs to.
+    pub fn tag_type(&self) -> u8 {
+        match self {
+            Self::Byte(_) => TAG_BYTE_ARRAY,
+            Self::Int(_) => TAG_INT_ARRAY,
+            Self::Long(_) => TAG_LONG_ARRAY,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        match self {
+            Self::Byte(v) => v.len(),
+            Self::Int(v) => v.len(),
+            Self::Long(v) => v.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[derive(Debug)]
+enum Frame {
+    /// `key` is the last key read, for error paths.
+    Compound { key: Option<Box<str>> },
+    List { element_type: u8, len: usize, remaining: usize },
+    Array { element_type: u8, remaining: usize },
+}
+
+impl Frame {
+    fn compound() -> Self {
+        Frame::Compound { key: None }
+    }
+
+    fn list(element_type: u8, len: usize) -> Self {
+        Frame::List {
+            element_type,
+            len,
+            remaining: len,
+        }
+    }
+}
+
+/// Pull parser over uncompressed NBT.
+///
+/// Compressed input can be read through [`EventReader::gzip`] and [`EventReader::zlib`].
+/// After an error the reader is left in an undefined state and should be dropped.
+#[derive(Debug)]
+pub struct EventReader<R> {
+    reader: CountingReader<R>,
+    stack: Vec<Frame>,
+    /// Type of the value following the last [`Event::Key`]
+    pending: Option<u8>,
+    started: bool,
+}
+
+impl<R: Read> EventReader<R> {
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader: CountingReader::new(reader),
+            stack: Vec::new(),
+            pending: None,
+            started: false,
+        }
+    }
+
+    pub fn into_inner(self) -> R {
+        self.reader.into_inner()
+    }
+
+    /// Bytes read so far.
+    pub fn offset(&self) -> u64 {
+        self.reader.position()
+    }
+
+    /// Current nesting depth, `0` before the root compound and after its end.
+    pub fn depth(&self) -> usize {
+        self.stack.len()
+    }
+
+    /// Reads the next event, `None` once the root compound has been closed.
+    ///
+    /// Errors carry the offset and path they were found at, see
+    /// [`TagDecodeError::Context`].
+    pub fn next_event(&mut self) -> DecodeResult<Option<Event>> {
+        self.event().map_err(|err| self.locate(err))
+    }
+
+    fn event(&mut self) -> DecodeResult<Option<Event>> {
+        if let Some(tag) = self.take_value_type() {
+            return self.value(tag).map(Some);
+        }
+
+        let event = match self.stack.last_mut() {
+            None if self.started => return Ok(None),
+            None => {
+                self.started = true;
+                match raw::read_header(&mut self.reader)? {
+                    (TAG_COMPOUND, name) => {
+                        self.stack.push(Frame::compound());
+                        Event::StartCompound(name)
+                    }
+                    _ => return Err(TagDecodeError::RootMustBeCompound),
+                }
+            }
+            Some(Frame::Compound { key }) => {
+                *key = None;
+                let tag = raw::read_ubyte(&mut self.reader)?;
+                if tag == TAG_END {
+                    self.stack.pop();
+                    Event::End
+                } else if tag > TAG_LONG_ARRAY {
+                    return Err(TagDecodeError::UnknownTagType { tag_type_id: tag });
+                } else {
+                    let name = raw::read_string(&mut self.reader)?.unwrap_or_default();
+                    let name = name.into_boxed_str();
+                    self.pending = Some(tag);
+                    *key = Some(name.clone());
+                    Event::Key(name)
+                }
+            }
+            // Lists with elements left were handled by `take_value_type`
+            Some(Frame::List { .. }) | Some(Frame::Array { remaining: 0, .. }) => {
+                self.stack.pop();
+                Event::End
+            }
+            Some(Frame::Array {
+                element_type,
+                remaining,
+            }) => {
+                let len = (*remaining).min(ARRAY_CHUNK_LEN);
+                *remaining -= len;
+                let tag = *element_type;
+                Event::ArrayChunk(self.chunk(tag, len)?)
+            }
+        };
+
+        Ok(Some(event))
+    }
+
+    /// Skips the value the next event would start, reading only type ids and lengths.
+    ///
+    /// Values start after an [`Event::Key`] and for every list element. Returns `false`
+    /// without reading anything when the next event doesn't start a value.
+    pub fn skip_value(&mut self) -> DecodeResult<bool> {
+        match self.take_value_type() {
+            Some(tag) => raw::skip_payload(&mut self.reader, tag)
+                .map(|_| true)
+                .map_err(|err| self.locate(err)),
+            None => Ok(false),
+        }
+    }
+
+    /// Decodes the value the next event would start into a [`Tag`].
+    ///
+    /// Returns `None` in the same places [`EventReader::skip_value`] returns `false`.
+    pub fn read_value(&mut self) -> DecodeResult<Option<Tag>> {
+        match self.take_value_type() {
+            Some(tag) => {
+                let first = self.value(tag).map_err(|err| self.locate(err))?;
+                self.build(first).map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Attaches the current offset and the path to the open value, unless `err` is
+    /// located already.
+    fn locate(&self, err: TagDecodeError) -> TagDecodeError {
+        if let TagDecodeError::Context { .. } = err {
+            return err;
+        }
+
+        let mut path = String::new();
+        for frame in &self.stack {
+            match frame {
+                Frame::Compound { key: Some(key) } => {
+                    if !path.is_empty() {
+                        path.push('.');
+                    }
+                    path.push_str(key);
+                }
+                Frame::Compound { key: None } => {}
+                Frame::List { len, remaining, .. } => {
+                    // The element being read was taken off `remaining` already
+                    path.push_str(&format!("[{}]", (len - remaining).saturating_sub(1)));
+                }
+                Frame::Array { .. } => {}
+            }
+        }
+
+        TagDecodeError::Context {
+            offset: self.reader.position(),
+            path,
+            source: Box::new(err),
+        }
+    }
+
+    /// Type of the value the next event starts, marking it as consumed.
+    fn take_value_type(&mut self) -> Option<u8> {
+        if let Some(tag) = self.pending.take() {
+            return Some(tag);
+        }
+
+        match self.stack.last_mut() {
+            Some(Frame::List {
+                element_type,
+                remaining,
+                ..
+            }) if *remaining > 0 => {
+                *remaining -= 1;
+                Some(*element_type)
+            }
+            _ => None,
+        }
+    }
+
+    /// Builds a tag out of the events following `first`, up to its [`Event::End`].
+    fn build(&mut self, first: Event) -> DecodeResult<Tag> {
+        let tag = match first {
+            Event::Scalar(tag) => tag,
+            Event::StartCompound(_) => {
+                let mut map = Map::new();
+                while let Event::Key(name) = self.expect_event()? {
+                    if let Some(value) = self.read_value()? {
+                        map.insert(name, value);
+                    }
+                }
+
+                Tag::Compound(map)
+            }
+            Event::StartList(element_type, _) => {
+                let mut list = ListTag::with_type(element_type)?;
+                while let Some(value) = self.read_value()? {
+                    list.push(value)?;
+                }
+                self.expect_event()?;
+
+                Tag::List(list)
+            }
+            Event::StartArray(TAG_BYTE_ARRAY, _) => {
+                let mut array = Vec::new();
+                while let Event::ArrayChunk(ArrayChunk::Byte(chunk)) = self.expect_event()? {
+                    array.extend(chunk);
+                }
+
+                Tag::ByteArray(array)
+            }
+            Event::StartArray(TAG_INT_ARRAY, _) => {
+                let mut array = Vec::new();
+                while let Event::ArrayChunk(ArrayChunk::Int(chunk)) = self.expect_event()? {
+                    array.extend(chunk);
+                }
+
+                Tag::IntArray(array)
+            }
+            Event::StartArray(_, _) => {
+                let mut array = Vec::new();
+                while let Event::ArrayChunk(ArrayChunk::Long(chunk)) = self.expect_event()? {
+                    array.extend(chunk);
+                }
+
+                Tag::LongArray(array)
+            }
+            Event::Key(_) | Event::ArrayChunk(_) | Event::End => {
+                unreachable!("values never start with {:?}", first)
+            }
+        };
+
+        Ok(tag)
+    }
+
+    #[inline]
+    fn expect_event(&mut self) -> DecodeResult<Event> {
+        self.next_event()?
+            .ok_or_else(|| io::Error::from(io::ErrorKind::UnexpectedEof).into())
+    }
+
+    fn value(&mut self, tag: u8) -> DecodeResult<Event> {
+        let src = &mut self.reader;
+        let scalar = match tag {
+            TAG_BYTE => Tag::Byte(raw::read_byte(src)?),
+            TAG_SHORT => Tag::Short(raw::read_short(src)?),
+            TAG_INT => Tag::Int(raw::read_int(src)?),
+            TAG_LONG => Tag::Long(raw::read_long(src)?),
+            TAG_FLOAT => Tag::Float(raw::read_float(src)?),
+            TAG_DOUBLE => Tag::Double(raw::read_double(src)?),
+            TAG_STRING => Tag::String(raw::read_string(src)?.unwrap_or_default().into()),
+            TAG_COMPOUND => {
+                self.stack.push(Frame::compound());
+                return Ok(Event::StartCompound(None));
+            }
+            TAG_LIST => {
+                let element_type = raw::read_ubyte(src)?;
+                let len = self.length()?;
+                if element_type > TAG_LONG_ARRAY || (len > 0 && element_type == TAG_END) {
+                    return Err(TagDecodeError::UnknownTagType {
+                        tag_type_id: element_type,
+                    });
+                }
+
+                self.stack.push(Frame::list(element_type, len));
+                return Ok(Event::StartList(element_type, len));
+            }
+            TAG_BYTE_ARRAY | TAG_INT_ARRAY | TAG_LONG_ARRAY => {
+                let len = self.length()?;
+                self.stack.push(Frame::Array {
+                    element_type: tag,
+                    remaining: len,
+                });
+                return Ok(Event::StartArray(tag, len));
+            }
+            _ => return Err(TagDecodeError::UnknownTagType { tag_type_id: tag }),
+        };
+
+        Ok(Event::Scalar(scalar))
+    }
+
+    fn length(&mut self) -> DecodeResult<usize> {
+        let len = raw::read_int(&mut self.reader)?;
+        usize::try_from(len).map_err(|_| TagDecodeError::InvalidLength(len))
+    }
+
+    fn chunk(&mut self, tag: u8, len: usize) -> DecodeResult<ArrayChunk> {
+        let src = &mut self.reader;
+        let chunk = match tag {
+            TAG_BYTE_ARRAY => {
+                ArrayChunk::Byte((0..len).map(|_| raw::read_byte(src)).collect::<Result<_, _>>()?)
+            }
+            TAG_INT_ARRAY => {
+                ArrayChunk::Int((0..len).map(|_| raw::read_int(src)).collect::<Result<_, _>>()?)
+            }
+            _ => {
+                ArrayChunk::Long((0..len).map(|_| raw::read_long(src)).collect::<Result<_, _>>()?)
+            }
+        };
+
+        Ok(chunk)
+    }
+}
+
+/// Decodes only the tag found at `path`, a dot separated list of compound keys such as
+/// `"Level.Sections"`.
+///
+/// Every entry off the path is skipped without being decoded, and nothing past the found
+/// tag is read. `reader` has to be at the start of the document. Returns `None` when a
+/// key is missing or leads into something that isn't a compound.
+pub fn find_path<R: Read>(reader: &mut EventReader<R>, path: &str) -> DecodeResult<Option<Tag>> {
+    if reader.started {
+        return Err(TagDecodeError::Serde(
+            "find_path needs an EventReader at the start of the document".into(),
+        ));
+    }
+    reader.next_event()?;
+
+    let keys: Vec<&str> = path.split('.').collect();
+    for (depth, key) in keys.iter().enumerate() {
+        loop {
+            match reader.next_event()? {
+                Some(Event::Key(name)) if name.as_ref() == *key => break,
+                Some(Event::Key(_)) => {
+                    reader.skip_value()?;
+                }
+                _ => return Ok(None),
+            }
+        }
+
+        if depth + 1 == keys.len() {
+            return reader.read_value();
+        }
+        if reader.pending != Some(TAG_COMPOUND) {
+            return Ok(None);
+        }
+        reader.next_event()?;
+    }
+
+    Ok(None)
+}
+
+impl<R: Read> EventReader<flate2::read::GzDecoder<R>> {
+    /// Reads events from gzip compressed NBT data.
+    pub fn gzip(reader: R) -> Self {
+        Self::new(flate2::read::GzDecoder::new(reader))
+    }
+}
+
+impl<R: Read> EventReader<flate2::read::ZlibDecoder<R>> {
+    /// Reads events from zlib compressed NBT data.
+    pub fn zlib(reader: R) -> Self {
+        Self::new(flate2::read::ZlibDecoder::new(reader))
+    }
+}
+
+impl<R: Read> Iterator for EventReader<R> {
+    type Item = DecodeResult<Event>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_event().transpose()
+    }
+}
+
+/// Push writer producing uncompressed NBT from [`Event`]s.
+///
+/// Events are checked against the document structure, so the output is valid NBT once
+/// [`EventWriter::is_finished`] returns `true`. Lists and arrays have to be announced
+/// with their exact length up front.
+#[derive(Debug)]
+pub struct EventWriter<W> {
+    writer: W,
+    stack: Vec<Frame>,
+    /// Name given by the last [`Event::Key`]
+    key: Option<Box<str>>,
+    started: bool,
+}
+
+impl<W: Write> EventWriter<W> {
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer,
+            stack: Vec::new(),
+            key: None,
+            started: false,
+        }
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+
+    /// Returns `true` once the root compound has been closed.
+    pub fn is_finished(&self) -> bool {
+        self.started && self.stack.is_empty()
+    }
+
+    /// Writes a single event.
+    ///
+    /// # Errors
+    ///
+    /// Fails on IO errors and with [`TagEncodeError::Serde`] when the event doesn't fit
+    /// the document at this point, e.g. a value without a key inside a compound or a list
+    /// element of the wrong type.
+    pub fn write_event(&mut self, event: Event) -> EncodeResult<()> {
+        match event {
+            Event::StartCompound(name) if !self.started => {
+                self.started = true;
+                raw::write_ubyte(&mut self.writer, TAG_COMPOUND)?;
+                raw::write_str(&mut self.writer, name.as_deref().unwrap_or_default())?;
+                self.stack.push(Frame::compound());
+                Ok(())
+            }
+            _ if !self.started => Err(TagEncodeError::RootMustBeCompound),
+            Event::Key(name) => match self.stack.last() {
+                Some(Frame::Compound { .. }) if self.key.is_none() => {
+                    self.key = Some(name);
+                    Ok(())
+                }
+                _ => Err(unexpected("key")),
+            },
+            Event::Scalar(tag) => match tag {
+                Tag::ByteArray(_)
+                | Tag::IntArray(_)
+                | Tag::LongArray(_)
+                | Tag::List(_)
+                | Tag::Compound(_) => Err(unexpected("non scalar tag")),
+                tag => {
+                    self.value_header(tag.tag_type())?;
+                    write_scalar(&mut self.writer, &tag)
+                }
+            },
+            Event::StartCompound(_) => {
+                self.value_header(TAG_COMPOUND)?;
+                self.stack.push(Frame::compound());
+                Ok(())
+            }
+            Event::StartList(element_type, len) => {
+                if element_type > TAG_LONG_ARRAY || (len > 0 && element_type == TAG_END) {
+                    return Err(unexpected("list element type"));
+                }
+
+                self.value_header(TAG_LIST)?;
+                raw::write_ubyte(&mut self.writer, element_type)?;
+                raw::write_int(&mut self.writer, length(len)?)?;
+                self.stack.push(Frame::list(element_type, len));
+                Ok(())
+            }
+            Event::StartArray(element_type, len) => {
+                if !matches!(element_type, TAG_BYTE_ARRAY | TAG_INT_ARRAY | TAG_LONG_ARRAY) {
+                    return Err(unexpected("array type"));
+                }
+
+                self.value_header(element_type)?;
+                raw::write_int(&mut self.writer, length(len)?)?;
+                self.stack.push(Frame::Array {
+                    element_type,
+                    remaining: len,
+                });
+                Ok(())
+            }
+            Event::ArrayChunk(chunk) => match self.stack.last_mut() {
+                Some(Frame::Array {
+                    element_type,
+                    remaining,
+                }) if *element_type == chunk.tag_type() && chunk.len() <= *remaining => {
+                    *remaining -= chunk.len();
+                    write_chunk(&mut self.writer, &chunk)
+                }
+                _ => Err(unexpected("array chunk")),
+            },
+            Event::End => match self.stack.last() {
+                Some(Frame::Compound { .. }) if self.key.is_none() => {
+                    self.stack.pop();
+                    raw::close_nbt(&mut self.writer)
+                }
+                Some(Frame::List { remaining: 0, .. }) | Some(Frame::Array { remaining: 0, .. }) => {
+                    self.stack.pop();
+                    Ok(())
+                }
+                _ => Err(unexpected("end")),
+            },
+        }
+    }
+
+    /// Writes what has to precede a value: type id and name inside a compound, nothing
+    /// but a type check inside a list.
+    fn value_header(&mut self, tag: u8) -> EncodeResult<()> {
+        match self.stack.last_mut() {
+            Some(Frame::Compound { .. }) => {
+                let name = self.key.take().ok_or_else(|| unexpected("value without key"))?;
+                raw::write_ubyte(&mut self.writer, tag)?;
+                raw::write_str(&mut self.writer, &name)
+            }
+            Some(Frame::List {
+                element_type,
+                remaining,
+                ..
+            }) if *element_type == tag && *remaining > 0 => {
+                *remaining -= 1;
+                Ok(())
+            }
+            _ => Err(unexpected(tag_name![tag])),
+        }
+    }
+}
+
+fn unexpected(what: &str) -> TagEncodeError {
+    TagEncodeError::Serde(format!("unexpected {} in event stream", what))
+}
+
+fn length(len: usize) -> EncodeResult<i32> {
+    i32::try_from(len).map_err(|_| TagEncodeError::Serde(format!("length {} too large", len)))
+}
+
+fn write_scalar<W: Write>(dst: &mut W, tag: &Tag) -> EncodeResult<()> {
+    match tag {
+        Tag::Byte(v) => raw::write_byte(dst, *v),
+        Tag::Short(v) => raw::write_short(dst, *v),
+        Tag::Int(v) => raw::write_int(dst, *v),
+        Tag::Long(v) => raw::write_long(dst, *v),
+        Tag::Float(v) => raw::write_float(dst, *v),
+        Tag::Double(v) => raw::write_double(dst, *v),
+        Tag::String(v) => raw::write_str(dst, v),
+        _ => Err(unexpected("non scalar tag")),
+    }
+}
+
+fn write_chunk<W: Write>(dst: &mut W, chunk: &ArrayChunk) -> EncodeResult<()> {
+    match chunk {
+        ArrayChunk::Byte(v) => v.iter().try_for_each(|b| raw::write_byte(dst, *b)),
+        ArrayChunk::Int(v) => v.iter().try_for_each(|i| raw::write_int(dst, *i)),
+        ArrayChunk::Long(v) => v.iter().try_for_each(|l| raw::write_long(dst, *l)),
+    }
+}
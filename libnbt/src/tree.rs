@@ -0,0 +1,237 @@
+//! Indented tree view of NBT data, for people rather than programs.
+//!
+//! ```text
+//! {}  Player: 4 entries
+//! ├── S   HP: 20
+//! ├── ""  Name: "Steve"
+//! ├── []  Pos: 2 entries
+//! │   ├── D   1.5
+//! │   └── D   -0.0
+//! └── I[] UUID: 4 ints: 1 -2 3 4
+//! ```
+//!
+//! Every tag starts with the icon of its type, then its key when inside a compound.
+//! Compounds and lists give their entry count, arrays their length along with as many
+//! values as fit in [`TreeOptions::width`].
+
+use std::fmt::{self, Display, Formatter};
+
+use crate::{CompoundTag, Tag};
+
+/// Icons of the tag types, indexed by tag type id.
+const ICONS: [&str; 13] = [
+    "", "B", "S", "I", "L", "F", "D", "B[]", "\"\"", "[]", "{}", "I[]", "L[]",
+];
+/// Width icons are padded to, so that names line up.
+const ICON_WIDTH: usize = 3;
+
+const BRANCH: &str = "├── ";
+const LAST_BRANCH: &str = "└── ";
+const INDENT: &str = "│   ";
+const LAST_INDENT: &str = "    ";
+
+const ELLIPSIS: char = '…';
+
+/// ANSI colours of the parts of a line.
+const ICON_COLOR: &str = "\x1b[2m";
+const NAME_COLOR: &str = "\x1b[1;34m";
+const STRING_COLOR: &str = "\x1b[32m";
+const NUMBER_COLOR: &str = "\x1b[33m";
+const COUNT_COLOR: &str = "\x1b[36m";
+const RESET: &str = "\x1b[0m";
+
+/// How [`CompoundTag::tree`] lays out the tree.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TreeOptions {
+    /// Levels of nesting shown below the root, deeper compounds and lists only give
+    /// their entry count. `None` shows everything.
+    pub depth: Option<usize>,
+    /// Characters a value may take before it is cut short with `…`
+    pub width: usize,
+    /// Colours the icons, names and values with ANSI escape codes
+    pub colors: bool,
+}
+
+impl Default for TreeOptions {
+    fn default() -> Self {
+        Self {
+            depth: None,
+            width: 64,
+            colors: false,
+        }
+    }
+}
+
+/// Icon of the tag type in the tree, e.g. `I[]` for `TAG_Int_Array`. Empty for
+/// `TAG_End` and unknown types.
+pub fn icon(tag_type: u8) -> &'static str {
+    ICONS.get(tag_type as usize).copied().unwrap_or_default()
+}
+
+/// Tree view of a compound, see [`CompoundTag::tree`].
+#[derive(Debug, Clone, Copy)]
+pub struct Tree<'a> {
+    root: &'a CompoundTag,
+    options: TreeOptions,
+}
+
+impl CompoundTag {
+    /// Displays the compound as an indented tree, see the [`tree`](crate::tree) module.
+    pub fn tree(&self, options: TreeOptions) -> Tree<'_> {
+        Tree {
+            root: self,
+            options,
+        }
+    }
+}
+
+impl Display for Tree<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let name = self.root.name.as_deref().filter(|name| !name.is_empty());
+        let mut line = Line {
+            f,
+            options: &self.options,
+        };
+
+        line.icon(crate::consts::TAG_COMPOUND)?;
+        if let Some(name) = name {
+            line.name(name)?;
+        }
+        line.count(self.root.map.len(), "entry", "entries")?;
+        writeln!(line.f)?;
+
+        if self.options.depth != Some(0) {
+            let mut entries = self.root.iter().map(|(key, tag)| (Some(key.as_ref()), tag));
+            line.children(&mut entries, self.root.map.len(), "", 1)?;
+        }
+
+        Ok(())
+    }
+}
+
+struct Line<'a, 'f> {
+    f: &'a mut Formatter<'f>,
+    options: &'a TreeOptions,
+}
+
+impl<'a> Line<'a, '_> {
+    fn children<'t>(
+        &mut self,
+        entries: &mut dyn Iterator<Item = (Option<&'t str>, &'t Tag)>,
+        len: usize,
+        prefix: &str,
+        level: usize,
+    ) -> fmt::Result {
+        for (i, (name, tag)) in entries.enumerate() {
+            let last = i + 1 == len;
+            let branch = if last { LAST_BRANCH } else { BRANCH };
+            write!(self.f, "{}{}", prefix, branch)?;
+            self.node(name, tag)?;
+            writeln!(self.f)?;
+
+            if self.options.depth.is_some_and(|depth| level >= depth) {
+                continue;
+            }
+            let indent = if last { LAST_INDENT } else { INDENT };
+            let prefix = format!("{}{}", prefix, indent);
+            match tag {
+                Tag::Compound(map) => {
+                    let mut entries = map.iter().map(|(key, tag)| (Some(key.as_ref()), tag));
+                    self.children(&mut entries, map.len(), &prefix, level + 1)?
+                }
+                Tag::List(list) => {
+                    let mut entries = list.into_iter().map(|tag| (None, tag));
+                    self.children(&mut entries, list.len(), &prefix, level + 1)?
+                }
+                _ => {}
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Writes the line of a single tag, without its children.
+    fn node(&mut self, name: Option<&str>, tag: &Tag) -> fmt::Result {
+        self.icon(tag.tag_type())?;
+        if let Some(name) = name {
+            self.name(name)?;
+        }
+
+        match tag {
+            Tag::Byte(v) => self.number(v),
+            Tag::Short(v) => self.number(v),
+            Tag::Int(v) => self.number(v),
+            Tag::Long(v) => self.number(v),
+            Tag::Float(v) => self.number(format_args!("{:?}", v)),
+            Tag::Double(v) => self.number(format_args!("{:?}", v)),
+            Tag::String(text) => {
+                let text = truncate(format!("{:?}", text), self.options.width);
+                self.paint(STRING_COLOR, text)
+            }
+            Tag::List(list) => self.count(list.len(), "entry", "entries"),
+            Tag::Compound(map) => self.count(map.len(), "entry", "entries"),
+            Tag::ByteArray(array) => self.array(array, "byte", "bytes"),
+            Tag::IntArray(array) => self.array(array, "int", "ints"),
+            Tag::LongArray(array) => self.array(array, "long", "longs"),
+        }
+    }
+
+    fn icon(&mut self, tag_type: u8) -> fmt::Result {
+        let icon = format!("{:<width$} ", icon(tag_type), width = ICON_WIDTH);
+        self.paint(ICON_COLOR, icon)
+    }
+
+    fn name(&mut self, name: &str) -> fmt::Result {
+        self.paint(NAME_COLOR, name)?;
+        write!(self.f, ": ")
+    }
+
+    fn number(&mut self, value: impl Display) -> fmt::Result {
+        self.paint(NUMBER_COLOR, value)
+    }
+
+    fn count(&mut self, len: usize, one: &str, many: &str) -> fmt::Result {
+        let noun = if len == 1 { one } else { many };
+        self.paint(COUNT_COLOR, format_args!("{} {}", len, noun))
+    }
+
+    fn array<T: Display>(&mut self, array: &[T], one: &str, many: &str) -> fmt::Result {
+        self.count(array.len(), one, many)?;
+        if array.is_empty() {
+            return Ok(());
+        }
+
+        // Only as many values as can be seen are formatted
+        let mut values = String::new();
+        for value in array {
+            if values.chars().count() > self.options.width {
+                break;
+            }
+            if !values.is_empty() {
+                values.push(' ');
+            }
+            values.push_str(&value.to_string());
+        }
+
+        write!(self.f, ": ")?;
+        self.number(truncate(values, self.options.width))
+    }
+
+    fn paint(&mut self, color: &str, text: impl Display) -> fmt::Result {
+        match self.options.colors {
+            true => write!(self.f, "{}{}{}", color, text, RESET),
+            false => write!(self.f, "{}", text),
+        }
+    }
+}
+
+/// Cuts `text` down to `width` characters, the last being `…` if anything was left out.
+fn truncate(mut text: String, width: usize) -> String {
+    if let Some((cut, _)) = text.char_indices().nth(width) {
+        let keep = text[..cut].char_indices().last().map_or(0, |(i, _)| i);
+        text.truncate(keep);
+        text.push(ELLIPSIS);
+    }
+
+    text
+}
@@ -0,0 +1,78 @@
+use std::borrow::Cow;
+
+use nbt::borrow::TagRef;
+use nbt::decode::{read_nbt, read_nbt_ref};
+use nbt::{to_vec, Tag};
+
+use crate::common::sample;
+
+#[test]
+fn borrowed_matches_owned() {
+    let buf = to_vec(&sample()).unwrap();
+    let borrowed = read_nbt_ref(&buf).unwrap();
+
+    assert_eq!(borrowed.to_compound(), read_nbt(buf.as_slice()).unwrap());
+    assert_eq!(borrowed.to_compound(), sample());
+}
+
+#[test]
+fn borrowed_strings_and_arrays() {
+    let buf = to_vec(&sample()).unwrap();
+    let data = read_nbt_ref(&buf).unwrap();
+
+    assert!(matches!(
+        data.get("Name"),
+        Some(TagRef::String(Cow::Borrowed("Steve")))
+    ));
+
+    match data.get("Longs") {
+        Some(TagRef::LongArray(arr)) => {
+            assert_eq!(arr.len(), 2);
+            assert_eq!(arr.get(1), Some(i64::MAX));
+            assert_eq!(arr.get(2), None);
+            // The end of the element would be past usize::MAX
+            assert_eq!(arr.get(usize::MAX / 8), None);
+            assert_eq!(arr.iter().collect::<Vec<_>>(), vec![i64::MIN, i64::MAX]);
+        }
+        tag => panic!("expected long array, found {:?}", tag),
+    }
+
+    assert_eq!(data.get("Bytes"), Some(&TagRef::ByteArray(&[-128, 0, 127])));
+}
+
+#[test]
+fn borrowed_mutf8() {
+    // "a\0b" with the null written as the two byte MUTF-8 form
+    let buf = [
+        0x0A, 0, 0, 8, 0, 1, b's', 0, 4, b'a', 0xC0, 0x80, b'b', 0,
+    ];
+    let data = read_nbt_ref(&buf).unwrap();
+    assert_eq!(data.get("s"), Some(&TagRef::String(Cow::Owned("a\0b".into()))));
+    assert_eq!(
+        read_nbt(buf.as_slice()).unwrap().get("s"),
+        Some(&Tag::String("a\0b".into()))
+    );
+}
+
+#[test]
+fn borrowed_truncated() {
+    let buf = to_vec(&sample()).unwrap();
+    for len in [0, 5, buf.len() / 2, buf.len() - 1] {
+        assert!(read_nbt_ref(&buf[..len]).is_err());
+    }
+}
+
+#[test]
+fn borrowed_huge_list_length() {
+    // A list of longs claiming far more elements than the data holds
+    let mut buf = vec![10, 0, 0, 9, 0, 4, b'L', b'o', b'n', b'g', 4];
+    buf.extend_from_slice(&i32::MAX.to_be_bytes());
+    buf.extend_from_slice(&[0; 64]);
+    assert!(read_nbt_ref(&buf).is_err());
+
+    // Empty compounds take a byte each
+    let mut buf = vec![10, 0, 0, 9, 0, 0, 10];
+    buf.extend_from_slice(&i32::MAX.to_be_bytes());
+    buf.extend_from_slice(&[0; 4096]);
+    assert!(read_nbt_ref(&buf).is_err());
+}
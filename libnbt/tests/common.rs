@@ -0,0 +1,65 @@
+//! Documents shared by the tests.
+
+use nbt::consts::TAG_COMPOUND;
+use nbt::{CompoundTag, ListTag, Map, Tag};
+
+/// Player-like data holding every tag type but compound lists, with an empty typed list
+/// and a long array spanning more than one stream chunk.
+pub fn sample() -> CompoundTag {
+    let mut pos = ListTag::new();
+    pos.push(Tag::Double(1.5)).unwrap();
+    pos.push(Tag::Double(-0.0)).unwrap();
+
+    let mut root = CompoundTag::new();
+    root.push("HP".into(), Tag::Short(20));
+    root.push("Name".into(), Tag::String("Steve".into()));
+    root.push("Pos".into(), Tag::List(pos));
+    root.push("Empty".into(), Tag::List(ListTag::with_type(TAG_COMPOUND).unwrap()));
+    root.push("Bytes".into(), Tag::ByteArray(vec![-128, 0, 127]));
+    root.push("Biomes".into(), Tag::IntArray(vec![7, 8]));
+    root.push("Longs".into(), Tag::LongArray(vec![i64::MIN, i64::MAX]));
+    root.push("Heights".into(), Tag::LongArray((0..5000).collect()));
+    root.push("Speed".into(), Tag::Float(0.1));
+    root.push("Far".into(), Tag::Double(f64::NEG_INFINITY));
+    root
+}
+
+/// [`sample`] along with a nested level holding a list of sections.
+pub fn chunk() -> CompoundTag {
+    let mut section = Map::new();
+    section.insert("Y".into(), Tag::Byte(-4));
+    section.insert("BlockLight".into(), Tag::ByteArray(vec![0; 2048]));
+
+    let mut sections = ListTag::new();
+    sections.push(Tag::Compound(section)).unwrap();
+
+    let mut level = Map::new();
+    level.insert("Status".into(), Tag::String("full".into()));
+    level.insert("Sections".into(), Tag::List(sections));
+
+    let mut root = sample();
+    root.push("Level".into(), Tag::Compound(level));
+    root.push("DataVersion".into(), Tag::Int(3465));
+    root
+}
+
+/// Named root holding a list of compounds, small enough to print in full.
+pub fn player() -> CompoundTag {
+    let mut pos = ListTag::new();
+    pos.push(Tag::Double(1.5)).unwrap();
+    pos.push(Tag::Double(-0.0)).unwrap();
+
+    let mut item = Map::new();
+    item.insert("id".into(), Tag::String("minecraft:stone".into()));
+    item.insert("Count".into(), Tag::Byte(64));
+    let mut inventory = ListTag::new();
+    inventory.push(Tag::Compound(item)).unwrap();
+
+    let mut root = CompoundTag::named("Player".into());
+    root.push("HP".into(), Tag::Short(20));
+    root.push("Name".into(), Tag::String("Steve".into()));
+    root.push("Pos".into(), Tag::List(pos));
+    root.push("Inventory".into(), Tag::List(inventory));
+    root.push("UUID".into(), Tag::IntArray(vec![1, -2, 3, 4]));
+    root
+}
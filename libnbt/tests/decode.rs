@@ -1,6 +1,6 @@
 use std::io::Cursor;
 
-use nbt::{decode::*, Tag};
+use nbt::{decode::*, ListTag, Tag};
 
 #[test]
 fn simple_decode() {
@@ -58,11 +58,11 @@ fn simple_decode_list_seq() {
     assert_eq!(data.name.as_deref(), None);
     assert_eq!(
         data.get("Disabled"),
-        Some(&Tag::List(vec![
-            Tag::Short(1),
-            Tag::Short(2),
-            Tag::Short(3)
-        ]))
+        Some(&Tag::List(
+            vec![Tag::Short(1), Tag::Short(2), Tag::Short(3)]
+                .try_into()
+                .unwrap()
+        ))
     );
 }
 
@@ -75,5 +75,96 @@ fn simple_decode_list_seq_empty() {
     ]);
     let data = read_nbt(&mut buff).unwrap();
     assert_eq!(data.name.as_deref(), None);
-    assert_eq!(data.get("Disabled"), Some(&Tag::List(vec![])));
+    assert_eq!(data.get("Disabled"), Some(&Tag::List(ListTag::new())));
+}
+
+#[test]
+fn simple_decode_list_seq_empty_typed() {
+    let mut buff = Cursor::new([
+        0x0A, 0, 0, 9, 0, 5, b'I', b't', b'e', b'm', b's', // headers
+        10,   // TAG ID for TAG_Compound
+        0, 0, 0, 0, // Length 0
+        0, // NBT end
+    ]);
+    let data = read_nbt(&mut buff).unwrap();
+    match data.get("Items") {
+        Some(Tag::List(list)) => {
+            assert!(list.is_empty());
+            assert_eq!(list.element_type(), 10);
+        }
+        tag => panic!("expected list, found {:?}", tag),
+    }
+}
+
+/// `Level.Sections[1].Palette` with an unknown list element type, plus the offset
+/// right past the broken list header.
+fn corrupt_chunk() -> (Vec<u8>, u64) {
+    let mut palette = ListTag::new();
+    palette.push(Tag::String("minecraft:air".into())).unwrap();
+
+    let mut sections = ListTag::new();
+    for y in 0..2 {
+        let mut section = nbt::Map::new();
+        section.insert("Y".into(), Tag::Byte(y));
+        section.insert("Palette".into(), Tag::List(palette.clone()));
+        sections.push(Tag::Compound(section)).unwrap();
+    }
+
+    let mut level = nbt::Map::new();
+    level.insert("Sections".into(), Tag::List(sections));
+    let mut root = nbt::CompoundTag::new();
+    root.push("Level".into(), Tag::Compound(level));
+
+    let mut buf = nbt::to_vec(&root).unwrap();
+    let key = buf.windows(7).rposition(|w| w == b"Palette").unwrap();
+    buf[key + 7] = 0x20;
+    (buf, (key + 7 + 1 + 4) as u64)
+}
+
+#[test]
+fn decode_error_location() {
+    let (buf, offset) = corrupt_chunk();
+
+    let err = read_nbt(buf.as_slice()).unwrap_err();
+    assert_eq!(err.path(), Some("Level.Sections[1].Palette"));
+    assert_eq!(err.offset(), Some(offset));
+    assert!(err.to_string().contains("in `Level.Sections[1].Palette`"));
+
+    let err = read_nbt_ref(&buf).unwrap_err();
+    assert_eq!(err.path(), Some("Level.Sections[1].Palette"));
+    assert_eq!(err.offset(), Some(offset));
+
+    let err = nbt::stream::EventReader::new(buf.as_slice())
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap_err();
+    assert_eq!(err.path(), Some("Level.Sections[1].Palette"));
+    assert_eq!(err.offset(), Some(offset));
+    assert!(matches!(err.inner(), nbt::err::TagDecodeError::UnknownTagType { tag_type_id: 0x20 }));
+}
+
+#[test]
+fn decode_error_truncated() {
+    let (buf, _) = corrupt_chunk();
+    let key = buf.windows(1).position(|w| w == b"Y").unwrap();
+    let buf = &buf[..key + 1];
+
+    let err = read_nbt(buf).unwrap_err();
+    assert_eq!(err.path(), Some("Level.Sections[0].Y"));
+    assert_eq!(err.offset(), Some(buf.len() as u64));
+    assert!(matches!(err.inner(), nbt::err::TagDecodeError::IOError(_)));
+
+    let err = read_nbt(&buf[..3]).unwrap_err();
+    assert_eq!(err.path(), Some(""));
+}
+
+#[test]
+fn tag_path_joins() {
+    use nbt::err::{join_index, join_key};
+
+    assert_eq!(join_key("Level", ""), "Level");
+    assert_eq!(join_key("Sections", "[1].Y"), "Sections[1].Y");
+    assert_eq!(join_key("Level", "Sections[1]"), "Level.Sections[1]");
+    assert_eq!(join_index(1, ""), "[1]");
+    assert_eq!(join_index(0, "[2]"), "[0][2]");
+    assert_eq!(join_index(1, "Palette"), "[1].Palette");
 }
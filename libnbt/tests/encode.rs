@@ -1,4 +1,5 @@
-use nbt::{encode::*, CompoundTag, Tag};
+use nbt::err::ListTagError;
+use nbt::{encode::*, CompoundTag, ListTag, Tag};
 
 #[test]
 fn simple_encode() {
@@ -37,3 +38,104 @@ fn simple_encode2() {
     assert_eq!(&buf[3..8], &[4, 0, 2, b'H', b'P']);
     assert_eq!(&buf[8..buf.len() - 1], &[0, 0, 0, 0, 0, 0, 0, 0x1B])
 }
+
+#[test]
+fn simple_encode_list_empty_typed() {
+    let data = {
+        let mut tmp = CompoundTag::new();
+        tmp.push("L".into(), Tag::List(ListTag::with_type(3).unwrap()));
+        tmp
+    };
+
+    let mut buf: Vec<u8> = Vec::with_capacity(13);
+    write_nbt(&data, &mut buf).unwrap();
+
+    // List header, element type and length
+    assert_eq!(&buf[3..6], &[9, 0, 1]);
+    assert_eq!(&buf[7..buf.len() - 1], &[3, 0, 0, 0, 0])
+}
+
+#[test]
+fn simple_encode_list() {
+    let data = {
+        let mut list = ListTag::new();
+        list.push(Tag::Short(1)).unwrap();
+        list.push(Tag::Short(2)).unwrap();
+
+        let mut tmp = CompoundTag::new();
+        tmp.push("L".into(), Tag::List(list));
+        tmp
+    };
+
+    let mut buf: Vec<u8> = Vec::with_capacity(13);
+    write_nbt(&data, &mut buf).unwrap();
+
+    assert_eq!(&buf[7..buf.len() - 1], &[2, 0, 0, 0, 2, 0, 1, 0, 2])
+}
+
+#[test]
+fn list_rejects_mixed_types() {
+    let mut list = ListTag::new();
+    list.push(Tag::Int(1)).unwrap();
+
+    assert_eq!(
+        list.push(Tag::String("x".into())),
+        Err(ListTagError::TypeMismatch {
+            found: 8,
+            expected: 3
+        })
+    );
+    assert_eq!(list.len(), 1);
+    assert!(ListTag::try_from(vec![Tag::Int(1), Tag::Long(2)]).is_err());
+}
+
+#[test]
+fn simple_encode_int_array() {
+    let data = {
+        let mut tmp = CompoundTag::new();
+        tmp.push("A".into(), Tag::IntArray(vec![1, 2]));
+        tmp
+    };
+
+    let mut buf: Vec<u8> = Vec::with_capacity(20);
+    write_nbt(&data, &mut buf).unwrap();
+
+    // Int array header and length
+    assert_eq!(&buf[3..10], &[11, 0, 1, b'A', 0, 0, 0]);
+    assert_eq!(&buf[10..buf.len() - 1], &[2, 0, 0, 0, 1, 0, 0, 0, 2])
+}
+
+#[test]
+fn list_edits_keep_type() {
+    let mut list = ListTag::try_from(vec![Tag::Int(1), Tag::Int(2)]).unwrap();
+
+    list.insert(0, Tag::Int(0)).unwrap();
+    list.swap(1, 2);
+    assert_eq!(list.set(2, Tag::Int(3)), Ok(Tag::Int(1)));
+    assert!(list.set(0, Tag::Long(0)).is_err());
+    assert!(list.insert(3, Tag::String("x".into())).is_err());
+    assert_eq!(list.as_slice(), &[Tag::Int(0), Tag::Int(2), Tag::Int(3)]);
+
+    // Emptied lists are still written as lists of ints
+    while !list.is_empty() {
+        list.remove(0);
+    }
+    assert_eq!(list.element_type(), 3);
+    assert!(list.push(Tag::Long(0)).is_err());
+}
+
+#[test]
+fn list_update_in_place() {
+    let mut list = ListTag::try_from(vec![Tag::Int(1), Tag::Int(2)]).unwrap();
+
+    assert_eq!(list.update(1, |tag| *tag = Tag::Int(5)), Some(()));
+    assert_eq!(list.update(2, |_| ()), None);
+    assert_eq!(list.as_slice(), &[Tag::Int(1), Tag::Int(5)]);
+}
+
+#[test]
+#[should_panic(expected = "list element changed its type")]
+fn list_update_keeps_type() {
+    let mut list = ListTag::try_from(vec![Tag::Int(1)]).unwrap();
+    list.update(0, |tag| *tag = Tag::String("x".into()));
+}
@@ -0,0 +1,88 @@
+#![cfg(feature = "json")]
+
+use nbt::err::JsonError;
+use nbt::json::{from_reader, from_value, to_value, JsonMode};
+use nbt::{to_vec, Tag};
+use serde_json::json;
+
+use crate::common::sample;
+
+#[test]
+fn json_typed_round_trip() {
+    // A NaN other than the usual one is kept as bits
+    let mut root = sample();
+    root.push("Odd".into(), Tag::Float(f32::from_bits(0x7fc0_0001)));
+    let value = to_value(&root, JsonMode::Typed);
+
+    assert_eq!(value["value"]["HP"], json!({"type": "short", "value": 20}));
+    assert_eq!(value["value"]["Speed"], json!({"type": "float", "value": 0.1}));
+    assert_eq!(value["value"]["Odd"]["value"], "0x7fc00001");
+    assert_eq!(value["value"]["Far"]["value"], "-Infinity");
+    assert_eq!(
+        value["value"]["Empty"],
+        json!({"type": "list", "element_type": "compound", "value": []})
+    );
+
+    // Through text, as written by nbtc
+    let text = serde_json::to_string_pretty(&value).unwrap();
+    let back = from_reader(text.as_bytes()).unwrap();
+    assert_eq!(to_vec(&back).unwrap(), to_vec(&root).unwrap());
+    let keys: Vec<_> = back.iter().map(|(key, _)| key.as_ref()).collect();
+    let expected: Vec<_> = root.iter().map(|(key, _)| key.as_ref()).collect();
+    assert_eq!(keys, expected);
+}
+
+#[test]
+fn json_plain() {
+    let value = to_value(&sample(), JsonMode::Plain);
+
+    assert_eq!(value["HP"], json!(20));
+    assert_eq!(value["Pos"], json!([1.5, -0.0]));
+    assert_eq!(value["Empty"], json!([]));
+    assert_eq!(value["Bytes"], json!([-128, 0, 127]));
+    assert_eq!(value["Speed"], json!(0.1));
+    assert_eq!(value["Far"], json!(null));
+}
+
+#[test]
+fn json_typed_errors() {
+    let located = |value| match from_value(&value).unwrap_err() {
+        JsonError::Context { path, source } => (path, *source),
+        other => panic!("error without location: {:?}", other),
+    };
+
+    let (path, err) = located(json!({"type": "compound", "value": {
+        "Level": {"type": "compound", "value": {
+            "Sections": {"type": "list", "value": [
+                {"type": "int", "value": 1},
+                {"type": "byte", "value": 1}
+            ]}
+        }}
+    }}));
+    assert_eq!(path, "Level.Sections[1]");
+    assert!(matches!(
+        err,
+        JsonError::List(nbt::err::ListTagError::TypeMismatch { .. })
+    ));
+
+    let (path, err) = located(json!({"type": "compound", "value": {
+        "b": {"type": "byte", "value": 300}
+    }}));
+    assert_eq!(path, "b");
+    assert!(matches!(err, JsonError::InvalidValue("byte")));
+
+    let (path, err) = located(json!({"type": "compound", "value": {
+        "a": {"type": "int_array", "value": [1, "2"]}
+    }}));
+    assert_eq!(path, "a[1]");
+    assert!(matches!(err, JsonError::InvalidValue("int_array")));
+
+    let (_, err) = located(json!({"type": "compound", "value": {"x": {"type": "uint", "value": 1}}}));
+    assert!(matches!(err, JsonError::UnknownType(name) if name == "uint"));
+
+    assert!(matches!(from_value(&json!({"HP": 20})), Err(JsonError::NotTyped)));
+    assert!(matches!(
+        from_value(&json!({"type": "int", "value": 1})),
+        Err(JsonError::RootMustBeCompound)
+    ));
+}
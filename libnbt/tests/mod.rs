@@ -1,2 +1,9 @@
+mod common;
+
 mod encode;
 mod decode;
+mod typed;
+mod borrow;
+mod stream;
+mod json;
+mod tree;
@@ -0,0 +1,177 @@
+use nbt::consts::*;
+use nbt::decode::read_nbt;
+use nbt::stream::{ArrayChunk, Event, EventReader, EventWriter, ARRAY_CHUNK_LEN};
+use nbt::{to_vec, CompoundTag, ListTag, Tag};
+
+use crate::common::{chunk, sample};
+
+#[test]
+fn stream_events() {
+    let mut data = CompoundTag::new();
+    data.push("HP".into(), Tag::Int(0x1B));
+    data.push("Pos".into(), {
+        let mut pos = ListTag::new();
+        pos.push(Tag::Short(4)).unwrap();
+        Tag::List(pos)
+    });
+    data.push("Bytes".into(), Tag::ByteArray(vec![1, -1]));
+
+    let buf = to_vec(&data).unwrap();
+    let events: Vec<Event> = EventReader::new(buf.as_slice())
+        .collect::<Result<_, _>>()
+        .unwrap();
+
+    assert_eq!(
+        events,
+        vec![
+            Event::StartCompound(None),
+            Event::Key("HP".into()),
+            Event::Scalar(Tag::Int(0x1B)),
+            Event::Key("Pos".into()),
+            Event::StartList(TAG_SHORT, 1),
+            Event::Scalar(Tag::Short(4)),
+            Event::End,
+            Event::Key("Bytes".into()),
+            Event::StartArray(TAG_BYTE_ARRAY, 2),
+            Event::ArrayChunk(ArrayChunk::Byte(vec![1, -1])),
+            Event::End,
+            Event::End,
+        ]
+    );
+}
+
+#[test]
+fn stream_round_trip() {
+    let buf = to_vec(&sample()).unwrap();
+
+    let mut writer = EventWriter::new(Vec::new());
+    let mut chunks = 0;
+    for event in EventReader::new(buf.as_slice()) {
+        let event = event.unwrap();
+        if let Event::ArrayChunk(chunk) = &event {
+            assert!(chunk.len() <= ARRAY_CHUNK_LEN);
+            chunks += 1;
+        }
+        writer.write_event(event).unwrap();
+    }
+
+    // Two for the heights, one for each of the smaller arrays
+    assert_eq!(chunks, 5);
+    assert!(writer.is_finished());
+    let out = writer.into_inner();
+    assert_eq!(out, buf);
+    assert_eq!(read_nbt(out.as_slice()).unwrap(), sample());
+}
+
+#[test]
+fn stream_writer_rejects_bad_events() {
+    let mut writer = EventWriter::new(Vec::new());
+    assert!(writer.write_event(Event::Key("HP".into())).is_err());
+
+    let mut writer = EventWriter::new(Vec::new());
+    writer.write_event(Event::StartCompound(None)).unwrap();
+    // Compound entries need a key first
+    assert!(writer.write_event(Event::Scalar(Tag::Int(1))).is_err());
+
+    writer.write_event(Event::Key("Pos".into())).unwrap();
+    writer.write_event(Event::StartList(TAG_INT, 2)).unwrap();
+    writer.write_event(Event::Scalar(Tag::Int(1))).unwrap();
+    assert!(writer.write_event(Event::Scalar(Tag::Short(2))).is_err());
+    // Closing before all announced elements were written
+    assert!(writer.write_event(Event::End).is_err());
+}
+
+#[test]
+fn stream_root_name() {
+    let mut writer = EventWriter::new(Vec::new());
+    writer.write_event(Event::StartCompound(Some("Level".into()))).unwrap();
+    writer.write_event(Event::End).unwrap();
+    let buf = writer.into_inner();
+
+    assert_eq!(buf, [0x0A, 0, 5, b'L', b'e', b'v', b'e', b'l', 0]);
+    let mut reader = EventReader::new(buf.as_slice());
+    assert_eq!(
+        reader.next_event().unwrap(),
+        Some(Event::StartCompound(Some("Level".into())))
+    );
+    assert_eq!(reader.next_event().unwrap(), Some(Event::End));
+    assert_eq!(reader.next_event().unwrap(), None);
+}
+
+#[test]
+fn stream_truncated() {
+    let buf = to_vec(&sample()).unwrap();
+    let res: Result<Vec<Event>, _> = EventReader::new(&buf[..buf.len() - 1]).collect();
+    assert!(res.is_err());
+}
+
+#[test]
+fn stream_find_path() {
+    use nbt::stream::find_path;
+
+    let buf = to_vec(&chunk()).unwrap();
+    let find = |path: &str| find_path(&mut EventReader::new(buf.as_slice()), path).unwrap();
+
+    assert_eq!(find("DataVersion"), Some(Tag::Int(3465)));
+    assert_eq!(find("Level.Status"), Some(Tag::String("full".into())));
+    match find("Level.Sections") {
+        Some(Tag::List(sections)) => {
+            assert_eq!(sections.len(), 1);
+            assert_eq!(sections.element_type(), TAG_COMPOUND);
+        }
+        tag => panic!("expected list, found {:?}", tag),
+    }
+
+    assert_eq!(find("Level.Missing"), None);
+    assert_eq!(find("DataVersion.Level"), None);
+    assert_eq!(find("Pos.0"), None);
+
+    let mut reader = EventReader::new(buf.as_slice());
+    reader.next_event().unwrap();
+    assert!(find_path(&mut reader, "DataVersion").is_err());
+}
+
+#[test]
+fn stream_skip_value() {
+    let buf = to_vec(&chunk()).unwrap();
+    let mut reader = EventReader::new(buf.as_slice());
+
+    assert!(!reader.skip_value().unwrap());
+    reader.next_event().unwrap();
+    // Next event is a key, not a value
+    assert!(!reader.skip_value().unwrap());
+
+    let mut keys = Vec::new();
+    while let Some(Event::Key(name)) = reader.next_event().unwrap() {
+        if name.as_ref() == "Pos" {
+            assert_eq!(reader.next_event().unwrap(), Some(Event::StartList(TAG_DOUBLE, 2)));
+            assert!(reader.skip_value().unwrap());
+            assert_eq!(reader.read_value().unwrap(), Some(Tag::Double(-0.0)));
+            assert!(!reader.skip_value().unwrap());
+            assert_eq!(reader.next_event().unwrap(), Some(Event::End));
+        } else {
+            assert!(reader.skip_value().unwrap());
+        }
+        keys.push(name);
+    }
+
+    assert_eq!(reader.depth(), 0);
+    assert_eq!(reader.next_event().unwrap(), None);
+    assert_eq!(keys.len(), chunk().len());
+}
+
+#[test]
+fn serde_skips_ignored_fields() {
+    #[derive(serde::Deserialize, Debug, PartialEq)]
+    #[serde(rename_all = "PascalCase")]
+    struct Header {
+        data_version: i32,
+    }
+
+    let buf = to_vec(&chunk()).unwrap();
+    let header: Header = nbt::from_slice(&buf).unwrap();
+    assert_eq!(header, Header { data_version: 3465 });
+
+    // Truncated skipped values still fail
+    assert!(nbt::from_slice::<Header>(&buf[..40]).is_err());
+}
@@ -0,0 +1,66 @@
+use nbt::tree::TreeOptions;
+use nbt::{CompoundTag, Tag};
+
+use crate::common::player;
+
+#[test]
+fn tree_full() {
+    let tree = player().tree(TreeOptions::default()).to_string();
+    assert_eq!(
+        tree,
+        r#"{}  Player: 5 entries
+├── S   HP: 20
+├── ""  Name: "Steve"
+├── []  Pos: 2 entries
+│   ├── D   1.5
+│   └── D   -0.0
+├── []  Inventory: 1 entry
+│   └── {}  2 entries
+│       ├── ""  id: "minecraft:stone"
+│       └── B   Count: 64
+└── I[] UUID: 4 ints: 1 -2 3 4
+"#
+    );
+}
+
+#[test]
+fn tree_depth_and_width() {
+    let mut root = player();
+    root.push("Heights".into(), Tag::LongArray((0..100).collect()));
+    let options = TreeOptions {
+        depth: Some(1),
+        width: 12,
+        ..Default::default()
+    };
+    let tree = root.tree(options).to_string();
+    assert_eq!(
+        tree,
+        r#"{}  Player: 6 entries
+├── S   HP: 20
+├── ""  Name: "Steve"
+├── []  Pos: 2 entries
+├── []  Inventory: 1 entry
+├── I[] UUID: 4 ints: 1 -2 3 4
+└── L[] Heights: 100 longs: 0 1 2 3 4 5…
+"#
+    );
+
+    let only_root = root.tree(TreeOptions { depth: Some(0), ..Default::default() });
+    assert_eq!(only_root.to_string(), "{}  Player: 6 entries\n");
+
+    let mut long = CompoundTag::new();
+    long.push("Text".into(), Tag::String("é".repeat(20).into()));
+    let tree = long.tree(TreeOptions { width: 5, ..Default::default() }).to_string();
+    assert_eq!(tree, "{}  1 entry\n└── \"\"  Text: \"ééé…\n");
+}
+
+#[test]
+fn tree_colors() {
+    let mut root = CompoundTag::new();
+    root.push("HP".into(), Tag::Short(20));
+    let tree = root.tree(TreeOptions { colors: true, ..Default::default() }).to_string();
+
+    assert!(tree.contains("\x1b[1;34mHP\x1b[0m: \x1b[33m20\x1b[0m"));
+    // Nothing is left coloured at the end of a line
+    assert!(tree.lines().all(|line| line.ends_with("\x1b[0m")));
+}
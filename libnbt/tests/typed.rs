@@ -0,0 +1,501 @@
+use serde::{Deserialize, Serialize};
+
+use nbt::{from_gzip_reader, from_slice, from_zlib_reader, to_gzip_writer, to_vec, to_zlib_writer};
+use nbt::err::TagEncodeError;
+use nbt::{CompoundTag, Decoder, Encoder, LongArray, Tag};
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct PlayerData {
+    #[serde(rename = "Health")]
+    health: f32,
+    #[serde(rename = "Pos")]
+    pos: Vec<f64>,
+    #[serde(rename = "OnGround")]
+    on_ground: bool,
+    #[serde(rename = "Abilities")]
+    abilities: Abilities,
+    #[serde(
+        rename = "UUID",
+        serialize_with = "nbt::ser::i32_array",
+        deserialize_with = "nbt::de::i32_array"
+    )]
+    uuid: Vec<i32>,
+    #[serde(rename = "Heightmap")]
+    heightmap: LongArray,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Abilities {
+    flying: i8,
+    #[serde(rename = "walkSpeed")]
+    walk_speed: f32,
+    name: String,
+}
+
+fn player() -> PlayerData {
+    PlayerData {
+        health: 20.0,
+        pos: vec![1.5, 64.0, -3.25],
+        on_ground: true,
+        abilities: Abilities {
+            flying: 0,
+            walk_speed: 0.1,
+            name: "ghosti3".into(),
+        },
+        uuid: vec![1, -2, 3, -4],
+        heightmap: vec![i64::MAX, 0, i64::MIN].into(),
+    }
+}
+
+#[test]
+fn typed_round_trip() {
+    let data = player();
+    let buf = to_vec(&data).unwrap();
+    assert_eq!(from_slice::<PlayerData>(&buf).unwrap(), data);
+}
+
+#[test]
+fn typed_round_trip_gzip() {
+    let data = player();
+    let mut buf = Vec::new();
+    to_gzip_writer(&mut buf, &data).unwrap();
+    assert_eq!(&buf[..2], &[0x1F, 0x8B]);
+    assert_eq!(from_gzip_reader::<PlayerData, _>(buf.as_slice()).unwrap(), data);
+}
+
+#[test]
+fn typed_round_trip_zlib() {
+    let data = player();
+    let mut buf = Vec::new();
+    to_zlib_writer(&mut buf, &data).unwrap();
+    assert_eq!(from_zlib_reader::<PlayerData, _>(buf.as_slice()).unwrap(), data);
+}
+
+#[test]
+fn typed_into_compound() {
+    let buf = to_vec(&player()).unwrap();
+    let data: CompoundTag = from_slice(&buf).unwrap();
+
+    assert_eq!(data.get("UUID"), Some(&Tag::IntArray(vec![1, -2, 3, -4])));
+    assert_eq!(
+        data.get("Heightmap"),
+        Some(&Tag::LongArray(vec![i64::MAX, 0, i64::MIN]))
+    );
+    assert_eq!(data.get("OnGround"), Some(&Tag::Byte(1)));
+}
+
+#[test]
+fn typed_array_rejects_list() {
+    #[derive(Serialize)]
+    struct AsList {
+        #[serde(rename = "Heightmap")]
+        heightmap: Vec<i64>,
+    }
+
+    #[derive(Debug, Deserialize)]
+    struct AsArray {
+        #[serde(rename = "Heightmap")]
+        _heightmap: LongArray,
+    }
+
+    let buf = to_vec(&AsList { heightmap: vec![1] }).unwrap();
+    assert!(from_slice::<AsArray>(&buf).is_err());
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+enum Mob {
+    Zombie,
+    Skeleton(String),
+    Slime(i32, i32),
+    Creeper { fuse: i16, powered: bool },
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Spawner {
+    kind: Mob,
+    variants: Vec<Mob>,
+    fallback: Mob,
+}
+
+#[test]
+fn enum_round_trip() {
+    let data = Spawner {
+        kind: Mob::Zombie,
+        variants: vec![
+            Mob::Skeleton("stray".into()),
+            Mob::Slime(1, 4),
+            Mob::Creeper {
+                fuse: 30,
+                powered: true,
+            },
+        ],
+        fallback: Mob::Slime(2, 2),
+    };
+
+    let buf = to_vec(&data).unwrap();
+    assert_eq!(from_slice::<Spawner>(&buf).unwrap(), data);
+}
+
+#[test]
+fn enum_layout() {
+    let buf = to_vec(&Spawner {
+        kind: Mob::Zombie,
+        variants: vec![],
+        fallback: Mob::Skeleton("x".into()),
+    })
+    .unwrap();
+    let data: CompoundTag = from_slice(&buf).unwrap();
+
+    assert_eq!(data.get("kind"), Some(&Tag::String("Zombie".into())));
+    match data.get("fallback") {
+        Some(Tag::Compound(map)) => {
+            assert_eq!(map.len(), 1);
+            assert_eq!(map.get("Skeleton"), Some(&Tag::String("x".into())));
+        }
+        tag => panic!("expected compound, found {:?}", tag),
+    }
+}
+
+#[test]
+fn enum_root_round_trip() {
+    let data = Mob::Creeper {
+        fuse: 15,
+        powered: false,
+    };
+    let buf = to_vec(&data).unwrap();
+    assert_eq!(from_slice::<Mob>(&buf).unwrap(), data);
+
+    assert!(to_vec(&Mob::Zombie).is_err());
+}
+
+#[test]
+fn enum_mixed_list() {
+    // Unit variants are strings, data variants compounds, a list can't hold both
+    let data = Spawner {
+        kind: Mob::Zombie,
+        variants: vec![Mob::Zombie, Mob::Skeleton("x".into())],
+        fallback: Mob::Zombie,
+    };
+    assert!(matches!(
+        to_vec(&data),
+        Err(TagEncodeError::TagMismatch {
+            found: 10,
+            expected: 8
+        })
+    ));
+}
+
+#[test]
+fn enum_mixed_tuple_variant() {
+    #[derive(Serialize)]
+    enum Slime {
+        Sized(i32, String),
+    }
+
+    #[derive(Serialize)]
+    struct Spawn {
+        slime: Slime,
+    }
+
+    let data = Spawn {
+        slime: Slime::Sized(1, "a".into()),
+    };
+    assert!(matches!(
+        to_vec(&data),
+        Err(TagEncodeError::TagMismatch {
+            found: 8,
+            expected: 3
+        })
+    ));
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Colors {
+    alpha: u8,
+    count: u16,
+    rgb: u32,
+    seed: u64,
+    glyph: char,
+}
+
+fn colors() -> Colors {
+    Colors {
+        alpha: 200,
+        count: u16::MAX,
+        rgb: 0xFF_80_40_20,
+        seed: u64::MAX - 1,
+        glyph: 'é',
+    }
+}
+
+fn unsigned_to_vec<T: Serialize>(value: &T) -> Vec<u8> {
+    let mut encoder = Encoder::new(Vec::new()).map_unsigned(true);
+    value.serialize(&mut encoder).unwrap();
+    encoder.into_inner()
+}
+
+#[test]
+fn unsigned_round_trip() {
+    let buf = unsigned_to_vec(&colors());
+    let mut decoder = Decoder::new(buf.as_slice()).map_unsigned(true);
+    assert_eq!(Colors::deserialize(&mut decoder).unwrap(), colors());
+
+    let data: CompoundTag = from_slice(&buf).unwrap();
+    assert_eq!(data.get("alpha"), Some(&Tag::Byte(200u8 as i8)));
+    assert_eq!(data.get("seed"), Some(&Tag::Long(-2)));
+    assert_eq!(data.get("glyph"), Some(&Tag::String("é".into())));
+}
+
+#[test]
+fn unsigned_is_opt_in() {
+    assert!(matches!(
+        to_vec(&colors()),
+        Err(TagEncodeError::UnrepresentableType("u8"))
+    ));
+
+    // Negative bytes don't fit u8 unless their bits get reinterpreted
+    let buf = unsigned_to_vec(&colors());
+    assert!(from_slice::<Colors>(&buf).is_err());
+}
+
+#[test]
+fn unsigned_overflow() {
+    #[derive(Serialize)]
+    struct Wide {
+        alpha: i16,
+    }
+
+    #[derive(Debug, Deserialize)]
+    struct Narrow {
+        alpha: u8,
+    }
+
+    let buf = to_vec(&Wide { alpha: 255 }).unwrap();
+    let mut decoder = Decoder::new(buf.as_slice()).map_unsigned(true);
+    assert_eq!(Narrow::deserialize(&mut decoder).unwrap().alpha, 255);
+
+    let buf = to_vec(&Wide { alpha: 256 }).unwrap();
+    let mut decoder = Decoder::new(buf.as_slice()).map_unsigned(true);
+    assert!(Narrow::deserialize(&mut decoder).is_err());
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Equipment {
+    name: Option<String>,
+    durability: Option<i16>,
+    slot: i8,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Inventory {
+    hand: Option<Equipment>,
+    offhand: Option<Equipment>,
+    count: i32,
+}
+
+#[test]
+fn option_none_left_out() {
+    let data = Inventory {
+        hand: Some(Equipment {
+            name: None,
+            durability: Some(7),
+            slot: 0,
+        }),
+        offhand: None,
+        count: 1,
+    };
+
+    let buf = to_vec(&data).unwrap();
+    let nbt: CompoundTag = from_slice(&buf).unwrap();
+    assert_eq!(nbt.get("offhand"), None);
+    match nbt.get("hand") {
+        Some(Tag::Compound(map)) => {
+            assert_eq!(map.get("name"), None);
+            assert_eq!(map.get("durability"), Some(&Tag::Short(7)));
+        }
+        tag => panic!("expected compound, found {:?}", tag),
+    }
+
+    assert_eq!(from_slice::<Inventory>(&buf).unwrap(), data);
+}
+
+#[test]
+fn option_missing_key_is_none() {
+    let mut nbt = CompoundTag::new();
+    nbt.push("count".into(), Tag::Int(3));
+    let buf = to_vec(&nbt).unwrap();
+
+    assert_eq!(
+        from_slice::<Inventory>(&buf).unwrap(),
+        Inventory {
+            hand: None,
+            offhand: None,
+            count: 3
+        }
+    );
+}
+
+#[test]
+fn option_none_in_list() {
+    #[derive(Serialize)]
+    struct Slots {
+        slots: Vec<Option<i32>>,
+    }
+
+    assert!(to_vec(&Slots {
+        slots: vec![Some(1), Some(2)]
+    })
+    .is_ok());
+    assert!(matches!(
+        to_vec(&Slots {
+            slots: vec![Some(1), None]
+        }),
+        Err(TagEncodeError::UnrepresentableType(_))
+    ));
+}
+
+#[test]
+fn option_none_in_variant() {
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    enum Name {
+        Custom(Option<String>),
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Named {
+        name: Name,
+    }
+
+    // The variant key is all there is to tell the variant, so it can't be left out
+    let data = Named {
+        name: Name::Custom(Some("Steve".into())),
+    };
+    let buf = to_vec(&data).unwrap();
+    assert_eq!(from_slice::<Named>(&buf).unwrap(), data);
+
+    assert!(matches!(
+        to_vec(&Named {
+            name: Name::Custom(None)
+        }),
+        Err(TagEncodeError::UnrepresentableType(_))
+    ));
+    assert!(to_vec(&Name::Custom(None)).is_err());
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Section {
+    #[serde(rename = "BlockStates")]
+    block_states: LongArray,
+    #[serde(rename = "Biomes")]
+    biomes: nbt::IntArray,
+    #[serde(rename = "SkyLight")]
+    sky_light: nbt::ByteArray,
+}
+
+fn section() -> Section {
+    Section {
+        block_states: (0..4096).map(|i| i * 0x0101_0101_0101).collect(),
+        biomes: (-512..512).collect(),
+        sky_light: (0..20_000).map(|i| i as i8).collect(),
+    }
+}
+
+#[test]
+fn array_bulk_round_trip() {
+    let data = section();
+    let buf = to_vec(&data).unwrap();
+    assert_eq!(from_slice::<Section>(&buf).unwrap(), data);
+
+    let tags: CompoundTag = from_slice(&buf).unwrap();
+    assert_eq!(to_vec(&tags).unwrap(), buf);
+    assert_eq!(
+        tags.get("Biomes"),
+        Some(&Tag::IntArray((-512..512).collect()))
+    );
+
+    // Truncated array payloads fail instead of being padded
+    assert!(from_slice::<Section>(&buf[..buf.len() - 100]).is_err());
+    assert!(from_slice::<CompoundTag>(&buf[..buf.len() - 100]).is_err());
+}
+
+#[test]
+fn array_bulk_writes() {
+    /// Unbuffered writer counting calls to `write`.
+    #[derive(Debug, Default)]
+    struct Counting {
+        writes: usize,
+        data: Vec<u8>,
+    }
+
+    impl std::io::Write for Counting {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.writes += 1;
+            self.data.extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    let data = section();
+    let mut out = Counting::default();
+    nbt::to_writer(&mut out, &data).unwrap();
+
+    assert_eq!(out.data, to_vec(&data).unwrap());
+    assert!(out.writes < 100, "{} writes", out.writes);
+}
+
+#[test]
+fn array_into_plain_vec() {
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Plain {
+        #[serde(rename = "SkyLight")]
+        sky_light: Vec<u8>,
+        #[serde(rename = "Biomes")]
+        biomes: Vec<i32>,
+    }
+
+    let buf = to_vec(&section()).unwrap();
+    let mut decoder = Decoder::new(buf.as_slice()).map_unsigned(true);
+    let plain = Plain::deserialize(&mut decoder).unwrap();
+
+    assert_eq!(plain.sky_light.len(), 20_000);
+    assert_eq!(plain.sky_light[255], 255);
+    assert_eq!(plain.biomes, (-512..512).collect::<Vec<_>>());
+}
+
+#[test]
+fn map_key_and_value_apart() {
+    use serde::ser::SerializeMap;
+
+    /// Gives keys and values in separate calls, unlike derived maps
+    struct Apart;
+
+    impl Serialize for Apart {
+        fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            let mut map = serializer.serialize_map(Some(2))?;
+            map.serialize_key("HP")?;
+            map.serialize_value(&20i16)?;
+            map.serialize_key("Name")?;
+            map.serialize_value("Steve")?;
+            map.end()
+        }
+    }
+
+    let mut expected = std::collections::BTreeMap::new();
+    expected.insert("HP", Tag::Short(20));
+    expected.insert("Name", Tag::String("Steve".into()));
+    assert_eq!(to_vec(&Apart).unwrap(), to_vec(&expected).unwrap());
+}
+
+#[test]
+fn map_non_string_key() {
+    let mut map = std::collections::BTreeMap::new();
+    map.insert(1, 2);
+
+    let err = to_vec(&map).unwrap_err();
+    assert!(matches!(err, TagEncodeError::NonStringMapKey));
+    assert_eq!(err.to_string(), "libnbt: Compound keys must be strings");
+}
@@ -0,0 +1,49 @@
+//! NBT to XML conversion and back, over the libnbt benchmark fixtures.
+
+use std::io::{BufReader, BufWriter};
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+
+use nbt::encode::write_nbt;
+use nbt::stream::EventReader;
+use nbtc::xml::{read::read_xml, write::print_xml, XmlOptions};
+
+#[path = "../../libnbt/benches/fixtures/mod.rs"]
+mod fixtures;
+
+fn xml(c: &mut Criterion) {
+    for (name, data) in fixtures::all() {
+        let mut raw = Vec::new();
+        write_nbt(&data, &mut raw).unwrap();
+
+        let mut xml = BufWriter::new(Vec::new());
+        print_xml(&mut xml, EventReader::new(raw.as_slice()), &XmlOptions::default()).unwrap();
+        let xml = xml.into_inner().unwrap();
+
+        let mut group = c.benchmark_group(format!("xml/{}", name));
+
+        group.throughput(Throughput::Bytes(raw.len() as u64));
+        group.bench_function("print_xml", |b| {
+            b.iter(|| {
+                let mut out = BufWriter::new(Vec::with_capacity(xml.len()));
+                print_xml(
+                    &mut out,
+                    EventReader::new(black_box(raw.as_slice())),
+                    &XmlOptions::default(),
+                )
+                .unwrap();
+                out
+            })
+        });
+
+        group.throughput(Throughput::Bytes(xml.len() as u64));
+        group.bench_function("read_xml", |b| {
+            b.iter(|| read_xml(&mut BufReader::new(black_box(xml.as_slice()))).unwrap())
+        });
+
+        group.finish();
+    }
+}
+
+criterion_group!(benches, xml);
+criterion_main!(benches);
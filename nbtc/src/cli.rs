@@ -6,6 +6,9 @@ use std::str::FromStr;
 
 use crate::err::{self, ConfigErr};
 use crate::util::DataFormat;
+use crate::xml::{FloatFormat, XmlOptions};
+use nbt::json::JsonMode;
+use nbt::tree::TreeOptions;
 
 #[derive(Debug, PartialEq, Eq)]
 pub enum Command {
@@ -13,6 +16,10 @@ pub enum Command {
     Compile,
     Decompile,
     Watch,
+    Tree,
+    Edit,
+    Shell,
+    Schema(SchemaFormat),
 }
 
 impl Command {
@@ -23,15 +30,72 @@ impl Command {
             "compile" | "c" => Some(Self::Compile),
             "decompile" | "d" => Some(Self::Decompile),
             "watch" | "w" => Some(Self::Watch),
+            "tree" | "print" | "t" => Some(Self::Tree),
+            "edit" | "e" => Some(Self::Edit),
+            "shell" | "sh" => Some(Self::Shell),
+            "schema" => Some(Self::Schema(SchemaFormat::Xml)),
             _ => None,
         }
     }
 }
 
+/// Format `nbtc schema` prints the schema of.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SchemaFormat {
+    Xml,
+}
+
+impl FromStr for SchemaFormat {
+    type Err = err::ConfigErr<'static>;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "xml" => Ok(Self::Xml),
+            _ => Err(Self::Err::ArgError("Schema is only available for: xml")),
+        }
+    }
+}
+
+/// Text format NBT data is decompiled to and compiled from.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TextFormat {
+    #[default]
+    Xml,
+    Json(JsonMode),
+    Yaml,
+    Toml,
+}
+
+impl TextFormat {
+    /// Extensions of files in this format, the first is given to files nbtc writes.
+    pub fn extensions(self) -> &'static [&'static str] {
+        match self {
+            Self::Xml => &["xml"],
+            Self::Json(_) => &["json"],
+            Self::Yaml => &["yaml", "yml"],
+            Self::Toml => &["toml"],
+        }
+    }
+}
+
 pub enum Args {
     FileOutput,
     Gzip,
     Zlib,
+    FloatBits,
+    Compact,
+    Json,
+    Plain,
+    Yaml,
+    Toml,
+    Depth,
+    Width,
+    Color,
+    Script,
+    Decompile,
+    Recursive,
+    OutDir,
+    Glob,
 }
 
 impl FromStr for Args {
@@ -43,6 +107,20 @@ impl FromStr for Args {
             "--file" | "-f" => Ok(Self::FileOutput),
             "--gzip" | "-z" => Ok(Self::Gzip),
             "--zlib" => Ok(Self::Zlib),
+            "--float-bits" => Ok(Self::FloatBits),
+            "--compact" => Ok(Self::Compact),
+            "--json" => Ok(Self::Json),
+            "--plain" => Ok(Self::Plain),
+            "--yaml" => Ok(Self::Yaml),
+            "--toml" => Ok(Self::Toml),
+            "--depth" | "-d" => Ok(Self::Depth),
+            "--width" | "-w" => Ok(Self::Width),
+            "--color" => Ok(Self::Color),
+            "--script" | "-s" => Ok(Self::Script),
+            "--decompile" => Ok(Self::Decompile),
+            "--recursive" | "-r" => Ok(Self::Recursive),
+            "--out-dir" | "-o" => Ok(Self::OutDir),
+            "--glob" => Ok(Self::Glob),
             _ => Err(Self::Err::ArgError("Not recognized given argument!")),
         }
     }
@@ -92,6 +170,19 @@ pub struct Config {
     file_input: FdArgument,
     file_out: FdArgument,
     format: DataFormat,
+    text: TextFormat,
+    xml: XmlOptions,
+    tree: TreeOptions,
+    /// Commands `nbtc shell` runs instead of reading them from the terminal
+    script: Option<Box<str>>,
+    /// Whether `nbtc watch` decompiles changed NBT files instead of compiling text
+    watch_decompile: bool,
+    /// Whether FILE is a directory, every file in it is compiled or decompiled
+    recursive: bool,
+    /// Directory files found with `--recursive` are written to, instead of next to them
+    out_dir: Option<Box<str>>,
+    /// Files found with `--recursive` must match one, when given
+    globs: Vec<glob::Pattern>,
 }
 
 impl Config {
@@ -106,6 +197,11 @@ impl Config {
             };
 
             match Command::from_str(cmd.as_ref()) {
+                // Schema format is given right after the command
+                Some(Command::Schema(_)) => match args.next() {
+                    Some(format) => Command::Schema(format.parse()?),
+                    None => return Err(ConfigErr::ArgError("Missing schema format!")),
+                },
                 Some(cmd) => cmd,
                 None => return Err(ConfigErr::BadCommand(Box::leak(cmd))),
             }
@@ -114,6 +210,14 @@ impl Config {
         let fin: OnceCell<FdArgument> = OnceCell::new();
         let fout: OnceCell<FdArgument> = OnceCell::new();
         let dformat: OnceCell<DataFormat> = OnceCell::new();
+        let mut text = TextFormat::default();
+        let mut xml = XmlOptions::default();
+        let mut tree = TreeOptions::default();
+        let mut script = None;
+        let mut watch_decompile = false;
+        let mut recursive = false;
+        let mut out_dir = None;
+        let mut globs = Vec::new();
         while let Some(arg) = args.next() {
             if arg.starts_with("-") && arg.as_ref() != "-" {
                 match Args::from_str(arg.as_ref())? {
@@ -129,6 +233,48 @@ impl Config {
                     Args::Zlib => {
                         let _ = dformat.set(DataFormat::Zlib);
                     }
+                    Args::FloatBits => xml.float_format = FloatFormat::Bits,
+                    Args::Compact => xml.compact_arrays = true,
+                    Args::Json if text == TextFormat::Xml => {
+                        text = TextFormat::Json(JsonMode::Typed)
+                    }
+                    Args::Json => {}
+                    Args::Plain => text = TextFormat::Json(JsonMode::Plain),
+                    Args::Yaml => text = TextFormat::Yaml,
+                    Args::Toml => text = TextFormat::Toml,
+                    Args::Depth => {
+                        tree.depth = Some(
+                            args.next()
+                                .and_then(|depth| depth.parse().ok())
+                                .ok_or(ConfigErr::ArgError("Missing or invalid depth!"))?,
+                        )
+                    }
+                    Args::Width => {
+                        tree.width = args
+                            .next()
+                            .and_then(|width| width.parse().ok())
+                            .ok_or(ConfigErr::ArgError("Missing or invalid width!"))?
+                    }
+                    Args::Color => tree.colors = true,
+                    Args::Script => {
+                        script = Some(
+                            args.next()
+                                .ok_or(ConfigErr::ArgError("Missing script file name!"))?,
+                        )
+                    }
+                    Args::Decompile => watch_decompile = true,
+                    Args::Recursive => recursive = true,
+                    Args::OutDir => {
+                        out_dir = Some(
+                            args.next()
+                                .ok_or(ConfigErr::ArgError("Missing output directory name!"))?,
+                        )
+                    }
+                    Args::Glob => globs.push(
+                        args.next()
+                            .and_then(|glob| glob::Pattern::new(&glob).ok())
+                            .ok_or(ConfigErr::ArgError("Missing or invalid glob pattern!"))?,
+                    ),
                 };
                 continue;
             }
@@ -139,15 +285,52 @@ impl Config {
             };
         }
 
+        let compiles = cmd == Command::Compile || (cmd == Command::Watch && !watch_decompile);
+        if compiles && text == TextFormat::Json(JsonMode::Plain) {
+            return Err(ConfigErr::ArgError("Plain JSON has no tag types, it can't be compiled!"));
+        }
+
+        // Edits are written back to the file they were read from
+        let edits = matches!(cmd, Command::Edit | Command::Shell);
+        if edits && !matches!(fin.get(), Some(FdArgument::File(_))) {
+            return Err(ConfigErr::ArgError("Edit needs a FILE to write back to!"));
+        }
+
+        if cmd == Command::Watch && !matches!(fin.get(), Some(FdArgument::File(_))) {
+            return Err(ConfigErr::ArgError("Watch needs a FILE or DIRECTORY to watch!"));
+        }
+
+        if recursive {
+            if !matches!(cmd, Command::Compile | Command::Decompile) {
+                return Err(ConfigErr::ArgError("Only compile and decompile can be recursive!"));
+            }
+            if !matches!(fin.get(), Some(FdArgument::File(_))) {
+                return Err(ConfigErr::ArgError("Recursive needs a DIRECTORY to convert!"));
+            }
+            if fout.get().is_some() {
+                return Err(ConfigErr::ArgError("Recursive writes many files, use -o DIR!"));
+            }
+        } else if out_dir.is_some() || !globs.is_empty() {
+            return Err(ConfigErr::ArgError("Output directory and globs need --recursive!"));
+        }
+
         fin.get_or_init(|| FdArgument::StdIn);
         fout.get_or_init(|| FdArgument::StdOut);
-        dformat.get_or_init(|| DataFormat::default());
+        dformat.get_or_init(DataFormat::default);
 
         Ok(Self {
             cmd,
             file_out: fout.into_inner().unwrap(),
             file_input: fin.into_inner().unwrap(),
-            format: dformat.into_inner().unwrap()
+            format: dformat.into_inner().unwrap(),
+            text,
+            xml,
+            tree,
+            script,
+            watch_decompile,
+            recursive,
+            out_dir,
+            globs,
         })
     }
 
@@ -165,4 +348,44 @@ impl Config {
     pub fn get_data_format(&self) -> &DataFormat {
         &self.format
     }
+
+    #[inline]
+    pub fn get_text_format(&self) -> TextFormat {
+        self.text
+    }
+
+    #[inline]
+    pub fn get_xml_options(&self) -> &XmlOptions {
+        &self.xml
+    }
+
+    #[inline]
+    pub fn get_tree_options(&self) -> TreeOptions {
+        self.tree
+    }
+
+    #[inline]
+    pub fn get_script(&self) -> Option<&str> {
+        self.script.as_deref()
+    }
+
+    #[inline]
+    pub fn get_watch_decompile(&self) -> bool {
+        self.watch_decompile
+    }
+
+    #[inline]
+    pub fn get_recursive(&self) -> bool {
+        self.recursive
+    }
+
+    #[inline]
+    pub fn get_out_dir(&self) -> Option<&str> {
+        self.out_dir.as_deref()
+    }
+
+    #[inline]
+    pub fn get_globs(&self) -> &[glob::Pattern] {
+        &self.globs
+    }
 }
@@ -1,10 +1,22 @@
-use std::io::{stdout, BufRead, BufReader, BufWriter, Write};
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::fs::File;
+use std::io::{stdout, BufRead, BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+
+use nbt::CompoundTag;
 
 use crate::util::DataFormat;
 use crate::{cli, err};
 
 const HELP_TEXT: &[u8] = include_bytes!("help.txt");
 
+/// Extensions of NBT files, the first is given to files nbtc writes.
+const NBT_EXTENSIONS: &[&str] = &["dat", "nbt"];
+
+/// Writes edited data back to the file, for `nbtc edit` and `nbtc shell`.
+pub(crate) type Save = Box<dyn FnMut(&CompoundTag) -> err::Result<()>>;
+
 pub(crate) fn help() -> err::Result<()> {
     stdout().write_all(HELP_TEXT)?;
 
@@ -19,38 +31,169 @@ pub(crate) fn help() -> err::Result<()> {
 ///
 /// TODO: Fill up the doc.
 pub(crate) fn decompile(config: &cli::Config) -> err::Result<()> {
-    use nbt::decode::*;
+    if config.get_recursive() {
+        return convert_dir(config, true);
+    }
+
+    let (fin, dformat) = open_nbt(config)?;
+    let mut fout = BufWriter::new(config.get_out_file().to_file(true)?);
+
+    write_text(config, fin, dformat, &mut fout)
+}
+
+/// Writes the NBT data read from `fin` as the text format chosen in `config`.
+fn write_text(
+    config: &cli::Config,
+    mut fin: BufReader<File>,
+    dformat: DataFormat,
+    fout: &mut BufWriter<File>,
+) -> err::Result<()> {
+    use crate::xml::write::print_xml;
+    use nbt::stream::EventReader;
+
+    let text_format = config.get_text_format();
+    if text_format != cli::TextFormat::Xml {
+        let nbt_data = read_compound(&mut fin, dformat)?;
+        match text_format {
+            cli::TextFormat::Json(mode) => {
+                nbt::json::to_writer(&mut *fout, &nbt_data, mode)?;
+                fout.write_all(b"\n")?;
+            }
+            cli::TextFormat::Yaml => crate::yaml::write_yaml(&mut *fout, &nbt_data)?,
+            cli::TextFormat::Toml => crate::toml::write_toml(&mut *fout, &nbt_data)?,
+            cli::TextFormat::Xml => unreachable!("streamed below"),
+        }
+        fout.flush()?;
+        return Ok(());
+    }
+
+    // Streamed straight into XML, the NBT tree is never built in memory
+    let options = config.get_xml_options();
+    match dformat {
+        DataFormat::Gzip => print_xml(fout, EventReader::gzip(fin), options),
+        DataFormat::Zlib => print_xml(fout, EventReader::zlib(fin), options),
+        _ => print_xml(fout, EventReader::new(fin), options),
+    }
+}
+
+/// Prints the NBT data as an indented tree, see [`nbt::tree`].
+pub(crate) fn tree(config: &cli::Config) -> err::Result<()> {
+    let (mut fin, dformat) = open_nbt(config)?;
+    let nbt_data = read_compound(&mut fin, dformat)?;
+
+    let mut fout = BufWriter::new(config.get_out_file().to_file(true)?);
+    write!(fout, "{}", nbt_data.tree(config.get_tree_options()))?;
+    fout.flush()?;
+
+    Ok(())
+}
+
+/// Opens the NBT file in the terminal editor, see [`crate::tui`]. Saving writes the file
+/// back with the compression it was read with.
+pub(crate) fn edit(config: &cli::Config) -> err::Result<()> {
+    let (mut fin, dformat) = open_nbt(config)?;
+    let nbt_data = read_compound(&mut fin, dformat)?;
+    drop(fin);
+
+    let file_name = config.get_in_file().to_str();
+    let save = Box::new(move |root: &CompoundTag| save_nbt(file_name, root, dformat));
+    let editor = crate::tui::Editor::new(nbtc::edit::Document::new(nbt_data), file_name, dformat, save);
+    crate::tui::run(editor)
+}
+
+/// Runs shell commands on the NBT file, see [`crate::shell`]. They are read from the
+/// script given, else from standard input, interactively when it is a terminal.
+pub(crate) fn shell(config: &cli::Config) -> err::Result<()> {
+    use std::io::{stdin, IsTerminal};
+
+    let (mut fin, dformat) = open_nbt(config)?;
+    let nbt_data = read_compound(&mut fin, dformat)?;
+    drop(fin);
+
+    let file_name = config.get_in_file().to_str();
+    let save = Box::new(move |root: &CompoundTag| save_nbt(file_name, root, dformat));
+    let mut shell = crate::shell::Shell::new(nbtc::edit::Document::new(nbt_data), file_name, save);
+
+    let mut out = stdout().lock();
+    match config.get_script() {
+        Some(script) => shell.run_script(BufReader::new(File::open(script)?), &mut out),
+        None if !stdin().is_terminal() => shell.run_script(stdin().lock(), &mut out),
+        None => shell.interactive(),
+    }
+}
+
+/// Replaces the file with `root`, keeping the file as it was when saving fails.
+fn save_nbt(file_name: &str, root: &CompoundTag, dformat: DataFormat) -> err::Result<()> {
+    write_atomic(Path::new(file_name), |fout| write_compound(root, fout, dformat))
+}
+
+/// Writes `path` through a file next to it that replaces it once `write` succeeds, so that
+/// a failed write leaves `path` as it was and nobody reads it half written.
+fn write_atomic<F>(path: &Path, write: F) -> err::Result<()>
+where
+    F: FnOnce(&mut BufWriter<File>) -> err::Result<()>,
+{
+    let mut tmp_name = path.as_os_str().to_owned();
+    tmp_name.push(".nbtc-tmp");
+    let res = File::create(&tmp_name).map_err(From::from).and_then(|file| {
+        let mut fout = BufWriter::new(file);
+        write(&mut fout)?;
+        fout.flush()?;
+        Ok(())
+    });
 
-    let mut fin = BufReader::new(config.get_in_file().to_file(false)?);
+    match res {
+        Ok(()) => std::fs::rename(&tmp_name, path).map_err(From::from),
+        Err(err) => {
+            let _ = std::fs::remove_file(&tmp_name);
+            Err(err)
+        }
+    }
+}
 
+/// Opens the input file, along with the compression its data was found to use.
+fn open_nbt(config: &cli::Config) -> err::Result<(BufReader<File>, DataFormat)> {
+    let fin = BufReader::new(config.get_in_file().to_file(false)?);
+    detect_format(config, fin, Path::new(config.get_in_file().to_str()))
+}
+
+/// Finds the compression of the data in `fin`, unless `config` names one.
+fn detect_format(
+    config: &cli::Config,
+    mut fin: BufReader<File>,
+    file_name: &Path,
+) -> err::Result<(BufReader<File>, DataFormat)> {
     let dformat = if config.get_data_format().is_default() {
         let buf_ref = fin.fill_buf()?;
         if buf_ref.len() < DataFormat::BYTE_COUNT {
             return Err(err::RuntimeErr::BadFileFormat {
-                file_name: config.get_in_file().to_str(),
+                file_name: file_name.to_path_buf(),
             });
         }
 
         let magic_bytes = &buf_ref[..DataFormat::BYTE_COUNT].try_into().unwrap();
-        DataFormat::from_magic_bytes(&magic_bytes)
+        DataFormat::from_magic_bytes(magic_bytes)
     } else {
         *config.get_data_format()
     };
 
-    let root_tag = match dformat {
-        DataFormat::Gzip => read_gzip_nbt(&mut fin)?,
-        DataFormat::NBT => read_nbt(&mut fin)?,
-        DataFormat::Zlib => read_zlib_nbt(&mut fin)?,
-        _ => {
-            return Err(err::RuntimeErr::BadFileFormat {
-                file_name: config.get_in_file().to_str(),
-            });
-        }
-    };
+    if !matches!(dformat, DataFormat::Gzip | DataFormat::NBT | DataFormat::Zlib) {
+        return Err(err::RuntimeErr::BadFileFormat {
+            file_name: file_name.to_path_buf(),
+        });
+    }
 
-    let mut fout = BufWriter::new(config.get_out_file().to_file(true)?);
+    Ok((fin, dformat))
+}
+
+fn read_compound(fin: &mut BufReader<File>, dformat: DataFormat) -> err::Result<CompoundTag> {
+    let nbt_data = match dformat {
+        DataFormat::Gzip => nbt::decode::read_gzip_nbt(fin)?,
+        DataFormat::Zlib => nbt::decode::read_zlib_nbt(fin)?,
+        _ => nbt::decode::read_nbt(fin)?,
+    };
 
-    crate::xml::write::print_xml(&mut fout, &root_tag)
+    Ok(nbt_data)
 }
 
 /// TODO: Fill me
@@ -59,23 +202,252 @@ pub(crate) fn decompile(config: &cli::Config) -> err::Result<()> {
 ///
 /// This function will return an error if .
 pub(crate) fn compile(config: &cli::Config) -> err::Result<()> {
-    use nbt::encode::*;
+    if config.get_recursive() {
+        return convert_dir(config, false);
+    }
 
-    let mut fin = BufReader::new(config.get_in_file().to_file(false)?);
-    let nbt_data = crate::xml::read::read_xml(&mut fin)?;
+    let nbt_data = read_text(config, config.get_in_file().to_file(false)?)?;
     let mut fout = BufWriter::new(config.get_out_file().to_file(true)?);
 
-    let dformat = if config.get_data_format().is_default() {
+    write_compound(&nbt_data, &mut fout, compile_format(config))
+}
+
+/// Reads the text format chosen in `config` from `fin`.
+fn read_text(config: &cli::Config, fin: File) -> err::Result<CompoundTag> {
+    use crate::xml::{read::read_xml, validate::validate_xml};
+
+    let nbt_data = match config.get_text_format() {
+        cli::TextFormat::Json(_) => nbt::json::from_reader(BufReader::new(fin))?,
+        cli::TextFormat::Yaml => crate::yaml::read_yaml(BufReader::new(fin))?,
+        cli::TextFormat::Toml => crate::toml::read_toml(BufReader::new(fin))?,
+        cli::TextFormat::Xml => {
+            // Read up front, validation needs a first pass over the document
+            let mut xml = Vec::new();
+            BufReader::new(fin).read_to_end(&mut xml)?;
+            validate_xml(xml.as_slice())?;
+
+            read_xml(&mut BufReader::new(xml.as_slice()))?
+        }
+    };
+
+    Ok(nbt_data)
+}
+
+/// Compression compiled data is written with, none unless `config` names one.
+fn compile_format(config: &cli::Config) -> DataFormat {
+    if config.get_data_format().is_default() {
         DataFormat::NBT
     } else {
         *config.get_data_format()
-    };
+    }
+}
+
+fn write_compound<W: Write + Debug>(nbt_data: &CompoundTag, fout: W, dformat: DataFormat) -> err::Result<()> {
+    use nbt::encode::*;
 
     match dformat {
-        DataFormat::NBT => write_nbt(&nbt_data, &mut fout),
-        DataFormat::Gzip => write_gzip_nbt(&nbt_data, &mut fout),
-        DataFormat::Zlib => write_zlib_nbt(&nbt_data, &mut fout),
+        DataFormat::NBT => write_nbt(nbt_data, fout),
+        DataFormat::Gzip => write_gzip_nbt(nbt_data, fout),
+        DataFormat::Zlib => write_zlib_nbt(nbt_data, fout),
         _ => return Err(err::RuntimeErr::BadDataCompression(dformat as u8)),
     }
     .map_err(From::from)
 }
+
+/// Compiles text files to NBT whenever they change, or decompiles NBT files to text with
+/// `--decompile`. FILE is watched, or every such file in DIRECTORY and the directories in
+/// it, until interrupted. Files that fail are reported and watched on.
+pub(crate) fn watch(config: &cli::Config) -> err::Result<()> {
+    let source = Path::new(config.get_in_file().to_str());
+    let out = match config.get_out_file() {
+        cli::FdArgument::File(name) => Some(Path::new(name.as_ref())),
+        _ => None,
+    };
+
+    let decompile = config.get_watch_decompile();
+    let text_extensions = config.get_text_format().extensions();
+    let (extensions, out_ext) = match decompile {
+        true => (NBT_EXTENSIONS, text_extensions[0]),
+        false => (text_extensions, NBT_EXTENSIONS[0]),
+    };
+
+    let mut watcher = crate::util::Watcher::new(source, extensions);
+    // Failing to look at the files is reported once, not on every poll
+    let mut failing = false;
+    println!("Watching {}, press Ctrl-C to stop", source.display());
+    loop {
+        let changed = match watcher.poll(std::time::Instant::now()) {
+            Ok(changed) => {
+                failing = false;
+                changed
+            }
+            Err(err) if !failing => {
+                failing = true;
+                eprintln!("nbtc: {}: {}", source.display(), err::RuntimeErr::from(err));
+                Vec::new()
+            }
+            Err(_) => Vec::new(),
+        };
+
+        for file in changed {
+            let target = target_path(source, &file, out, out_ext);
+            let res = match decompile {
+                true => decompile_file(config, &file, &target),
+                false => compile_file(config, &file, &target),
+            };
+
+            match res {
+                Ok(()) => println!("{} -> {}", file.display(), target.display()),
+                Err(err) => eprintln!("nbtc: {}: {}", file.display(), err),
+            }
+        }
+
+        std::thread::sleep(crate::util::POLL_INTERVAL);
+    }
+}
+
+/// Where `file`, found watching or converting `source`, is written to. Files in a directory
+/// keep their place below `out`, a single file is written to `out` itself. Without `out`
+/// files are written next to themselves, with the `ext` extension.
+pub(crate) fn target_path(source: &Path, file: &Path, out: Option<&Path>, ext: &str) -> PathBuf {
+    if file == source {
+        return match out {
+            Some(out) => out.to_path_buf(),
+            None => source.with_extension(ext),
+        };
+    }
+
+    let relative = file.strip_prefix(source).unwrap_or(file);
+    out.unwrap_or(source).join(relative).with_extension(ext)
+}
+
+fn compile_file(config: &cli::Config, source: &Path, target: &Path) -> err::Result<()> {
+    let nbt_data = read_text(config, File::open(source)?)?;
+    create_parent(target)?;
+    write_atomic(target, |fout| write_compound(&nbt_data, fout, compile_format(config)))
+}
+
+fn decompile_file(config: &cli::Config, source: &Path, target: &Path) -> err::Result<()> {
+    let (fin, dformat) = detect_format(config, BufReader::new(File::open(source)?), source)?;
+    create_parent(target)?;
+    write_atomic(target, |fout| write_text(config, fin, dformat, fout))
+}
+
+fn create_parent(path: &Path) -> err::Result<()> {
+    match path.parent() {
+        Some(parent) => std::fs::create_dir_all(parent).map_err(From::from),
+        None => Ok(()),
+    }
+}
+
+/// Compiles or decompiles every file in the input directory and the directories in it, on
+/// all cores. Files that fail are reported once all are done, and fail the command.
+fn convert_dir(config: &cli::Config, decompile: bool) -> err::Result<()> {
+    use rayon::prelude::*;
+
+    let source = Path::new(config.get_in_file().to_str());
+    let out = config.get_out_dir().map(Path::new);
+    let text_extensions = config.get_text_format().extensions();
+    let (extensions, out_ext) = match decompile {
+        true => (NBT_EXTENSIONS, text_extensions[0]),
+        false => (text_extensions, NBT_EXTENSIONS[0]),
+    };
+
+    if source.is_file() {
+        return Err(std::io::Error::from(std::io::ErrorKind::NotADirectory).into());
+    }
+
+    let (files, mut failures) = find_files(config, source, extensions);
+    let total = files.len() + failures.len();
+
+    // Files whose names only differ in the extension, e.g. level.dat and level.dat_old
+    // with a glob, would be written to the same target. None of them are converted.
+    let mut targets: HashMap<PathBuf, Vec<&PathBuf>> = HashMap::new();
+    for file in &files {
+        targets.entry(target_path(source, file, out, out_ext)).or_default().push(file);
+    }
+    let mut jobs = Vec::with_capacity(files.len());
+    for (target, sources) in targets {
+        if let [file] = sources[..] {
+            jobs.push((file, target));
+            continue;
+        }
+        for (i, file) in sources.iter().enumerate() {
+            let other = sources[(i + 1) % sources.len()].clone();
+            let err = err::RuntimeErr::TargetConflict { target: target.clone(), other };
+            failures.push(((*file).clone(), err));
+        }
+    }
+
+    failures.par_extend(jobs.into_par_iter().filter_map(|(file, target)| {
+        let res = match decompile {
+            true => decompile_file(config, file, &target),
+            false => compile_file(config, file, &target),
+        };
+        res.err().map(|err| (file.clone(), err))
+    }));
+
+    failures.sort_by(|a, b| a.0.cmp(&b.0));
+    let failed = failures.len();
+    for (file, err) in failures {
+        eprintln!("nbtc: {}: {}", file.display(), err);
+    }
+
+    let verb = if decompile { "Decompiled" } else { "Compiled" };
+    println!("{} {} of {} files", verb, total - failed, total);
+    match failed {
+        0 => Ok(()),
+        failed => Err(err::RuntimeErr::FilesFailed { failed, total }),
+    }
+}
+
+/// Files in `dir` and the directories in it to convert, sorted, along with those that
+/// couldn't be looked at. They match one of the globs in `config`, or have one of
+/// `extensions` without globs. Globs match the path below `dir`, or the file name.
+pub(crate) fn find_files(
+    config: &cli::Config,
+    dir: &Path,
+    extensions: &[&str],
+) -> (Vec<PathBuf>, Vec<(PathBuf, err::RuntimeErr)>) {
+    let matches = |path: &Path| {
+        let relative = path.strip_prefix(dir).unwrap_or(path);
+        match config.get_globs() {
+            [] => relative
+                .extension()
+                .and_then(|ext| ext.to_str())
+                .is_some_and(|ext| extensions.contains(&ext)),
+            globs => globs.iter().any(|glob| {
+                glob.matches_path(relative) || path.file_name().is_some_and(|name| glob.matches_path(Path::new(name)))
+            }),
+        }
+    };
+
+    let mut files = Vec::new();
+    let mut failures = Vec::new();
+    for entry in walkdir::WalkDir::new(dir).sort_by_file_name() {
+        match entry {
+            Ok(entry) if entry.file_type().is_file() && matches(entry.path()) => files.push(entry.into_path()),
+            Ok(_) => {}
+            Err(err) => {
+                let path = err.path().unwrap_or(dir).to_path_buf();
+                failures.push((path, std::io::Error::from(err).into()));
+            }
+        }
+    }
+
+    (files, failures)
+}
+
+/// Prints the schema of the format chosen in `config`.
+pub(crate) fn schema(config: &cli::Config) -> err::Result<()> {
+    let schema = match config.cmd {
+        cli::Command::Schema(cli::SchemaFormat::Xml) => crate::xml::XSD,
+        _ => unreachable!("only called for the schema command"),
+    };
+
+    let mut fout = BufWriter::new(config.get_out_file().to_file(true)?);
+    fout.write_all(schema.as_bytes())?;
+    fout.flush()?;
+
+    Ok(())
+}
@@ -0,0 +1,768 @@
+//! Changes to NBT data in memory, behind `nbtc edit`.
+//!
+//! A [`Document`] holds the root compound, its tags are found by their [`Path`] of
+//! compound keys and list indices. Values are read and given as text, in the form
+//! [`value_text`] writes and [`parse_tag`] checks against the tag type:
+//!
+//! - numbers as Rust writes them, floats and doubles also as `NaN`, `inf` and `-inf`
+//!   or their bits, e.g. `0x7fc00001`
+//! - strings as they are, without quotes
+//! - arrays as numbers separated by spaces or commas
+//!
+//! Lists and compounds have no text, they are made empty and filled tag by tag.
+
+use std::borrow::Cow;
+use std::fmt::{self, Display, Formatter};
+use std::iter::Peekable;
+use std::str::{Chars, FromStr};
+
+use nbt::consts::*;
+use nbt::float::{nan_bits, parse_float};
+use nbt::{CompoundTag, ListTag, Map, Tag};
+
+use crate::err::{self, RuntimeErr};
+use crate::xml::element_of;
+
+/// One step down into a compound or list.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Step {
+    Key(Box<str>),
+    Index(usize),
+}
+
+impl From<&str> for Step {
+    fn from(key: &str) -> Self {
+        Self::Key(key.into())
+    }
+}
+
+impl From<usize> for Step {
+    fn from(index: usize) -> Self {
+        Self::Index(index)
+    }
+}
+
+/// Path of a tag from the root compound, written like `Level.Sections[3].Palette`.
+/// The root has the empty path.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Path(Vec<Step>);
+
+impl Path {
+    #[inline]
+    pub fn root() -> Self {
+        Self(Vec::new())
+    }
+
+    #[inline]
+    pub fn is_root(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    #[inline]
+    pub fn steps(&self) -> &[Step] {
+        &self.0
+    }
+
+    #[inline]
+    pub fn last(&self) -> Option<&Step> {
+        self.0.last()
+    }
+
+    /// Path of the compound or list holding the tag, `None` for the root.
+    pub fn parent(&self) -> Option<Path> {
+        let (_, parent) = self.0.split_last()?;
+        Some(Self(parent.to_vec()))
+    }
+
+    /// Path of a tag inside this one.
+    pub fn join(&self, step: impl Into<Step>) -> Path {
+        let mut steps = self.0.clone();
+        steps.push(step.into());
+        Self(steps)
+    }
+
+    /// Whether the tag is `ancestor` or inside it.
+    pub fn starts_with(&self, ancestor: &Path) -> bool {
+        self.0.starts_with(&ancestor.0)
+    }
+}
+
+impl FromIterator<Step> for Path {
+    fn from_iter<T: IntoIterator<Item = Step>>(iter: T) -> Self {
+        Self(iter.into_iter().collect())
+    }
+}
+
+impl Display for Path {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for (i, step) in self.0.iter().enumerate() {
+            match step {
+                Step::Key(key) if i == 0 => write!(f, "{}", quote_key(key))?,
+                Step::Key(key) => write!(f, ".{}", quote_key(key))?,
+                Step::Index(index) => write!(f, "[{}]", index)?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl FromStr for Path {
+    type Err = RuntimeErr;
+
+    /// Reads a path as it is written, e.g. `Level.Sections[3]."block states"`. The empty
+    /// path is the root.
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let invalid = || RuntimeErr::InvalidPath(text.to_string());
+        let mut chars = text.chars().peekable();
+        let mut steps = Vec::new();
+
+        while let Some(c) = chars.peek().copied() {
+            match c {
+                '[' => {
+                    chars.next();
+                    let index = chars.by_ref().take_while(|c| *c != ']').collect::<String>();
+                    steps.push(Step::Index(index.parse().map_err(|_| invalid())?));
+                }
+                '.' if !steps.is_empty() => {
+                    chars.next();
+                    steps.push(Step::Key(key_from(&mut chars).ok_or_else(invalid)?));
+                }
+                _ if steps.is_empty() => steps.push(Step::Key(key_from(&mut chars).ok_or_else(invalid)?)),
+                _ => return Err(invalid()),
+            }
+        }
+
+        Ok(Self(steps))
+    }
+}
+
+/// `key` as it is written in paths, in quotes if it holds anything paths are made of.
+pub fn quote_key(key: &str) -> Cow<'_, str> {
+    let plain = !key.is_empty() && !key.contains(|c: char| c.is_whitespace() || ".[]\"\\/".contains(c));
+    match plain {
+        true => Cow::Borrowed(key),
+        false => Cow::Owned(format!("\"{}\"", key.replace('\\', "\\\\").replace('"', "\\\""))),
+    }
+}
+
+/// Reads a key, up to the next step or the end of its quotes.
+fn key_from(chars: &mut Peekable<Chars<'_>>) -> Option<Box<str>> {
+    let mut key = String::new();
+    if chars.next_if_eq(&'"').is_none() {
+        while let Some(c) = chars.next_if(|c| !matches!(c, '.' | '[')) {
+            key.push(c);
+        }
+        return (!key.is_empty()).then(|| key.into());
+    }
+
+    loop {
+        match chars.next()? {
+            '"' => return Some(key.into()),
+            '\\' => key.push(chars.next()?),
+            c => key.push(c),
+        }
+    }
+}
+
+/// Root compound being edited.
+#[derive(Debug, Clone)]
+pub struct Document {
+    name: Option<Box<str>>,
+    /// Always a [`Tag::Compound`], so that the root is found like any other tag
+    root: Tag,
+    /// Edits taking back each edit made, the last edit at the end
+    undo: Vec<Inverse>,
+    /// Length of `undo` when the root was as in the file, `None` once that can't be
+    /// undone back to
+    saved: Option<usize>,
+}
+
+impl Document {
+    /// Edits kept for [`Document::undo`], the oldest are dropped past it.
+    pub const UNDO_LIMIT: usize = 100;
+
+    pub fn new(mut root: CompoundTag) -> Self {
+        Self {
+            name: root.name.take(),
+            root: Tag::Compound(root.into_map()),
+            undo: Vec::new(),
+            saved: Some(0),
+        }
+    }
+
+    /// The edited data, to be written out.
+    pub fn to_compound(&self) -> CompoundTag {
+        let mut compound = CompoundTag::with(self.root_map().clone());
+        compound.name = self.name.clone();
+        compound
+    }
+
+    /// Name of the root compound, usually empty.
+    #[inline]
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    #[inline]
+    pub fn root(&self) -> &Tag {
+        &self.root
+    }
+
+    /// Whether the data differs from the file it was opened from or last saved to.
+    #[inline]
+    pub fn is_modified(&self) -> bool {
+        self.saved != Some(self.undo.len())
+    }
+
+    #[inline]
+    pub fn mark_saved(&mut self) {
+        self.saved = Some(self.undo.len());
+    }
+
+    /// Takes back the last edit, `false` if there is none left.
+    pub fn undo(&mut self) -> bool {
+        match self.undo.pop() {
+            Some(inverse) => {
+                inverse.apply(&mut self.root).expect("inverse of an edit that was made");
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn get(&self, path: &Path) -> Option<&Tag> {
+        path.steps().iter().try_fold(&self.root, |tag, step| match (tag, step) {
+            (Tag::Compound(map), Step::Key(key)) => map.get(key),
+            (Tag::List(list), Step::Index(index)) => list.get(*index),
+            _ => None,
+        })
+    }
+
+    /// Position of the tag in its compound or list.
+    pub fn position(&self, path: &Path) -> Option<usize> {
+        match (self.get(&path.parent()?)?, path.last()?) {
+            (Tag::Compound(map), Step::Key(key)) => map.get_index_of(key),
+            (Tag::List(list), Step::Index(index)) if *index < list.len() => Some(*index),
+            _ => None,
+        }
+    }
+
+    /// Changes the value of a scalar or array tag, keeping its type.
+    pub fn set_value(&mut self, path: &Path, text: &str) -> err::Result<()> {
+        let tag = self.get(path).ok_or_else(|| RuntimeErr::NoSuchTag(path.to_string()))?;
+        if matches!(tag, Tag::List(_) | Tag::Compound(_)) {
+            let type_name = element_of(tag.tag_type()).unwrap_or_default();
+            return Err(RuntimeErr::InvalidValue(type_name));
+        }
+
+        let tag = parse_tag(tag.tag_type(), text)?;
+        self.replace(path, tag).map(drop)
+    }
+
+    /// Puts `tag` in place of the tag at `path`, returning the old one. Tags in lists
+    /// have to keep their type.
+    pub fn replace(&mut self, path: &Path, tag: Tag) -> err::Result<Tag> {
+        self.edit(|root| replace_in(root, path, tag))
+    }
+
+    /// Adds an entry to the compound at `parent`, at `index` among its entries.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is past the end of the compound.
+    pub fn insert_entry(&mut self, parent: &Path, index: usize, key: &str, tag: Tag) -> err::Result<Path> {
+        self.edit(|root| insert_entry_in(root, parent, index, key, tag))
+    }
+
+    /// Adds an element to the list at `parent`, at `index`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is past the end of the list.
+    pub fn insert_element(&mut self, parent: &Path, index: usize, tag: Tag) -> err::Result<Path> {
+        self.edit(|root| insert_element_in(root, parent, index, tag))
+    }
+
+    /// Renames a compound entry, keeping its position.
+    pub fn rename(&mut self, path: &Path, key: &str) -> err::Result<Path> {
+        if let Some(Step::Key(old_key)) = path.last() {
+            if old_key.as_ref() == key {
+                return Ok(path.clone());
+            }
+        }
+
+        self.edit(|root| rename_in(root, path, key))
+    }
+
+    /// Removes a tag from its compound or list.
+    pub fn remove(&mut self, path: &Path) -> err::Result<Tag> {
+        self.edit(|root| remove_in(root, path))
+    }
+
+    /// Moves a tag to `index` in its compound or list, shifting the tags in between.
+    pub fn move_to(&mut self, path: &Path, index: usize) -> err::Result<Path> {
+        self.edit(|root| move_in(root, path, index))
+    }
+
+    /// Paths of the tags whose key or value holds `needle`, ignoring case, in the
+    /// order they are written.
+    pub fn search(&self, needle: &str) -> Vec<Path> {
+        let needle = needle.to_lowercase();
+        let mut found = Vec::new();
+        search_in(&self.root, &mut Path::root(), &needle, &mut found);
+        found
+    }
+
+    /// Runs an edit of the root, its inverse is kept for [`Document::undo`] if the edit
+    /// succeeds. Edits check everything before they change anything.
+    fn edit<T>(&mut self, edit: impl FnOnce(&mut Tag) -> err::Result<(T, Inverse)>) -> err::Result<T> {
+        let (res, inverse) = edit(&mut self.root)?;
+
+        // The saved state was undone past and can't be reached anymore
+        if self.saved.is_some_and(|saved| saved > self.undo.len()) {
+            self.saved = None;
+        }
+        if self.undo.len() == Self::UNDO_LIMIT {
+            self.undo.remove(0);
+            self.saved = self.saved.and_then(|saved| saved.checked_sub(1));
+        }
+        self.undo.push(inverse);
+
+        Ok(res)
+    }
+
+    fn root_map(&self) -> &Map {
+        match &self.root {
+            Tag::Compound(map) => map,
+            _ => unreachable!("the root is a compound"),
+        }
+    }
+}
+
+/// Edit taking back another one, see [`Document::undo`]. Only the tags an edit replaced
+/// or removed are kept.
+#[derive(Debug, Clone)]
+enum Inverse {
+    /// Puts back the tag the one at the path replaced
+    Replace(Path, Tag),
+    /// Removes the tag added at the path
+    Remove(Path),
+    /// Puts back a removed compound entry at its position
+    InsertEntry {
+        parent: Path,
+        index: usize,
+        key: Box<str>,
+        tag: Tag,
+    },
+    /// Puts back a removed list element
+    InsertElement { parent: Path, index: usize, tag: Tag },
+    /// Gives the entry at the path its old key back
+    Rename(Path, Box<str>),
+    /// Moves the tag at the path back to its old position
+    Move(Path, usize),
+}
+
+impl Inverse {
+    fn apply(self, root: &mut Tag) -> err::Result<()> {
+        match self {
+            Self::Replace(path, tag) => replace_in(root, &path, tag).map(drop),
+            Self::Remove(path) => remove_in(root, &path).map(drop),
+            Self::InsertEntry {
+                parent,
+                index,
+                key,
+                tag,
+            } => insert_entry_in(root, &parent, index, &key, tag).map(drop),
+            Self::InsertElement { parent, index, tag } => {
+                insert_element_in(root, &parent, index, tag).map(drop)
+            }
+            Self::Rename(path, key) => rename_in(root, &path, &key).map(drop),
+            Self::Move(path, index) => move_in(root, &path, index).map(drop),
+        }
+    }
+}
+
+// The edits of a `Document`, each returns its result along with its inverse
+
+fn replace_in(root: &mut Tag, path: &Path, tag: Tag) -> err::Result<(Tag, Inverse)> {
+    let old = match (path.parent(), path.last()) {
+        (Some(parent), Some(Step::Index(index))) => {
+            with_tag(root, &parent, |list| {
+                let list = list_mut(list, &parent)?;
+                if *index >= list.len() {
+                    return Err(RuntimeErr::NoSuchTag(path.to_string()));
+                }
+                let found = element_of(tag.tag_type()).unwrap_or_default();
+                list.set(*index, tag).map_err(|_| RuntimeErr::MixedList {
+                    expected: element_of(list.element_type()).unwrap_or_default(),
+                    found,
+                })
+            })?
+        }
+        // Entries of a compound may change their type
+        (Some(_), _) => with_tag(root, path, |old| Ok(std::mem::replace(old, tag)))?,
+        (None, _) => return Err(RuntimeErr::RootTag("replaced")),
+    };
+
+    Ok((old.clone(), Inverse::Replace(path.clone(), old)))
+}
+
+fn insert_entry_in(
+    root: &mut Tag,
+    parent: &Path,
+    index: usize,
+    key: &str,
+    tag: Tag,
+) -> err::Result<(Path, Inverse)> {
+    with_tag(root, parent, |map| {
+        let map = compound_mut(map, parent)?;
+        if map.contains_key(key) {
+            return Err(RuntimeErr::DuplicateKey(key.to_string()));
+        }
+
+        map.shift_insert(index, key.into(), tag);
+        Ok(())
+    })?;
+    let path = parent.join(key);
+    Ok((path.clone(), Inverse::Remove(path)))
+}
+
+fn insert_element_in(root: &mut Tag, parent: &Path, index: usize, tag: Tag) -> err::Result<(Path, Inverse)> {
+    let found = element_of(tag.tag_type()).unwrap_or_default();
+    with_tag(root, parent, |list| {
+        let list = list_mut(list, parent)?;
+        list.insert(index, tag).map_err(|_| RuntimeErr::MixedList {
+            expected: element_of(list.element_type()).unwrap_or_default(),
+            found,
+        })
+    })?;
+
+    let path = parent.join(index);
+    Ok((path.clone(), Inverse::Remove(path)))
+}
+
+fn rename_in(root: &mut Tag, path: &Path, key: &str) -> err::Result<(Path, Inverse)> {
+    let (parent, old_key) = match (path.parent(), path.last()) {
+        (Some(parent), Some(Step::Key(old_key))) => (parent, old_key),
+        (Some(parent), _) => {
+            return Err(RuntimeErr::WrongTagType {
+                path: parent.to_string(),
+                expected: element_of(TAG_COMPOUND).unwrap_or_default(),
+            })
+        }
+        (None, _) => return Err(RuntimeErr::RootTag("renamed")),
+    };
+
+    with_tag(root, &parent, |map| {
+        let map = compound_mut(map, &parent)?;
+        if map.contains_key(key) {
+            return Err(RuntimeErr::DuplicateKey(key.to_string()));
+        }
+        let (index, _, tag) = map
+            .shift_remove_full(old_key.as_ref())
+            .ok_or_else(|| RuntimeErr::NoSuchTag(path.to_string()))?;
+        map.shift_insert(index, key.into(), tag);
+        Ok(())
+    })?;
+
+    let path = parent.join(key);
+    Ok((path.clone(), Inverse::Rename(path, old_key.clone())))
+}
+
+fn remove_in(root: &mut Tag, path: &Path) -> err::Result<(Tag, Inverse)> {
+    let parent = path.parent().ok_or(RuntimeErr::RootTag("removed"))?;
+    let missing = || RuntimeErr::NoSuchTag(path.to_string());
+
+    with_tag(root, &parent, |tag| match (tag, path.last()) {
+        (Tag::Compound(map), Some(Step::Key(key))) => {
+            let (index, key, tag) = map.shift_remove_full(key).ok_or_else(missing)?;
+            let inverse = Inverse::InsertEntry {
+                parent: parent.clone(),
+                index,
+                key,
+                tag: tag.clone(),
+            };
+            Ok((tag, inverse))
+        }
+        (Tag::List(list), Some(Step::Index(index))) if *index < list.len() => {
+            let tag = list.remove(*index);
+            let inverse = Inverse::InsertElement {
+                parent: parent.clone(),
+                index: *index,
+                tag: tag.clone(),
+            };
+            Ok((tag, inverse))
+        }
+        _ => Err(missing()),
+    })
+}
+
+fn move_in(root: &mut Tag, path: &Path, index: usize) -> err::Result<(Path, Inverse)> {
+    let parent = path.parent().ok_or(RuntimeErr::RootTag("moved"))?;
+    let missing = || RuntimeErr::NoSuchTag(path.to_string());
+    let out_of_range = || RuntimeErr::NoSuchTag(parent.join(index).to_string());
+
+    with_tag(root, &parent, |tag| match (tag, path.last()) {
+        (Tag::Compound(map), Some(Step::Key(key))) => {
+            let from = map.get_index_of(key).ok_or_else(missing)?;
+            if index >= map.len() {
+                return Err(out_of_range());
+            }
+            map.move_index(from, index);
+            Ok((path.clone(), Inverse::Move(path.clone(), from)))
+        }
+        (Tag::List(list), Some(Step::Index(from))) if *from < list.len() => {
+            if index >= list.len() {
+                return Err(out_of_range());
+            }
+            let tag = list.remove(*from);
+            list.insert(index, tag).expect("element of the same list");
+            let path = parent.join(index);
+            Ok((path.clone(), Inverse::Move(path, *from)))
+        }
+        _ => Err(missing()),
+    })
+}
+
+/// Runs `edit` on the tag at `path` below `root`. List elements are reached through
+/// [`ListTag::update`], so an edit must not change the type of one, only what it holds.
+fn with_tag<R>(root: &mut Tag, path: &Path, edit: impl FnOnce(&mut Tag) -> err::Result<R>) -> err::Result<R> {
+    with_tag_at(root, path.steps(), path, edit)
+}
+
+fn with_tag_at<R>(
+    tag: &mut Tag,
+    steps: &[Step],
+    path: &Path,
+    edit: impl FnOnce(&mut Tag) -> err::Result<R>,
+) -> err::Result<R> {
+    let missing = || RuntimeErr::NoSuchTag(path.to_string());
+    let Some((step, rest)) = steps.split_first() else {
+        return edit(tag);
+    };
+
+    match (tag, step) {
+        (Tag::Compound(map), Step::Key(key)) => {
+            with_tag_at(map.get_mut(key).ok_or_else(missing)?, rest, path, edit)
+        }
+        (Tag::List(list), Step::Index(index)) => list
+            .update(*index, |element| with_tag_at(element, rest, path, edit))
+            .ok_or_else(missing)?,
+        _ => Err(missing()),
+    }
+}
+
+fn compound_mut<'a>(tag: &'a mut Tag, path: &Path) -> err::Result<&'a mut Map> {
+    match tag {
+        Tag::Compound(map) => Ok(map),
+        _ => Err(RuntimeErr::WrongTagType {
+            path: path.to_string(),
+            expected: element_of(TAG_COMPOUND).unwrap_or_default(),
+        }),
+    }
+}
+
+fn list_mut<'a>(tag: &'a mut Tag, path: &Path) -> err::Result<&'a mut ListTag> {
+    match tag {
+        Tag::List(list) => Ok(list),
+        _ => Err(RuntimeErr::WrongTagType {
+            path: path.to_string(),
+            expected: element_of(TAG_LIST).unwrap_or_default(),
+        }),
+    }
+}
+
+fn search_in(tag: &Tag, path: &mut Path, needle: &str, found: &mut Vec<Path>) {
+    let matches = |text: &str| text.to_lowercase().contains(needle);
+
+    match tag {
+        Tag::Compound(map) => {
+            for (key, tag) in map {
+                path.0.push(Step::Key(key.clone()));
+                if matches(key) || value_text(tag).is_some_and(|text| matches(&text)) {
+                    found.push(path.clone());
+                }
+                search_in(tag, path, needle, found);
+                path.0.pop();
+            }
+        }
+        Tag::List(list) => {
+            for (index, tag) in list.iter().enumerate() {
+                path.0.push(Step::Index(index));
+                if value_text(tag).is_some_and(|text| matches(&text)) {
+                    found.push(path.clone());
+                }
+                search_in(tag, path, needle, found);
+                path.0.pop();
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Value of a scalar or array tag as text, `None` for lists and compounds.
+pub fn value_text(tag: &Tag) -> Option<String> {
+    let text = match tag {
+        Tag::Byte(v) => v.to_string(),
+        Tag::Short(v) => v.to_string(),
+        Tag::Int(v) => v.to_string(),
+        Tag::Long(v) => v.to_string(),
+        Tag::Float(v) => nan_bits(*v).unwrap_or_else(|| format!("{:?}", v)),
+        Tag::Double(v) => nan_bits(*v).unwrap_or_else(|| format!("{:?}", v)),
+        Tag::String(text) => text.to_string(),
+        Tag::ByteArray(array) => join(array),
+        Tag::IntArray(array) => join(array),
+        Tag::LongArray(array) => join(array),
+        Tag::List(_) | Tag::Compound(_) => return None,
+    };
+
+    Some(text)
+}
+
+/// Short text of a tag for listing it: the value of scalars, strings in quotes, the
+/// size of lists, compounds and arrays along with the values of arrays.
+pub fn summary(tag: &Tag) -> String {
+    let count = |len: usize, one: &str, many: &str| format!("{} {}", len, if len == 1 { one } else { many });
+    let array = |len: usize, one: &str, many: &str| match len {
+        0 => count(len, one, many),
+        _ => format!("{}: {}", count(len, one, many), value_text(tag).unwrap_or_default()),
+    };
+
+    match tag {
+        Tag::String(text) => format!("{:?}", text),
+        Tag::Compound(map) => count(map.len(), "entry", "entries"),
+        Tag::List(list) => count(list.len(), "entry", "entries"),
+        Tag::ByteArray(values) => array(values.len(), "byte", "bytes"),
+        Tag::IntArray(values) => array(values.len(), "int", "ints"),
+        Tag::LongArray(values) => array(values.len(), "long", "longs"),
+        _ => value_text(tag).unwrap_or_default(),
+    }
+}
+
+fn join<T: Display>(array: &[T]) -> String {
+    array.iter().map(T::to_string).collect::<Vec<_>>().join(" ")
+}
+
+/// Makes a tag of the type from its text, as written by [`value_text`]. Lists and
+/// compounds are made empty and take no text.
+pub fn parse_tag(tag_type: u8, text: &str) -> err::Result<Tag> {
+    let type_name = element_of(tag_type).ok_or(RuntimeErr::InvalidValue("tag type"))?;
+    let invalid = || RuntimeErr::InvalidValue(type_name);
+    let number = text.trim();
+
+    let tag = match tag_type {
+        TAG_BYTE => Tag::Byte(number.parse().map_err(|_| invalid())?),
+        TAG_SHORT => Tag::Short(number.parse().map_err(|_| invalid())?),
+        TAG_INT => Tag::Int(number.parse().map_err(|_| invalid())?),
+        TAG_LONG => Tag::Long(number.parse().map_err(|_| invalid())?),
+        TAG_FLOAT => Tag::Float(parse_float(number).map_err(|_| invalid())?),
+        TAG_DOUBLE => Tag::Double(parse_float(number).map_err(|_| invalid())?),
+        TAG_STRING => Tag::String(text.into()),
+        TAG_BYTE_ARRAY => Tag::ByteArray(array_from(number, type_name)?),
+        TAG_INT_ARRAY => Tag::IntArray(array_from(number, type_name)?),
+        TAG_LONG_ARRAY => Tag::LongArray(array_from(number, type_name)?),
+        TAG_LIST | TAG_COMPOUND if !number.is_empty() => return Err(invalid()),
+        TAG_LIST => Tag::List(ListTag::new()),
+        _ => Tag::Compound(Map::new()),
+    };
+
+    Ok(tag)
+}
+
+fn array_from<T: std::str::FromStr>(text: &str, type_name: &'static str) -> err::Result<Vec<T>> {
+    text.split(|c: char| c.is_whitespace() || c == ',')
+        .filter(|value| !value.is_empty())
+        .enumerate()
+        .map(|(index, value)| {
+            value.parse().map_err(|_| RuntimeErr::InvalidValue(type_name).in_index(index))
+        })
+        .collect()
+}
+
+/// Makes a tag from a value written with its type, in the SNBT style of Minecraft
+/// commands:
+///
+/// - numbers with the suffix of their type: `1b`, `2s`, `3`, `4L`, `1.5f` and `1.5d`
+///   or `1.5`
+/// - `true` and `false` as bytes
+/// - strings in quotes, `"Steve"` or `'Steve'`
+/// - arrays as `[B; 1, 2]`, `[I; 1, 2]` and `[L; 1, 2]`
+/// - `{}` and `[]` for an empty compound and list
+///
+/// Values of another type than the `existing` tag they replace are refused. Values
+/// without a suffix take its type, e.g. `20` for a short or `Steve` for a string, else
+/// are ints, doubles or strings.
+pub fn parse_value(text: &str, existing: Option<u8>) -> err::Result<Tag> {
+    let text = text.trim();
+
+    match (typed_value(text)?, existing) {
+        (Some((tag, _)), Some(tag_type)) if tag.tag_type() == tag_type => Ok(tag),
+        (Some((_, true)), Some(tag_type)) => {
+            Err(RuntimeErr::InvalidValue(element_of(tag_type).unwrap_or_default()))
+        }
+        (_, Some(tag_type)) => parse_tag(tag_type, text),
+        (Some((tag, _)), None) => Ok(tag),
+        (None, None) => Ok(Tag::String(text.into())),
+    }
+}
+
+/// The tag a value gives along with whether its type was written out, `None` for bare
+/// words.
+fn typed_value(text: &str) -> err::Result<Option<(Tag, bool)>> {
+    if let Some(quote @ ('"' | '\'')) = text.chars().next() {
+        let inner = text[1..].strip_suffix(quote).ok_or(RuntimeErr::InvalidValue("string"))?;
+        let mut string = String::with_capacity(inner.len());
+        let mut chars = inner.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '\\' => string.push(chars.next().ok_or(RuntimeErr::InvalidValue("string"))?),
+                c => string.push(c),
+            }
+        }
+        return Ok(Some((Tag::String(string.into()), true)));
+    }
+
+    if let Some(inner) = text.strip_prefix('[').and_then(|text| text.strip_suffix(']')) {
+        let tag = match inner.split_once(';') {
+            Some((array_type, values)) => match array_type.trim() {
+                "B" => Tag::ByteArray(array_from(values, "byte_array")?),
+                "I" => Tag::IntArray(array_from(values, "int_array")?),
+                "L" => Tag::LongArray(array_from(values, "long_array")?),
+                _ => return Ok(None),
+            },
+            None if inner.trim().is_empty() => Tag::List(ListTag::new()),
+            None => return Ok(None),
+        };
+        return Ok(Some((tag, true)));
+    }
+
+    let tag = match text {
+        "{}" => Tag::Compound(Map::new()),
+        "true" | "false" => Tag::Byte((text == "true") as i8),
+        _ => {
+            let (number, suffix) = text.split_at(text.len() - text.chars().last().map_or(0, char::len_utf8));
+            let typed = match suffix {
+                "b" | "B" => number.parse().ok().map(Tag::Byte),
+                "s" | "S" => number.parse().ok().map(Tag::Short),
+                "l" | "L" => number.parse().ok().map(Tag::Long),
+                "f" | "F" => number.parse().ok().map(Tag::Float),
+                "d" | "D" => number.parse().ok().map(Tag::Double),
+                _ => None,
+            };
+            if let Some(tag) = typed {
+                return Ok(Some((tag, true)));
+            }
+
+            // Numbers without a suffix
+            match (text.parse().ok(), text.parse().ok()) {
+                (Some(int), _) => Tag::Int(int),
+                (None, Some(double)) if text.contains(|c: char| c.is_ascii_digit()) => Tag::Double(double),
+                _ => return Ok(None),
+            }
+        }
+    };
+
+    let typed = !matches!(tag, Tag::Int(_) | Tag::Double(_));
+    Ok(Some((tag, typed)))
+}
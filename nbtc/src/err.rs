@@ -1,10 +1,12 @@
+use std::fmt::{self, Display, Formatter};
 use std::io;
 use std::num::{ParseFloatError, ParseIntError};
+use std::path::PathBuf;
 use std::str::Utf8Error;
-use nbt::err::{TagDecodeError, TagEncodeError};
+use nbt::err::{join_index, join_key, FloatTextError, JsonError, TagDecodeError, TagEncodeError};
 
 #[derive(Debug)]
-pub(crate) enum MyError<'a> {
+pub enum MyError<'a> {
     Setup(ConfigErr<'a>),
     Runtime(RuntimeErr),
 }
@@ -22,15 +24,227 @@ pub enum RuntimeErr {
     NBTDecode(TagDecodeError),
     NBTEncode(TagEncodeError),
     XmlError(quick_xml::Error),
+    JsonError(JsonError),
+    YamlError(serde_yaml::Error),
+    TomlDecode(toml::de::Error),
+    TomlEncode(toml::ser::Error),
     /// For when unrecognized/unsupported file format is detected
     BadFileFormat {
-        file_name: &'static str,
+        file_name: PathBuf,
     },
     /// For when unrecognized/unsupported compression algorithm is detected
     BadDataCompression(u8),
     AsciiToUtf8(Utf8Error),
     ParseInt(ParseIntError),
     ParseFloat(ParseFloatError),
+    /// Element name that isn't an NBT tag type
+    UnknownElement(String),
+    /// Text inside an element that only holds other elements
+    UnexpectedText,
+    /// XML construct that can't be compiled to NBT, e.g. a comment
+    UnsupportedXml(&'static str),
+    /// Compound entry without a `name` attribute
+    MissingName,
+    /// Document ended before its root element was closed
+    UnexpectedEof,
+    /// Attribute nbtc's XML dialect doesn't define
+    UnknownAttribute(String),
+    /// `name` attribute on a list or array element
+    UnexpectedName,
+    /// List holding elements of different types
+    MixedList {
+        expected: &'static str,
+        found: &'static str,
+    },
+    /// Element that can't appear inside its parent, e.g. `<int>` in a `<byte_array>`
+    UnexpectedElement {
+        parent: &'static str,
+        child: &'static str,
+    },
+    /// `type` attribute of a list that isn't an element name
+    UnknownListType(String),
+    /// Text of a base64 byte array that isn't valid base64
+    InvalidBase64,
+    /// YAML tag that isn't an NBT type
+    UnknownTag(String),
+    /// Value that doesn't fit its NBT type, holds the type name
+    InvalidValue(&'static str),
+    /// Path of an edit that leads to no tag, e.g. a missing key
+    NoSuchTag(String),
+    /// Key a compound already has an entry for
+    DuplicateKey(String),
+    /// Tag an edit can't be made to, e.g. adding an entry to a list
+    WrongTagType {
+        path: String,
+        /// Type name the edit needs, e.g. `compound`
+        expected: &'static str,
+    },
+    /// Edit the root compound can't take, holds what was tried, e.g. `removed`
+    RootTag(&'static str),
+    /// Text that isn't a path to a tag, e.g. `Pos[x]`
+    InvalidPath(String),
+    /// Shell command nbtc doesn't know
+    UnknownCommand(String),
+    /// Shell command given without an argument it needs, holds its name, e.g. `PATH`
+    MissingArgument(&'static str),
+    /// Any of the other errors, with the line of the shell script it was found on
+    ScriptContext {
+        line: usize,
+        source: Box<RuntimeErr>,
+    },
+    /// File of a recursive compile or decompile that would be written to the same
+    /// target as another file, holds that target and the other file
+    TargetConflict {
+        target: PathBuf,
+        other: PathBuf,
+    },
+    /// Files a recursive compile or decompile failed on, each was reported on its own
+    FilesFailed {
+        failed: usize,
+        total: usize,
+    },
+    /// Document failed validation, holds every problem found
+    InvalidXml(Vec<RuntimeErr>),
+    /// Any of the other errors, with where in the XML document it was found
+    XmlContext {
+        line: usize,
+        column: usize,
+        /// Path of the innermost open tag, e.g. `Level.Sections[3].Palette`. Empty for
+        /// the root compound.
+        path: String,
+        source: Box<RuntimeErr>,
+    },
+    /// Any of the other errors, with the tag of a YAML document it was found in
+    TagContext {
+        /// Path of the tag, e.g. `Level.Sections[3].Palette`
+        path: String,
+        source: Box<RuntimeErr>,
+    },
+}
+
+impl RuntimeErr {
+    /// Prefixes the path of a [`RuntimeErr::TagContext`] with a compound key.
+    pub(crate) fn in_key(self, key: &str) -> Self {
+        self.prefix(|path| join_key(key, path))
+    }
+
+    /// Prefixes the path of a [`RuntimeErr::TagContext`] with a list or array index.
+    pub(crate) fn in_index(self, index: usize) -> Self {
+        self.prefix(|path| join_index(index, path))
+    }
+
+    fn prefix(self, prefix: impl FnOnce(&str) -> String) -> Self {
+        match self {
+            Self::TagContext { path, source } => Self::TagContext {
+                path: prefix(&path),
+                source,
+            },
+            _ => Self::TagContext {
+                path: prefix(""),
+                source: Box::new(self),
+            },
+        }
+    }
+}
+
+impl Display for RuntimeErr {
+    /// The error on its first line, followed by indented lines telling where it was
+    /// found, if known.
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::OSError(os_err) => write!(f, "OS Error: {}", os_err),
+            Self::NBTDecode(nbt_err) => {
+                write!(f, "NBT LIB Error: {}", nbt_err.inner())?;
+                if let Some(offset) = nbt_err.offset() {
+                    write!(f, "\n    at byte: {}", offset)?;
+                }
+                match nbt_err.path() {
+                    Some(path) => write_tag_path(f, path),
+                    None => Ok(()),
+                }
+            }
+            Self::NBTEncode(nbt_err) => write!(f, "NBT LIB Error: {}", nbt_err),
+            Self::XmlError(xml_error) => write!(f, "QUICK XML Error: {}", xml_error),
+            Self::YamlError(yaml_err) => write!(f, "YAML Error: {}", yaml_err),
+            Self::TomlDecode(toml_err) => write!(f, "TOML Error: {}", toml_err),
+            Self::TomlEncode(toml_err) => write!(f, "TOML Error: {}", toml_err),
+            Self::UnknownTag(tag) => write!(f, "Unknown NBT type tag: '!{}'", tag),
+            Self::InvalidValue(type_name) => write!(f, "Value doesn't fit its NBT type: {}", type_name),
+            Self::TagContext { path, source } => write!(f, "{}\n    in tag: {}", source, path),
+            Self::JsonError(json_err) => {
+                write!(f, "JSON Error: {}", json_err.inner())?;
+                match json_err.path() {
+                    Some(path) => write_tag_path(f, path),
+                    None => Ok(()),
+                }
+            }
+            Self::BadFileFormat { file_name } => {
+                write!(f, "Unrecognised/unsupported file format: {}", file_name.display())
+            }
+            Self::BadDataCompression(algo_id) => write!(
+                f,
+                "Unrecognised/unsupported compression algorithm. Found algorithm id: {}",
+                algo_id
+            ),
+            Self::AsciiToUtf8(utf8_err) => write!(f, "Bad text in xml: {}", utf8_err),
+            Self::ParseInt(int_err) => write!(f, "integer error: {}", int_err),
+            Self::ParseFloat(float_err) => write!(f, "float error: {}", float_err),
+            Self::UnknownElement(name) => write!(f, "Unknown element in xml: <{}>", name),
+            Self::UnexpectedText => write!(f, "Text found in xml where only elements are allowed"),
+            Self::UnsupportedXml(what) => write!(f, "Unsupported in xml: {}", what),
+            Self::MissingName => write!(f, "Compound entry in xml is missing its 'name' attribute"),
+            Self::UnexpectedEof => write!(f, "Xml ended before the root element was closed"),
+            Self::UnknownAttribute(name) => write!(f, "Unknown attribute in xml: '{}'", name),
+            Self::UnexpectedName => {
+                write!(f, "List and array elements in xml can't have a 'name' attribute")
+            }
+            Self::MixedList { expected, found } => {
+                write!(f, "List in xml holds <{}> but found <{}>", expected, found)
+            }
+            Self::UnexpectedElement { parent, child } => write!(f, "<{}> can't hold <{}>", parent, child),
+            Self::UnknownListType(list_type) => write!(f, "Unknown list type in xml: '{}'", list_type),
+            Self::InvalidBase64 => write!(f, "Byte array in xml isn't valid base64"),
+            Self::NoSuchTag(path) => write!(f, "No tag at: {}", path),
+            Self::DuplicateKey(key) => write!(f, "Compound already has an entry named: '{}'", key),
+            Self::WrongTagType { path, expected } => match path.as_str() {
+                "" => write!(f, "Tag isn't a {}: <root>", expected),
+                path => write!(f, "Tag isn't a {}: {}", expected, path),
+            },
+            Self::RootTag(what) => write!(f, "The root compound can't be {}", what),
+            Self::InvalidPath(path) => write!(f, "Not a path to a tag: '{}'", path),
+            Self::UnknownCommand(cmd) => write!(f, "Unknown shell command: '{}', see 'help'", cmd),
+            Self::MissingArgument(arg) => write!(f, "Missing argument: {}", arg),
+            Self::ScriptContext { line, source } => write!(f, "{}\n    at script line: {}", source, line),
+            Self::TargetConflict { target, other } => write!(
+                f,
+                "Would overwrite {}, also converted from {}",
+                target.display(),
+                other.display()
+            ),
+            Self::FilesFailed { failed, total } => write!(f, "{} of {} files failed", failed, total),
+            Self::InvalidXml(problems) => {
+                write!(f, "Xml failed validation with {} problem(s):", problems.len())?;
+                problems.iter().try_for_each(|problem| write!(f, "\n  {}", problem))
+            }
+            Self::XmlContext {
+                line,
+                column,
+                path,
+                source,
+            } => {
+                write!(f, "{}\n    at line: {}, column: {}", source, line, column)?;
+                write_tag_path(f, path)
+            }
+        }
+    }
+}
+
+/// Line giving the path of the tag an error was found in.
+fn write_tag_path(f: &mut Formatter<'_>, path: &str) -> fmt::Result {
+    match path {
+        "" => write!(f, "\n    in tag: <root>"),
+        path => write!(f, "\n    in tag: {}", path),
+    }
 }
 
 impl From<io::Error> for RuntimeErr {
@@ -51,12 +265,45 @@ impl From<TagEncodeError> for RuntimeErr {
     }
 }
 
+impl From<JsonError> for RuntimeErr {
+    fn from(value: JsonError) -> Self {
+        Self::JsonError(value)
+    }
+}
+
+impl From<serde_yaml::Error> for RuntimeErr {
+    fn from(value: serde_yaml::Error) -> Self {
+        Self::YamlError(value)
+    }
+}
+
+impl From<toml::de::Error> for RuntimeErr {
+    fn from(value: toml::de::Error) -> Self {
+        Self::TomlDecode(value)
+    }
+}
+
+impl From<toml::ser::Error> for RuntimeErr {
+    fn from(value: toml::ser::Error) -> Self {
+        Self::TomlEncode(value)
+    }
+}
+
 impl From<quick_xml::Error> for RuntimeErr {
     fn from(value: quick_xml::Error) -> Self {
         Self::XmlError(value)
     }
 }
 
+impl From<FloatTextError> for RuntimeErr {
+    fn from(value: FloatTextError) -> Self {
+        match value {
+            FloatTextError::Decimal(float_err) => Self::ParseFloat(float_err),
+            FloatTextError::Bits(int_err) => Self::ParseInt(int_err),
+        }
+    }
+}
+
 impl From<Utf8Error> for RuntimeErr {
     fn from(value: Utf8Error) -> Self {
         Self::AsciiToUtf8(value)
@@ -75,4 +322,4 @@ impl From<ParseFloatError> for RuntimeErr {
     }
 }
 
-pub(crate) type Result<T> = std::result::Result<T, RuntimeErr>;
+pub type Result<T> = std::result::Result<T, RuntimeErr>;
@@ -0,0 +1,10 @@
+//! Format conversions behind the `nbtc` command line tool.
+//!
+//! The binary handles arguments and files, the conversions live here so they can be
+//! benchmarked on their own.
+
+pub mod edit;
+pub mod err;
+pub mod toml;
+pub mod xml;
+pub mod yaml;
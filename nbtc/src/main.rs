@@ -1,10 +1,12 @@
 use std::env;
 
+use nbtc::{err, toml, xml, yaml};
+
 mod cli;
 mod cmd;
-mod err;
+mod shell;
+mod tui;
 mod util;
-mod xml;
 
 #[cfg(test)]
 mod test;
@@ -25,7 +27,11 @@ fn main() {
         cli::Command::Help => cmd::help(),
         cli::Command::Decompile => cmd::decompile(&config),
         cli::Command::Compile => cmd::compile(&config),
-        cli::Command::Watch => todo!("Run util::watch"),
+        cli::Command::Watch => cmd::watch(&config),
+        cli::Command::Tree => cmd::tree(&config),
+        cli::Command::Edit => cmd::edit(&config),
+        cli::Command::Shell => cmd::shell(&config),
+        cli::Command::Schema(_) => cmd::schema(&config),
     };
 
     if let Err(err) = res {
@@ -37,7 +43,7 @@ fn main() {
 
 fn process_err(err: err::MyError) -> ! {
     use err::MyError::{Runtime, Setup};
-    use err::{ConfigErr, RuntimeErr};
+    use err::ConfigErr;
     use std::process::exit;
 
     eprint!("nbtc: ");
@@ -51,36 +57,7 @@ fn process_err(err: err::MyError) -> ! {
         Setup(ConfigErr::ArgError(arg)) => {
             eprintln!("Argument: '{}' !!!", arg)
         }
-        Runtime(RuntimeErr::OSError(os_err)) => {
-            eprintln!("OS Error: {}", os_err)
-        }
-        Runtime(RuntimeErr::NBTDecode(nbt_err)) => {
-            eprintln!("NBT LIB Error: {}", nbt_err)
-        }
-        Runtime(RuntimeErr::NBTEncode(nbt_err)) => {
-            eprintln!("NBT LIB Error: {}", nbt_err)
-        }
-        Runtime(RuntimeErr::XmlError(xml_error)) => {
-            eprintln!("QUICK XML Error: {}", xml_error)
-        }
-        Runtime(RuntimeErr::BadFileFormat { .. }) => {
-            eprintln!("Unrecognised/unsupported file format")
-        }
-        Runtime(RuntimeErr::BadDataCompression(algo_id)) => {
-            eprintln!(
-                "Unrecognised/unsupported compression algorithm. Found algorithm id: {}",
-                algo_id
-            )
-        }
-        Runtime(RuntimeErr::AsciiToUtf8(utf8_err)) => {
-            eprintln!("Bad text in xml: {}", utf8_err)
-        }
-        Runtime(RuntimeErr::ParseInt(int_err)) => {
-            eprintln!("integer error: {}", int_err)
-        }
-        Runtime(RuntimeErr::ParseFloat(float_err)) => {
-            eprintln!("float error: {}", float_err)
-        }
+        Runtime(runtime_err) => eprintln!("{}", runtime_err),
     };
 
     exit(1);
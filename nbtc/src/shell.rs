@@ -0,0 +1,348 @@
+//! `nbtc shell`, commands run one line at a time on a [`Document`].
+//!
+//! Lines are read from the terminal with history and tab completion, or from a script,
+//! so that fixes to many files can be recorded once and replayed:
+//!
+//! ```text
+//! cd Data.Player
+//! set Health 20f
+//! rm ActiveEffects
+//! save
+//! ```
+//!
+//! Paths are relative to the current compound or list, `/` starts from the root and
+//! `..` goes up, e.g. `../Inventory[0].id`.
+
+mod line;
+
+use std::io::{BufRead, Write};
+
+use nbt::tree::icon;
+use nbt::Tag;
+use nbtc::edit::{parse_value, quote_key, summary, value_text, Document, Path, Step};
+
+use crate::cmd::Save;
+use crate::err::{self, RuntimeErr};
+use crate::xml::element_of;
+
+const COMMANDS: [&str; 13] = [
+    "cd", "pwd", "ls", "get", "set", "add", "mv", "rm", "undo", "save", "help", "exit", "quit",
+];
+
+const HELP: &str = "\
+cd [PATH]          go into a compound or list, the root without PATH
+pwd                print the current path
+ls [PATH]          list the tags of a compound or list
+get PATH           print the value of a tag
+set PATH VALUE     change a tag, or add it to a compound: 20s, 1.5f, \"text\", [I; 1, 2], {}
+add PATH VALUE     add a tag to the end of a list
+mv PATH NAME       rename a compound entry
+rm PATH            remove a tag
+undo               take back the last change
+save               write the file back, with the compression it was read with
+exit, quit         leave the shell
+";
+
+/// Whether to keep reading lines after a command.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum Flow {
+    Continue,
+    Exit,
+}
+
+pub(crate) struct Shell {
+    doc: Document,
+    cwd: Path,
+    file_name: String,
+    save: Save,
+    /// Whether a person is typing, who is told what commands did
+    interactive: bool,
+    /// Whether leaving with unsaved changes was warned about
+    warned: bool,
+}
+
+impl Shell {
+    pub(crate) fn new(doc: Document, file_name: &str, save: Save) -> Self {
+        Self {
+            doc,
+            cwd: Path::root(),
+            file_name: file_name.to_string(),
+            save,
+            interactive: false,
+            warned: false,
+        }
+    }
+
+    #[cfg(test)]
+    pub(crate) fn document(&self) -> &Document {
+        &self.doc
+    }
+
+    /// Reads commands from the terminal until `exit` or Ctrl-D.
+    pub(crate) fn interactive(&mut self) -> err::Result<()> {
+        self.interactive = true;
+        let mut editor = line::LineEditor::new();
+        let stdout = std::io::stdout();
+        println!("Editing {}, 'help' lists the commands", self.file_name);
+
+        loop {
+            let prompt = format!("/{}> ", self.cwd);
+            let Some(line) = editor.read_line(&prompt, |line| self.complete(line))? else {
+                break;
+            };
+
+            match self.run(&line, &mut stdout.lock()) {
+                Ok(Flow::Continue) => {}
+                Ok(Flow::Exit) => break,
+                Err(err) => eprintln!("nbtc: {}", err),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Runs every line of a script, stopping at the first that fails.
+    pub(crate) fn run_script<R: BufRead>(&mut self, script: R, out: &mut dyn Write) -> err::Result<()> {
+        for (index, line) in script.lines().enumerate() {
+            let context = |err: RuntimeErr| RuntimeErr::ScriptContext {
+                line: index + 1,
+                source: Box::new(err),
+            };
+
+            match self.run(&line.map_err(|err| context(err.into()))?, out) {
+                Ok(Flow::Continue) => {}
+                Ok(Flow::Exit) => break,
+                Err(err) => return Err(context(err)),
+            }
+        }
+
+        if self.doc.is_modified() {
+            eprintln!("nbtc: {} was changed but not saved, end the script with 'save'", self.file_name);
+        }
+        Ok(())
+    }
+
+    /// Runs one command line, blank lines and `#` comments do nothing.
+    pub(crate) fn run(&mut self, line: &str, out: &mut dyn Write) -> err::Result<Flow> {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            return Ok(Flow::Continue);
+        }
+
+        let (cmd, args) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+        let (arg, rest) = split_arg(args.trim());
+        match cmd {
+            "cd" => {
+                let path = self.resolve(arg)?;
+                match self.doc.get(&path) {
+                    Some(Tag::Compound(_) | Tag::List(_)) => self.cwd = path,
+                    Some(_) => {
+                        return Err(RuntimeErr::WrongTagType {
+                            path: path.to_string(),
+                            expected: "compound or list",
+                        })
+                    }
+                    None => return Err(RuntimeErr::NoSuchTag(path.to_string())),
+                }
+            }
+            "pwd" => writeln!(out, "/{}", self.cwd)?,
+            "ls" => self.list(&self.resolve(arg)?, out)?,
+            "get" => {
+                let path = self.resolve(required(arg, "PATH")?)?;
+                match self.doc.get(&path).map(|tag| (tag, value_text(tag))) {
+                    Some((_, Some(text))) => writeln!(out, "{}", text)?,
+                    Some((_, None)) => self.list(&path, out)?,
+                    None => return Err(RuntimeErr::NoSuchTag(path.to_string())),
+                }
+            }
+            "set" => {
+                let path = self.resolve(required(arg, "PATH")?)?;
+                self.set(&path, required(rest, "VALUE")?)?;
+            }
+            "add" => {
+                let path = self.resolve(required(arg, "PATH")?)?;
+                let (len, element_type) = match self.doc.get(&path) {
+                    Some(Tag::List(list)) => (list.len(), list.element_type()),
+                    Some(_) => {
+                        return Err(RuntimeErr::WrongTagType {
+                            path: path.to_string(),
+                            expected: element_of(nbt::consts::TAG_LIST).unwrap_or_default(),
+                        })
+                    }
+                    None => return Err(RuntimeErr::NoSuchTag(path.to_string())),
+                };
+                let existing = element_of(element_type).map(|_| element_type);
+                let tag = parse_value(required(rest, "VALUE")?, existing)?;
+                self.doc.insert_element(&path, len, tag)?;
+            }
+            "mv" => {
+                let path = self.resolve(required(arg, "PATH")?)?;
+                let name = unquote(required(rest, "NAME")?)?;
+                let new_path = self.doc.rename(&path, &name)?;
+                if self.cwd.starts_with(&path) {
+                    let depth = path.steps().len();
+                    self.cwd = new_path.steps().iter().chain(&self.cwd.steps()[depth..]).cloned().collect();
+                }
+            }
+            "rm" => {
+                let path = self.resolve(required(arg, "PATH")?)?;
+                self.doc.remove(&path)?;
+            }
+            "undo" => {
+                if !self.doc.undo() {
+                    self.say(out, "Nothing to undo")?;
+                }
+            }
+            "save" => {
+                (self.save)(&self.doc.to_compound())?;
+                self.doc.mark_saved();
+                self.say(out, &format!("Saved {}", self.file_name))?;
+            }
+            "help" => write!(out, "{}", HELP)?,
+            "exit" | "quit" if self.interactive && self.doc.is_modified() && !self.warned => {
+                self.warned = true;
+                self.say(out, "There are unsaved changes, 'save' them or leave again to drop them")?;
+            }
+            "exit" | "quit" => return Ok(Flow::Exit),
+            _ => return Err(RuntimeErr::UnknownCommand(cmd.to_string())),
+        }
+
+        // Undoing and removing may take away the current tag
+        while self.doc.get(&self.cwd).is_none() {
+            self.cwd = self.cwd.parent().unwrap_or_default();
+        }
+        Ok(Flow::Continue)
+    }
+
+    /// Changes the tag at `path`, or adds it when its compound has no such entry.
+    fn set(&mut self, path: &Path, value: &str) -> err::Result<()> {
+        if let Some(tag) = self.doc.get(path) {
+            let tag = parse_value(value, Some(tag.tag_type()))?;
+            return self.doc.replace(path, tag).map(drop);
+        }
+
+        match (path.parent(), path.last()) {
+            (Some(parent), Some(Step::Key(key))) => {
+                let len = match self.doc.get(&parent) {
+                    Some(Tag::Compound(map)) => map.len(),
+                    _ => return Err(RuntimeErr::NoSuchTag(parent.to_string())),
+                };
+                let tag = parse_value(value, None)?;
+                self.doc.insert_entry(&parent, len, key, tag).map(drop)
+            }
+            _ => Err(RuntimeErr::NoSuchTag(path.to_string())),
+        }
+    }
+
+    /// Writes a line for every tag of a compound or list, or for the tag itself.
+    fn list(&self, path: &Path, out: &mut dyn Write) -> err::Result<()> {
+        let line = |out: &mut dyn Write, name: String, tag: &Tag| {
+            writeln!(out, "{:<3} {}{}", icon(tag.tag_type()), name, summary(tag))
+        };
+
+        match self.doc.get(path) {
+            Some(Tag::Compound(map)) => {
+                for (key, tag) in map {
+                    line(out, format!("{}: ", quote_key(key)), tag)?;
+                }
+            }
+            Some(Tag::List(list)) => {
+                for (index, tag) in list.iter().enumerate() {
+                    line(out, format!("[{}] ", index), tag)?;
+                }
+            }
+            Some(tag) => line(out, String::new(), tag)?,
+            None => return Err(RuntimeErr::NoSuchTag(path.to_string())),
+        }
+
+        Ok(())
+    }
+
+    /// Path of the tag `text` leads to from the current one.
+    fn resolve(&self, text: &str) -> err::Result<Path> {
+        let (mut path, mut rest) = match text.strip_prefix('/') {
+            Some(rest) => (Path::root(), rest),
+            None => (self.cwd.clone(), text),
+        };
+        while rest == ".." || rest.starts_with("../") {
+            path = path.parent().unwrap_or_default();
+            rest = rest[2..].trim_start_matches('/');
+        }
+
+        let relative: Path = rest.parse()?;
+        Ok(path.steps().iter().chain(relative.steps()).cloned().collect())
+    }
+
+    /// Completions of the last word of `line`: commands, then keys of the tags paths
+    /// lead to. Gives where the word starts, along with every word it may become.
+    pub(crate) fn complete(&self, line: &str) -> (usize, Vec<String>) {
+        let start = line.rfind(char::is_whitespace).map_or(0, |i| i + 1);
+        let word = &line[start..];
+        if start == 0 {
+            let commands = COMMANDS.iter().filter(|cmd| cmd.starts_with(word));
+            return (start, commands.map(|cmd| cmd.to_string()).collect());
+        }
+        // Only the first argument is a path
+        if line[..start].split_whitespace().count() != 1 {
+            return (start, Vec::new());
+        }
+
+        // Keys of the tag before the last `.`, or `/` for the root and parents
+        let (base, prefix, partial) = match word.rfind(['.', '/']) {
+            Some(i) if word.as_bytes()[i] == b'/' => (&word[..=i], &word[..=i], &word[i + 1..]),
+            Some(i) => (&word[..i], &word[..=i], &word[i + 1..]),
+            None => ("", "", word),
+        };
+
+        let completions = match self.resolve(base).ok().and_then(|path| self.doc.get(&path)) {
+            Some(Tag::Compound(map)) => map
+                .keys()
+                .filter(|key| key.starts_with(partial))
+                .map(|key| format!("{}{}", prefix, quote_key(key)))
+                .collect(),
+            _ => Vec::new(),
+        };
+        (start, completions)
+    }
+
+    /// Tells a person typing what happened, scripts stay quiet.
+    fn say(&self, out: &mut dyn Write, text: &str) -> err::Result<()> {
+        if self.interactive {
+            writeln!(out, "{}", text)?;
+        }
+        Ok(())
+    }
+}
+
+fn required<'a>(arg: &'a str, name: &'static str) -> err::Result<&'a str> {
+    match arg.is_empty() {
+        true => Err(RuntimeErr::MissingArgument(name)),
+        false => Ok(arg),
+    }
+}
+
+/// Splits off the first argument, which ends at whitespace outside of quotes.
+fn split_arg(args: &str) -> (&str, &str) {
+    let mut quoted = false;
+    let mut escaped = false;
+    for (i, c) in args.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if quoted => escaped = true,
+            '"' => quoted = !quoted,
+            c if c.is_whitespace() && !quoted => return (&args[..i], args[i..].trim_start()),
+            _ => {}
+        }
+    }
+
+    (args, "")
+}
+
+/// Name given as it is or in quotes, like keys in paths.
+fn unquote(name: &str) -> err::Result<String> {
+    let path: Path = name.parse()?;
+    match path.steps() {
+        [Step::Key(key)] => Ok(key.to_string()),
+        _ => Err(RuntimeErr::InvalidValue("compound key")),
+    }
+}
@@ -0,0 +1,159 @@
+//! Reading a line from the terminal, with history and tab completion.
+
+use std::io::{self, stdout, Write};
+
+use ratatui::crossterm::cursor::MoveToColumn;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::crossterm::style::Print;
+use ratatui::crossterm::terminal::{self, Clear, ClearType};
+use ratatui::crossterm::{execute, queue};
+
+use crate::tui::Input;
+
+pub(super) struct LineEditor {
+    history: Vec<String>,
+}
+
+/// Leaves raw mode when dropped, also when reading fails.
+struct RawMode;
+
+impl RawMode {
+    fn enable() -> io::Result<Self> {
+        terminal::enable_raw_mode()?;
+        Ok(Self)
+    }
+}
+
+impl Drop for RawMode {
+    fn drop(&mut self) {
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+impl LineEditor {
+    pub(super) fn new() -> Self {
+        Self { history: Vec::new() }
+    }
+
+    /// Reads a line, `None` once Ctrl-D is pressed on an empty line. `complete` gives
+    /// where the word being completed starts and what it may become.
+    pub(super) fn read_line(
+        &mut self,
+        prompt: &str,
+        complete: impl Fn(&str) -> (usize, Vec<String>),
+    ) -> io::Result<Option<String>> {
+        let _raw = RawMode::enable()?;
+        let mut input = Input::new("");
+        // Lines of the history being walked through, past its end is the new line
+        let mut entry = self.history.len();
+        let mut draft = String::new();
+
+        loop {
+            draw(prompt, &input)?;
+            let Event::Key(key) = event::read()? else { continue };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+
+            let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+            match key.code {
+                KeyCode::Enter => break,
+                KeyCode::Char('d') if ctrl && input.text.is_empty() => {
+                    execute!(stdout(), Print("\r\n"))?;
+                    return Ok(None);
+                }
+                KeyCode::Char('c') if ctrl => {
+                    execute!(stdout(), Print("^C\r\n"))?;
+                    input = Input::new("");
+                    entry = self.history.len();
+                }
+                KeyCode::Char('a') if ctrl => input.handle(KeyEvent::from(KeyCode::Home)),
+                KeyCode::Char('e') if ctrl => input.handle(KeyEvent::from(KeyCode::End)),
+                KeyCode::Char('u') if ctrl => {
+                    input.text.replace_range(..input.cursor, "");
+                    input.cursor = 0;
+                }
+                KeyCode::Up if entry > 0 => {
+                    if entry == self.history.len() {
+                        draft = input.text.clone();
+                    }
+                    entry -= 1;
+                    input = Input::new(&self.history[entry]);
+                }
+                KeyCode::Down if entry < self.history.len() => {
+                    entry += 1;
+                    input = Input::new(self.history.get(entry).unwrap_or(&draft));
+                }
+                KeyCode::Tab => self.complete(prompt, &mut input, &complete)?,
+                _ if !ctrl => input.handle(key),
+                _ => {}
+            }
+        }
+
+        execute!(stdout(), Print("\r\n"))?;
+        let line = input.text;
+        if !line.trim().is_empty() && self.history.last() != Some(&line) {
+            self.history.push(line.clone());
+        }
+        Ok(Some(line))
+    }
+
+    /// Completes the word before the cursor as far as every completion agrees, showing
+    /// them all when that adds nothing.
+    fn complete(
+        &self,
+        prompt: &str,
+        input: &mut Input,
+        complete: &impl Fn(&str) -> (usize, Vec<String>),
+    ) -> io::Result<()> {
+        let (start, completions) = complete(&input.text[..input.cursor]);
+        let Some(first) = completions.first() else {
+            return Ok(());
+        };
+
+        let common = completions.iter().fold(first.as_str(), |common, completion| {
+            let len = common
+                .char_indices()
+                .zip(completion.chars())
+                .take_while(|((_, a), b)| a == b)
+                .last()
+                .map_or(0, |((i, c), _)| i + c.len_utf8());
+            &common[..len]
+        });
+
+        if completions.len() == 1 || common.len() > input.cursor - start {
+            input.text.replace_range(start..input.cursor, common);
+            input.cursor = start + common.len();
+            // Commands are followed by their arguments, keys may be followed by `.`
+            if completions.len() == 1 && start == 0 {
+                input.handle(KeyEvent::from(KeyCode::Char(' ')));
+            }
+        } else {
+            let mut out = stdout();
+            queue!(out, Print("\r\n"))?;
+            for completion in &completions {
+                queue!(out, Print(completion), Print("  "))?;
+            }
+            queue!(out, Print("\r\n"), Print(prompt))?;
+            out.flush()?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Redraws the line being typed, with the cursor where it is in the text.
+fn draw(prompt: &str, input: &Input) -> io::Result<()> {
+    let cursor = prompt.chars().count() + input.text[..input.cursor].chars().count();
+
+    let mut out = stdout();
+    queue!(
+        out,
+        MoveToColumn(0),
+        Clear(ClearType::UntilNewLine),
+        Print(prompt),
+        Print(&input.text),
+        MoveToColumn(cursor as u16)
+    )?;
+    out.flush()
+}
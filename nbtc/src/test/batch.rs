@@ -0,0 +1,83 @@
+use std::path::Path;
+
+use nbt::{CompoundTag, Tag};
+
+use super::temp_dir;
+use crate::cli::Config;
+use crate::cmd::{compile, decompile, find_files};
+use crate::err::RuntimeErr;
+
+fn parse(args: &[&str]) -> Config {
+    Config::parse(args.iter().map(|arg| Box::from(*arg))).unwrap()
+}
+
+fn write_player(path: &Path, health: f32) {
+    let mut root = CompoundTag::new();
+    root.push("Health".into(), Tag::Float(health));
+
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    nbt::encode::write_gzip_nbt(&root, std::fs::File::create(path).unwrap()).unwrap();
+}
+
+#[test]
+fn batch_convert() {
+    let dir = temp_dir("batch_convert");
+    let (world, text) = (dir.join("world"), dir.join("text"));
+    write_player(&world.join("a.dat"), 20.0);
+    write_player(&world.join("old/b.dat"), 5.0);
+    std::fs::write(world.join("broken.dat"), "nbt").unwrap();
+    std::fs::write(world.join("notes.txt"), "not nbt").unwrap();
+
+    let config = parse(&["d", "-r", world.to_str().unwrap(), "-o", text.to_str().unwrap(), "--yaml"]);
+    // The broken file fails the command, the others are still converted
+    assert!(matches!(decompile(&config), Err(RuntimeErr::FilesFailed { failed: 1, total: 3 })));
+    assert_eq!(std::fs::read_to_string(text.join("a.yaml")).unwrap(), "Health: !float 20.0\n");
+    assert!(text.join("old/b.yaml").exists());
+    assert!(!text.join("broken.yaml").exists());
+
+    // And back, next to the text files
+    let config = parse(&["c", "-r", text.to_str().unwrap(), "--yaml", "-z"]);
+    assert!(compile(&config).is_ok());
+    assert_eq!(std::fs::read(text.join("old/b.dat")).unwrap(), std::fs::read(world.join("old/b.dat")).unwrap());
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn batch_target_conflict() {
+    let dir = temp_dir("batch_target_conflict");
+    write_player(&dir.join("level.dat"), 20.0);
+    write_player(&dir.join("level.dat_old"), 5.0);
+    write_player(&dir.join("other.dat"), 1.0);
+
+    // Both level files would be written to level.xml, neither is
+    let config = parse(&["d", "-r", dir.to_str().unwrap(), "--glob", "*.dat*"]);
+    assert!(matches!(decompile(&config), Err(RuntimeErr::FilesFailed { failed: 2, total: 3 })));
+    assert!(!dir.join("level.xml").exists());
+    assert!(dir.join("other.xml").exists());
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn batch_globs() {
+    let dir = temp_dir("batch_globs");
+    for name in ["a.dat", "b.dat", "b.nbt", "sub/b.dat", "sub/c.dat_old"] {
+        std::fs::create_dir_all(dir.join(name).parent().unwrap()).unwrap();
+        std::fs::write(dir.join(name), "").unwrap();
+    }
+    let files = |args: &[&str]| {
+        let config = parse(&[&["d", "-r", dir.to_str().unwrap()], args].concat());
+        let (files, failures) = find_files(&config, &dir, &["dat", "nbt"]);
+        assert!(failures.is_empty());
+        files.into_iter().map(|file| file.strip_prefix(&dir).unwrap().to_path_buf()).collect::<Vec<_>>()
+    };
+
+    assert_eq!(files(&[]), ["a.dat", "b.dat", "b.nbt", "sub/b.dat"].map(Path::new));
+    // Globs match the path below the directory or the file name
+    assert_eq!(files(&["--glob", "b.*"]), ["b.dat", "b.nbt", "sub/b.dat"].map(Path::new));
+    assert_eq!(files(&["--glob", "sub/*"]), ["sub/b.dat", "sub/c.dat_old"].map(Path::new));
+    assert_eq!(files(&["--glob", "a.dat", "--glob", "*.dat_old"]), ["a.dat", "sub/c.dat_old"].map(Path::new));
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
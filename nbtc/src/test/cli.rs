@@ -12,3 +12,118 @@ fn cli_compile() {
     assert_eq!(cli.cmd, Command::Compile);
     assert_eq!(cli.get_in_file().to_str(), "ghosti3.dat");
 }
+
+#[test]
+fn cli_schema() {
+    let args = [Box::from("schema"), Box::from("xml")].into_iter();
+    let cli = Config::parse(args).unwrap();
+    assert_eq!(cli.cmd, Command::Schema(SchemaFormat::Xml));
+
+    assert!(Config::parse([Box::from("schema")].into_iter()).is_err());
+    assert!(Config::parse([Box::from("schema"), Box::from("yaml")].into_iter()).is_err());
+}
+
+#[test]
+fn cli_json() {
+    use nbt::json::JsonMode;
+
+    let parse = |args: &[&str]| Config::parse(args.iter().map(|arg| Box::from(*arg)));
+
+    assert_eq!(parse(&["d", "level.dat"]).unwrap().get_text_format(), TextFormat::Xml);
+    assert_eq!(
+        parse(&["d", "--json", "level.dat"]).unwrap().get_text_format(),
+        TextFormat::Json(JsonMode::Typed)
+    );
+    assert_eq!(
+        parse(&["d", "--json", "--plain", "level.dat"]).unwrap().get_text_format(),
+        TextFormat::Json(JsonMode::Plain)
+    );
+    assert_eq!(
+        parse(&["c", "--json", "level.json"]).unwrap().get_text_format(),
+        TextFormat::Json(JsonMode::Typed)
+    );
+    assert!(parse(&["c", "--plain", "level.json"]).is_err());
+}
+
+#[test]
+fn cli_yaml_toml() {
+    let parse = |args: &[&str]| Config::parse(args.iter().map(|arg| Box::from(*arg)));
+
+    assert_eq!(parse(&["d", "--yaml", "level.dat"]).unwrap().get_text_format(), TextFormat::Yaml);
+    assert_eq!(parse(&["c", "--toml", "level.toml"]).unwrap().get_text_format(), TextFormat::Toml);
+}
+
+#[test]
+fn cli_tree() {
+    let parse = |args: &[&str]| Config::parse(args.iter().map(|arg| Box::from(*arg)));
+
+    let cli = parse(&["print", "--depth", "2", "-w", "20", "--color", "level.dat"]).unwrap();
+    assert_eq!(cli.cmd, Command::Tree);
+    assert_eq!(cli.get_in_file().to_str(), "level.dat");
+    let options = cli.get_tree_options();
+    assert_eq!(options.depth, Some(2));
+    assert_eq!(options.width, 20);
+    assert!(options.colors);
+
+    assert_eq!(parse(&["tree"]).unwrap().get_tree_options(), Default::default());
+    assert!(parse(&["tree", "--depth"]).is_err());
+    assert!(parse(&["tree", "--width", "wide"]).is_err());
+}
+
+#[test]
+fn cli_edit() {
+    let parse = |args: &[&str]| Config::parse(args.iter().map(|arg| Box::from(*arg)));
+
+    let cli = parse(&["edit", "level.dat"]).unwrap();
+    assert_eq!(cli.cmd, Command::Edit);
+    assert_eq!(cli.get_in_file().to_str(), "level.dat");
+
+    assert!(parse(&["edit"]).is_err());
+    assert!(parse(&["e", "-"]).is_err());
+}
+
+#[test]
+fn cli_shell() {
+    let parse = |args: &[&str]| Config::parse(args.iter().map(|arg| Box::from(*arg)));
+
+    let cli = parse(&["shell", "level.dat", "--script", "fix.nbtsh"]).unwrap();
+    assert_eq!(cli.cmd, Command::Shell);
+    assert_eq!(cli.get_in_file().to_str(), "level.dat");
+    assert_eq!(cli.get_script(), Some("fix.nbtsh"));
+
+    assert_eq!(parse(&["sh", "level.dat"]).unwrap().get_script(), None);
+    assert!(parse(&["shell", "level.dat", "-s"]).is_err());
+    assert!(parse(&["shell"]).is_err());
+}
+
+#[test]
+fn cli_watch() {
+    let parse = |args: &[&str]| Config::parse(args.iter().map(|arg| Box::from(*arg)));
+
+    let cli = parse(&["watch", "src.xml", "-f", "out.dat"]).unwrap();
+    assert_eq!(cli.cmd, Command::Watch);
+    assert_eq!(cli.get_out_file().to_str(), "out.dat");
+    assert!(!cli.get_watch_decompile());
+
+    assert!(parse(&["w", "--decompile", "--plain", "world"]).unwrap().get_watch_decompile());
+    assert!(parse(&["watch", "--plain", "world"]).is_err());
+    assert!(parse(&["watch"]).is_err());
+    assert!(parse(&["watch", "-"]).is_err());
+}
+
+#[test]
+fn cli_recursive() {
+    let parse = |args: &[&str]| Config::parse(args.iter().map(|arg| Box::from(*arg)));
+
+    let cli = parse(&["d", "-r", "world/playerdata", "-o", "out", "--glob", "*.dat", "--glob", "a?.nbt"]).unwrap();
+    assert!(cli.get_recursive());
+    assert_eq!(cli.get_out_dir(), Some("out"));
+    assert_eq!(cli.get_globs().len(), 2);
+
+    assert!(parse(&["c", "--recursive", "pack"]).unwrap().get_out_dir().is_none());
+    assert!(parse(&["d", "-r", "world", "-f", "out.xml"]).is_err());
+    assert!(parse(&["d", "-r", "-"]).is_err());
+    assert!(parse(&["print", "-r", "world"]).is_err());
+    assert!(parse(&["d", "world", "-o", "out"]).is_err());
+    assert!(parse(&["d", "-r", "world", "--glob", "[a"]).is_err());
+}
@@ -0,0 +1,274 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use nbt::{CompoundTag, ListTag, Map, Tag};
+use nbtc::edit::{parse_tag, parse_value, value_text, Document, Path, Step};
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+use crate::err::RuntimeErr;
+use crate::tui::Editor;
+use crate::util::DataFormat;
+
+/// The shared player under a root name, which the editor shows and saves.
+fn player() -> CompoundTag {
+    let mut root = super::player();
+    root.name = Some("Player".into());
+    root
+}
+
+fn path(steps: &[Step]) -> Path {
+    steps.iter().cloned().collect()
+}
+
+#[test]
+fn edit_values() {
+    let mut doc = Document::new(player());
+    let hp = path(&["HP".into()]);
+
+    doc.set_value(&hp, "15").unwrap();
+    assert_eq!(doc.get(&hp), Some(&Tag::Short(15)));
+    assert!(doc.is_modified());
+
+    assert!(matches!(doc.set_value(&hp, "40000"), Err(RuntimeErr::InvalidValue("short"))));
+    assert!(matches!(doc.set_value(&hp, "x"), Err(RuntimeErr::InvalidValue("short"))));
+    assert!(matches!(doc.set_value(&path(&["Pos".into()]), "1"), Err(RuntimeErr::InvalidValue("list"))));
+    assert!(matches!(doc.set_value(&path(&["Nope".into()]), "1"), Err(RuntimeErr::NoSuchTag(_))));
+
+    let uuid = path(&["UUID".into()]);
+    doc.set_value(&uuid, "5, 6 7").unwrap();
+    assert_eq!(doc.get(&uuid), Some(&Tag::IntArray(vec![5, 6, 7])));
+    assert_eq!(value_text(doc.get(&uuid).unwrap()).unwrap(), "5 6 7");
+
+    // Floats keep every bit
+    let nan = f32::from_bits(0x7fc00001);
+    assert_eq!(value_text(&Tag::Float(nan)).unwrap(), "0x7fc00001");
+    match parse_tag(nbt::consts::TAG_FLOAT, "0x7fc00001").unwrap() {
+        Tag::Float(v) => assert_eq!(v.to_bits(), nan.to_bits()),
+        tag => panic!("not a float: {:?}", tag),
+    }
+    assert_eq!(parse_tag(nbt::consts::TAG_DOUBLE, "-inf").unwrap(), Tag::Double(f64::NEG_INFINITY));
+    assert_eq!(parse_tag(nbt::consts::TAG_STRING, " a b ").unwrap(), Tag::String(" a b ".into()));
+    assert!(parse_tag(nbt::consts::TAG_COMPOUND, "x").is_err());
+}
+
+#[test]
+fn edit_structure() {
+    let mut doc = Document::new(player());
+    let root = Path::root();
+    let pos = path(&["Pos".into()]);
+
+    // Renaming keeps the position
+    let name = doc.rename(&path(&["Name".into()]), "CustomName").unwrap();
+    assert_eq!(doc.position(&name), Some(1));
+    assert!(matches!(doc.rename(&name, "HP"), Err(RuntimeErr::DuplicateKey(_))));
+    assert!(matches!(doc.rename(&pos.join(0), "x"), Err(RuntimeErr::WrongTagType { .. })));
+    assert!(matches!(doc.rename(&root, "x"), Err(RuntimeErr::RootTag("renamed"))));
+
+    doc.insert_entry(&root, 0, "Air", Tag::Short(300)).unwrap();
+    assert_eq!(doc.position(&path(&["Air".into()])), Some(0));
+    assert!(matches!(doc.insert_entry(&pos, 0, "x", Tag::Int(1)), Err(RuntimeErr::WrongTagType { .. })));
+
+    assert_eq!(doc.insert_element(&pos, 1, Tag::Double(0.5)).unwrap(), pos.join(1));
+    assert!(matches!(
+        doc.insert_element(&pos, 0, Tag::Int(1)),
+        Err(RuntimeErr::MixedList { expected: "double", found: "int" })
+    ));
+
+    assert_eq!(doc.move_to(&pos.join(0), 2).unwrap(), pos.join(2));
+    assert_eq!(doc.move_to(&path(&["Air".into()]), 1).unwrap(), path(&["Air".into()]));
+    assert!(doc.move_to(&pos.join(0), 3).is_err());
+
+    assert_eq!(doc.remove(&pos.join(2)).unwrap(), Tag::Double(1.5));
+    assert!(doc.remove(&root).is_err());
+
+    let saved = doc.to_compound();
+    assert_eq!(saved.name.as_deref(), Some("Player"));
+    let keys = saved.iter().map(|(key, _)| key.as_ref()).collect::<Vec<_>>();
+    assert_eq!(keys, ["HP", "Air", "CustomName", "Pos", "abilities", "UUID"]);
+    assert_eq!(
+        saved.get("Pos"),
+        Some(&Tag::List(ListTag::try_from(vec![Tag::Double(0.5), Tag::Double(-2.0)]).unwrap()))
+    );
+}
+
+#[test]
+fn edit_search() {
+    let doc = Document::new(player());
+
+    assert_eq!(doc.search("steve"), [path(&["Name".into()])]);
+    assert_eq!(doc.search("-2"), [path(&["Pos".into(), 1.into()]), path(&["UUID".into()])]);
+    assert_eq!(doc.search("FLY"), [path(&["abilities".into(), "flying".into()])]);
+    assert!(doc.search("nothing").is_empty());
+}
+
+#[test]
+fn edit_undo() {
+    let mut doc = Document::new(player());
+    let hp = path(&["HP".into()]);
+
+    doc.set_value(&hp, "1").unwrap();
+    doc.remove(&hp).unwrap();
+    assert!(doc.set_value(&hp, "2").is_err());
+
+    assert!(doc.undo());
+    assert_eq!(doc.get(&hp), Some(&Tag::Short(1)));
+    assert!(doc.undo());
+    assert_eq!(doc.get(&hp), Some(&Tag::Short(20)));
+    assert!(!doc.is_modified());
+    assert!(!doc.undo());
+
+    // Saving moves what unmodified means
+    doc.set_value(&hp, "3").unwrap();
+    doc.mark_saved();
+    assert!(!doc.is_modified());
+    assert!(doc.undo());
+    assert!(doc.is_modified());
+    doc.set_value(&hp, "4").unwrap();
+    assert!(doc.is_modified());
+}
+
+#[test]
+fn edit_undo_structure() {
+    let mut doc = Document::new(player());
+    let root = Path::root();
+    let pos = path(&["Pos".into()]);
+
+    let name = doc.rename(&path(&["Name".into()]), "CustomName").unwrap();
+    doc.insert_entry(&root, 0, "Air", Tag::Short(300)).unwrap();
+    doc.insert_element(&pos, 1, Tag::Double(0.5)).unwrap();
+    doc.move_to(&pos.join(0), 2).unwrap();
+    doc.move_to(&path(&["Air".into()]), 3).unwrap();
+    doc.replace(&pos.join(1), Tag::Double(9.0)).unwrap();
+    doc.remove(&pos.join(0)).unwrap();
+    doc.remove(&name).unwrap();
+    doc.remove(&path(&["abilities".into()])).unwrap();
+
+    // Each edit is taken back on its own, in reverse order
+    while doc.undo() {}
+    assert!(!doc.is_modified());
+    let (undone, player) = (doc.to_compound(), player());
+    assert_eq!(undone, player);
+    assert!(undone.iter().map(|(key, _)| key).eq(player.iter().map(|(key, _)| key)));
+}
+
+#[test]
+fn edit_path_text() {
+    let text = r#"Level.Sections[3]."block states".palette"#;
+    let parsed: Path = text.parse().unwrap();
+    assert_eq!(
+        parsed,
+        path(&["Level".into(), "Sections".into(), 3.into(), "block states".into(), "palette".into()])
+    );
+    assert_eq!(parsed.to_string(), text);
+
+    assert_eq!("".parse::<Path>().unwrap(), Path::root());
+    assert_eq!(r#""a\"b""#.parse::<Path>().unwrap(), path(&[r#"a"b"#.into()]));
+    for invalid in ["Pos[x]", "a..b", ".a", "\"open", "a[1]b"] {
+        assert!(matches!(invalid.parse::<Path>(), Err(RuntimeErr::InvalidPath(_))), "{}", invalid);
+    }
+}
+
+#[test]
+fn edit_parse_value() {
+    use nbt::consts::*;
+
+    assert_eq!(parse_value("20f", None).unwrap(), Tag::Float(20.0));
+    assert_eq!(parse_value("1b", None).unwrap(), Tag::Byte(1));
+    assert_eq!(parse_value("true", None).unwrap(), Tag::Byte(1));
+    assert_eq!(parse_value("-3s", None).unwrap(), Tag::Short(-3));
+    assert_eq!(parse_value("4L", None).unwrap(), Tag::Long(4));
+    assert_eq!(parse_value("5", None).unwrap(), Tag::Int(5));
+    assert_eq!(parse_value("1.5", None).unwrap(), Tag::Double(1.5));
+    assert_eq!(parse_value("Steve", None).unwrap(), Tag::String("Steve".into()));
+    assert_eq!(parse_value(r#""20f \" x""#, None).unwrap(), Tag::String(r#"20f " x"#.into()));
+    assert_eq!(parse_value("[I; 1, -2]", None).unwrap(), Tag::IntArray(vec![1, -2]));
+    assert_eq!(parse_value("[]", None).unwrap(), Tag::List(ListTag::new()));
+    assert_eq!(parse_value("{}", None).unwrap(), Tag::Compound(Map::new()));
+
+    // Untyped values take the type they replace, typed ones must match it
+    assert_eq!(parse_value("20", Some(TAG_SHORT)).unwrap(), Tag::Short(20));
+    assert_eq!(parse_value("1.5", Some(TAG_FLOAT)).unwrap(), Tag::Float(1.5));
+    assert_eq!(parse_value("5", Some(TAG_STRING)).unwrap(), Tag::String("5".into()));
+    assert_eq!(parse_value("1 2", Some(TAG_LONG_ARRAY)).unwrap(), Tag::LongArray(vec![1, 2]));
+    assert!(matches!(parse_value("20f", Some(TAG_SHORT)), Err(RuntimeErr::InvalidValue("short"))));
+    assert!(matches!(parse_value("\"a\"", Some(TAG_INT)), Err(RuntimeErr::InvalidValue("int"))));
+    assert!(matches!(parse_value("x", Some(TAG_INT)), Err(RuntimeErr::InvalidValue("int"))));
+}
+
+fn press(editor: &mut Editor, keys: &str) -> bool {
+    keys.chars().any(|c| editor.handle(KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE)))
+}
+
+fn key(editor: &mut Editor, code: KeyCode) -> bool {
+    editor.handle(KeyEvent::new(code, KeyModifiers::NONE))
+}
+
+#[test]
+fn edit_keys() {
+    let saved = Rc::new(RefCell::new(None));
+    let save = {
+        let saved = saved.clone();
+        Box::new(move |root: &CompoundTag| {
+            *saved.borrow_mut() = Some(root.clone());
+            Ok(())
+        })
+    };
+    let mut editor = Editor::new(Document::new(player()), "player.dat", DataFormat::Gzip, save);
+
+    // Down to HP, change it
+    press(&mut editor, "je");
+    assert_eq!(editor.selected(), &path(&["HP".into()]));
+    key(&mut editor, KeyCode::Backspace);
+    key(&mut editor, KeyCode::Backspace);
+    press(&mut editor, "7");
+    key(&mut editor, KeyCode::Enter);
+    assert_eq!(editor.document().get(&path(&["HP".into()])), Some(&Tag::Short(7)));
+
+    // Search opens the list the match is in, then add after it
+    press(&mut editor, "/-2");
+    key(&mut editor, KeyCode::Enter);
+    assert_eq!(editor.selected(), &path(&["Pos".into(), 1.into()]));
+    press(&mut editor, "a3");
+    key(&mut editor, KeyCode::Enter);
+    assert_eq!(editor.selected(), &path(&["Pos".into(), 2.into()]));
+    assert_eq!(editor.document().get(editor.selected()), Some(&Tag::Double(3.0)));
+
+    // Move it to the front, then delete it
+    press(&mut editor, "KK");
+    assert_eq!(editor.selected(), &path(&["Pos".into(), 0.into()]));
+    press(&mut editor, "dy");
+    assert_eq!(editor.document().get(&path(&["Pos".into(), 2.into()])), None);
+
+    // Unsaved changes are asked about
+    assert!(!press(&mut editor, "qn"));
+    press(&mut editor, "s");
+    assert_eq!(saved.borrow().as_ref(), Some(&editor.document().to_compound()));
+    assert!(press(&mut editor, "q"));
+}
+
+#[test]
+fn edit_draw() {
+    use ratatui::backend::TestBackend;
+    use ratatui::Terminal;
+
+    let save = Box::new(|_: &CompoundTag| Ok(()));
+    let mut editor = Editor::new(Document::new(player()), "player.dat", DataFormat::Gzip, save);
+    press(&mut editor, "jjjl");
+
+    let mut terminal = Terminal::new(TestBackend::new(60, 12)).unwrap();
+    terminal.draw(|frame| editor.draw(frame)).unwrap();
+    let screen = terminal.backend().buffer().content().iter().map(|cell| cell.symbol()).collect::<String>();
+
+    for line in [
+        " player.dat (gzip)",
+        "▾ {}  Player: 5 entries",
+        "    S   HP: 20",
+        "    \"\"  Name: \"Steve\"",
+        "  ▾ []  Pos: 2 entries",
+        "        D   [0] 1.5",
+        "  ▸ {}  abilities: 1 entry",
+        "    I[] UUID: 4 ints: 1 -2 3 4",
+    ] {
+        assert!(screen.contains(line), "missing line: {}", line);
+    }
+}
@@ -1 +1,43 @@
+mod batch;
 mod cli;
+mod edit;
+mod shell;
+mod watch;
+mod xml;
+mod yaml;
+
+use nbt::encode::write_nbt;
+use nbt::{CompoundTag, ListTag, Map, Tag};
+
+/// Empty directory of its own for a test, in the system's temporary directory.
+fn temp_dir(name: &str) -> std::path::PathBuf {
+    let dir = std::env::temp_dir().join(format!("nbtc-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// Small player-like document: HP, Name, a list of doubles, a compound and an int array.
+fn player() -> CompoundTag {
+    let mut pos = ListTag::new();
+    pos.push(Tag::Double(1.5)).unwrap();
+    pos.push(Tag::Double(-2.0)).unwrap();
+
+    let mut abilities = Map::new();
+    abilities.insert("flying".into(), Tag::Byte(0));
+
+    let mut root = CompoundTag::new();
+    root.push("HP".into(), Tag::Short(20));
+    root.push("Name".into(), Tag::String("Steve".into()));
+    root.push("Pos".into(), Tag::List(pos));
+    root.push("abilities".into(), Tag::Compound(abilities));
+    root.push("UUID".into(), Tag::IntArray(vec![1, -2, 3, 4]));
+    root
+}
+
+/// Encoded `root`, to compare documents holding NaNs bit for bit.
+fn to_nbt(root: &CompoundTag) -> Vec<u8> {
+    let mut nbt = Vec::new();
+    write_nbt(root, &mut nbt).unwrap();
+    nbt
+}
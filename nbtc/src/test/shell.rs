@@ -0,0 +1,143 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use nbt::{CompoundTag, ListTag, Map, Tag};
+use nbtc::edit::Document;
+
+use crate::err::RuntimeErr;
+use crate::shell::Shell;
+
+/// level.dat-like root holding the shared player under `Data.Player`, with the
+/// entries the scripts edit.
+fn level() -> CompoundTag {
+    let mut player = super::player();
+    player.push("Health".into(), Tag::Float(4.0));
+    player.push("ActiveEffects".into(), Tag::List(ListTag::new()));
+    player.push("custom name".into(), Tag::String("Steve".into()));
+
+    let mut data = Map::new();
+    data.insert("Player".into(), Tag::Compound(player.into_map()));
+
+    let mut root = CompoundTag::new();
+    root.push("Data".into(), Tag::Compound(data));
+    root
+}
+
+fn shell() -> (Shell, Rc<RefCell<Option<CompoundTag>>>) {
+    let saved = Rc::new(RefCell::new(None));
+    let save = {
+        let saved = saved.clone();
+        Box::new(move |root: &CompoundTag| {
+            *saved.borrow_mut() = Some(root.clone());
+            Ok(())
+        })
+    };
+
+    (Shell::new(Document::new(level()), "level.dat", save), saved)
+}
+
+fn run_script(shell: &mut Shell, script: &str) -> (Result<(), RuntimeErr>, String) {
+    let mut out = Vec::new();
+    let res = shell.run_script(script.as_bytes(), &mut out);
+    (res, String::from_utf8(out).unwrap())
+}
+
+#[test]
+fn shell_script() {
+    let (mut shell, saved) = shell();
+    let script = r#"
+        # Heal the player and clear their effects
+        cd Data.Player
+        set Health 20f
+        rm ActiveEffects
+        add Pos 64
+        mv "custom name" CustomName
+        set Level 30
+        set Tags []
+        get Pos[2]
+        ls
+        pwd
+        cd ../..
+        get Data.Player.CustomName
+        save
+    "#;
+    let (res, out) = run_script(&mut shell, script);
+    res.unwrap();
+
+    assert_eq!(
+        out,
+        "64.0\n\
+         S   HP: 20\n\
+         \"\"  Name: \"Steve\"\n\
+         []  Pos: 3 entries\n\
+         {}  abilities: 1 entry\n\
+         I[] UUID: 4 ints: 1 -2 3 4\n\
+         F   Health: 20.0\n\
+         \"\"  CustomName: \"Steve\"\n\
+         I   Level: 30\n\
+         []  Tags: 0 entries\n\
+         /Data.Player\n\
+         Steve\n"
+    );
+
+    let saved = saved.borrow();
+    let Some(Tag::Compound(data)) = saved.as_ref().unwrap().get("Data") else { panic!("no Data") };
+    let Some(Tag::Compound(player)) = data.get("Player") else { panic!("no Player") };
+    assert_eq!(player.get("Health"), Some(&Tag::Float(20.0)));
+    assert_eq!(player.get("ActiveEffects"), None);
+    assert!(!shell.document().is_modified());
+}
+
+#[test]
+fn shell_errors() {
+    let (mut shell, saved) = shell();
+
+    let (res, _) = run_script(&mut shell, "cd Data\n\nset Player.Health 20s\nsave");
+    match res {
+        Err(RuntimeErr::ScriptContext { line: 3, source }) => {
+            assert!(matches!(*source, RuntimeErr::InvalidValue("float")))
+        }
+        res => panic!("expected an error on line 3: {:?}", res),
+    }
+    assert!(saved.borrow().is_none());
+
+    for (line, expected) in [
+        ("jump", "UnknownCommand"),
+        ("get", "MissingArgument"),
+        ("cd Player.Health", "WrongTagType"),
+        ("rm Nope", "NoSuchTag"),
+        ("get Pos[x]", "InvalidPath"),
+        ("add Player 1", "WrongTagType"),
+    ] {
+        let err = shell.run(line, &mut Vec::new()).unwrap_err();
+        assert!(format!("{:?}", err).starts_with(expected), "{}: {:?}", line, err);
+    }
+}
+
+#[test]
+fn shell_undo() {
+    let (mut shell, _) = shell();
+
+    // Undoing the rename of the current compound leaves it for the closest tag left
+    let (res, out) = run_script(&mut shell, "cd Data.Player\nmv /Data Root\npwd\nundo\npwd\nundo");
+    res.unwrap();
+    assert_eq!(out, "/Root.Player\n/\n");
+    assert!(!shell.document().is_modified());
+}
+
+#[test]
+fn shell_complete() {
+    let (mut shell, _) = shell();
+    let complete = |shell: &Shell, line: &str| shell.complete(line);
+
+    assert_eq!(complete(&shell, "s"), (0, vec!["set".to_string(), "save".to_string()]));
+    assert_eq!(complete(&shell, "cd D"), (3, vec!["Data".to_string()]));
+    assert_eq!(complete(&shell, "cd Data.P"), (3, vec!["Data.Player".to_string()]));
+    assert_eq!(complete(&shell, "ls /Data.Player.c"), (3, vec![r#"/Data.Player."custom name""#.to_string()]));
+    // Values aren't paths
+    assert_eq!(complete(&shell, "set Data D").1, Vec::<String>::new());
+
+    shell.run("cd Data.Player", &mut Vec::new()).unwrap();
+    assert_eq!(complete(&shell, "get A"), (4, vec!["ActiveEffects".to_string()]));
+    assert_eq!(complete(&shell, "get ../P"), (4, vec!["../Player".to_string()]));
+}
@@ -0,0 +1,73 @@
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use super::temp_dir;
+use crate::cmd::target_path;
+use crate::util::{Watcher, DEBOUNCE};
+
+#[test]
+fn watch_debounce() {
+    let dir = temp_dir("watch_debounce");
+    std::fs::create_dir(dir.join("sub")).unwrap();
+    std::fs::write(dir.join("a.xml"), "<compound/>").unwrap();
+    std::fs::write(dir.join("b.dat"), "").unwrap();
+    std::fs::write(dir.join("sub/c.xml"), "<compound/>").unwrap();
+
+    let mut watcher = Watcher::new(&dir, &["xml"]);
+    let start = Instant::now();
+    // Files are handed out once they have stayed the same long enough
+    assert!(watcher.poll(start).unwrap().is_empty());
+    assert!(watcher.poll(start + DEBOUNCE / 2).unwrap().is_empty());
+    assert_eq!(watcher.poll(start + DEBOUNCE).unwrap(), [dir.join("a.xml"), dir.join("sub/c.xml")]);
+    assert!(watcher.poll(start + DEBOUNCE * 2).unwrap().is_empty());
+
+    // Writes in quick succession count as one change
+    let later = start + DEBOUNCE * 3;
+    std::fs::write(dir.join("a.xml"), "<compound>").unwrap();
+    assert!(watcher.poll(later).unwrap().is_empty());
+    std::fs::write(dir.join("a.xml"), "<compound></compound>").unwrap();
+    assert!(watcher.poll(later + DEBOUNCE / 2).unwrap().is_empty());
+    assert!(watcher.poll(later + DEBOUNCE).unwrap().is_empty());
+    assert_eq!(watcher.poll(later + DEBOUNCE * 2).unwrap(), [dir.join("a.xml")]);
+
+    // Removed files are no longer watched
+    std::fs::remove_file(dir.join("sub/c.xml")).unwrap();
+    assert!(watcher.poll(later + DEBOUNCE * 3).unwrap().is_empty());
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn watch_file() {
+    let dir = temp_dir("watch_file");
+    let file = dir.join("level.dat");
+
+    let mut watcher = Watcher::new(&file, &["xml"]);
+    let start = Instant::now();
+    // A watched file may not exist yet, its extension doesn't matter
+    assert!(watcher.poll(start).unwrap().is_empty());
+    std::fs::write(&file, "data").unwrap();
+    assert!(watcher.poll(start + Duration::from_millis(10)).unwrap().is_empty());
+    assert_eq!(watcher.poll(start + DEBOUNCE * 2).unwrap(), [file]);
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn target_paths() {
+    let path = Path::new;
+
+    assert_eq!(target_path(path("src.xml"), path("src.xml"), None, "dat"), path("src.dat"));
+    assert_eq!(
+        target_path(path("src.xml"), path("src.xml"), Some(path("out/level.dat")), "dat"),
+        path("out/level.dat")
+    );
+    assert_eq!(
+        target_path(path("pack"), path("pack/data/a.yml"), None, "dat"),
+        path("pack/data/a.dat")
+    );
+    assert_eq!(
+        target_path(path("world"), path("world/data/raids.dat"), Some(path("text")), "xml"),
+        path("text/data/raids.xml")
+    );
+}
@@ -0,0 +1,367 @@
+use std::io::{BufReader, BufWriter};
+
+use nbt::encode::write_nbt;
+use nbt::stream::EventReader;
+use nbt::{ListTag, Tag};
+use nbtc::err::RuntimeErr;
+use nbtc::xml::read::read_xml;
+use nbtc::xml::write::print_xml;
+use nbtc::xml::{FloatFormat, XmlOptions};
+
+fn compile(xml: &str) -> Result<nbt::CompoundTag, RuntimeErr> {
+    read_xml(&mut BufReader::new(xml.as_bytes()))
+}
+
+/// Splits a located error into line, column, path and the error itself.
+fn located(err: RuntimeErr) -> (usize, usize, String, RuntimeErr) {
+    match err {
+        RuntimeErr::XmlContext {
+            line,
+            column,
+            path,
+            source,
+        } => (line, column, path, *source),
+        other => panic!("error without location: {:?}", other),
+    }
+}
+
+#[test]
+fn xml_compile() {
+    let root = compile(
+        r#"<compound>
+    <int name="HP">27</int>
+    <string name="Empty"/>
+    <list name="Pos">
+        <double>1.5</double>
+    </list>
+</compound>"#,
+    )
+    .unwrap();
+
+    assert_eq!(root.get("HP"), Some(&Tag::Int(27)));
+    assert_eq!(root.get("Empty"), Some(&Tag::String("".into())));
+}
+
+#[test]
+fn xml_unknown_element() {
+    let err = compile(
+        r#"<compound>
+    <compound name="Level">
+        <list name="Sections">
+            <compound/>
+            <compound>
+                <bogus name="Y">1</bogus>
+            </compound>
+        </list>
+    </compound>
+</compound>"#,
+    )
+    .unwrap_err();
+
+    assert_eq!(
+        err.to_string(),
+        "Unknown element in xml: <bogus>\n    at line: 6, column: 17\n    in tag: Level.Sections[1]"
+    );
+    let (line, column, path, err) = located(err);
+    assert_eq!((line, column), (6, 17));
+    assert_eq!(path, "Level.Sections[1]");
+    assert!(matches!(err, RuntimeErr::UnknownElement(name) if name == "bogus"));
+}
+
+#[test]
+fn xml_text_in_list() {
+    let err = compile("<compound>\n<list name=\"a\">12</list>\n</compound>").unwrap_err();
+
+    let (line, _, path, err) = located(err);
+    assert_eq!(line, 2);
+    assert_eq!(path, "a");
+    assert!(matches!(err, RuntimeErr::UnexpectedText));
+}
+
+#[test]
+fn xml_missing_name() {
+    let err = compile("<compound><int>1</int></compound>").unwrap_err();
+
+    let (line, column, path, err) = located(err);
+    assert_eq!((line, column), (1, 11));
+    assert_eq!(path, "");
+    assert!(matches!(err, RuntimeErr::MissingName));
+}
+
+#[test]
+fn xml_unsupported_and_truncated() {
+    let (_, _, _, err) = located(compile("<!DOCTYPE nbt><compound/>").unwrap_err());
+    assert!(matches!(err, RuntimeErr::UnsupportedXml("DOCTYPE")));
+
+    let (_, _, path, err) = located(compile("<compound><list name=\"l\">").unwrap_err());
+    assert_eq!(path, "l");
+    assert!(matches!(err, RuntimeErr::UnexpectedEof));
+
+    let (_, _, _, err) = located(compile("<int>1</int>").unwrap_err());
+    assert!(matches!(err, RuntimeErr::NBTDecode(_)));
+}
+
+#[test]
+fn xml_decl_comments_cdata() {
+    let root = compile(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<!-- decompiled from level.dat -->
+<compound>
+    <!-- spawn point -->
+    <int name="SpawnX">12</int>
+    <string name="Raw"><![CDATA[<b> & </b>]]></string>
+    <string name="Mixed">a<![CDATA[<]]>b</string>
+    <list name="Pos">
+        <!-- x -->
+        <double>1.5</double>
+    </list>
+</compound>"#,
+    )
+    .unwrap();
+
+    assert_eq!(root.get("SpawnX"), Some(&Tag::Int(12)));
+    assert_eq!(root.get("Raw"), Some(&Tag::String("<b> & </b>".into())));
+    assert_eq!(root.get("Mixed"), Some(&Tag::String("a<b".into())));
+    match root.get("Pos") {
+        Some(Tag::List(pos)) => assert_eq!(pos.len(), 1),
+        other => panic!("unexpected Pos: {:?}", other),
+    }
+
+    let err = compile("<compound><?xml version=\"1.0\"?></compound>").unwrap_err();
+    assert!(matches!(err, RuntimeErr::XmlContext { .. }));
+}
+
+fn decompile(nbt: &[u8], float_format: FloatFormat) -> String {
+    let mut out = BufWriter::new(Vec::new());
+    let options = XmlOptions {
+        float_format,
+        ..Default::default()
+    };
+    print_xml(&mut out, EventReader::new(nbt), &options).unwrap();
+    String::from_utf8(out.into_inner().unwrap()).unwrap()
+}
+
+#[test]
+fn xml_string_whitespace() {
+    use nbtc::xml::validate::validate_xml;
+
+    let mut root = nbt::CompoundTag::new();
+    for (key, value) in [("Padded", "  two sides  "), ("Blank", "   "), ("Lines", "\n\ta\n")] {
+        root.push(key.into(), Tag::String(value.into()));
+    }
+    let mut nbt = Vec::new();
+    write_nbt(&root, &mut nbt).unwrap();
+
+    let xml = decompile(&nbt, FloatFormat::Decimal);
+    validate_xml(xml.as_bytes()).unwrap();
+    let mut back = Vec::new();
+    write_nbt(&compile(&xml).unwrap(), &mut back).unwrap();
+    assert_eq!(back, nbt, "whitespace lost in:\n{}", xml);
+
+    // Whitespace around numbers is still allowed
+    let root = compile("<compound>\n  <int name=\"a\"> 5\n</int>\n</compound>\n").unwrap();
+    assert_eq!(root.get("a"), Some(&Tag::Int(5)));
+}
+
+#[test]
+fn xml_escaped_text() {
+    let special = r#"a & b <c> "d" 'e'"#;
+    let mut inner = nbt::CompoundTag::new();
+    inner.push(special.into(), Tag::String(special.into()));
+    let mut root = nbt::CompoundTag::new();
+    root.push(special.into(), Tag::Compound(inner.into_map()));
+    let mut nbt = Vec::new();
+    write_nbt(&root, &mut nbt).unwrap();
+
+    // Every cycle reads back the same data, escapes don't pile up
+    let mut data = nbt.clone();
+    for _ in 0..2 {
+        let xml = decompile(&data, FloatFormat::Decimal);
+        nbtc::xml::validate::validate_xml(xml.as_bytes()).unwrap();
+        data.clear();
+        write_nbt(&compile(&xml).unwrap(), &mut data).unwrap();
+        assert_eq!(data, nbt, "escapes changed in:\n{}", xml);
+    }
+
+    let root = compile(r#"<compound name="&lt;&amp;&gt;"><string name="&quot;">&apos;&amp;</string></compound>"#);
+    assert_eq!(root.unwrap().get("\""), Some(&Tag::String("'&".into())));
+}
+
+#[test]
+fn xml_floats_lossless() {
+    let mut doubles = ListTag::new();
+    for d in [f64::NAN, f64::INFINITY, f64::NEG_INFINITY, -0.0, 0.1, 1e300, f64::MIN_POSITIVE] {
+        doubles.push(Tag::Double(d)).unwrap();
+    }
+    // NaN with a payload can only be kept as bits
+    doubles.push(Tag::Double(f64::from_bits(0xfff8_0000_dead_beef))).unwrap();
+
+    let mut floats = ListTag::new();
+    for f in [f32::NAN, f32::NEG_INFINITY, -0.0, 16_777_217.0, 3.4e38, f32::from_bits(1)] {
+        floats.push(Tag::Float(f)).unwrap();
+    }
+
+    let mut root = nbt::CompoundTag::new();
+    root.push("Doubles".into(), Tag::List(doubles));
+    root.push("Floats".into(), Tag::List(floats));
+    let mut nbt = Vec::new();
+    write_nbt(&root, &mut nbt).unwrap();
+
+    for format in [FloatFormat::Decimal, FloatFormat::Bits] {
+        let xml = decompile(&nbt, format);
+        let mut back = Vec::new();
+        write_nbt(&compile(&xml).unwrap(), &mut back).unwrap();
+        assert_eq!(back, nbt, "{:?} lost bits in:\n{}", format, xml);
+    }
+
+    let xml = decompile(&nbt, FloatFormat::Decimal);
+    for text in ["NaN", "-Infinity", "-0.0", "1e300", "0xfff80000deadbeef"] {
+        assert!(xml.contains(&format!(">{}<", text)), "{} missing in:\n{}", text, xml);
+    }
+    let xml = decompile(&nbt, FloatFormat::Bits);
+    assert!(xml.contains(">0x7ff0000000000000<"));
+    assert!(xml.contains(">0x00000001<"));
+}
+
+#[test]
+fn xml_validate() {
+    use nbtc::xml::validate::validate_xml;
+
+    validate_xml(
+        r#"<?xml version="1.0"?>
+<compound name="root">
+    <list name="Pos"><double>1.5</double><double>NaN</double></list>
+    <int_array name="a"><int>1</int></int_array>
+    <string name="s"/>
+</compound>"#
+            .as_bytes(),
+    )
+    .unwrap();
+
+    let err = validate_xml(
+        r#"<compound>
+    <int>1</int>
+    <list name="Pos" color="red">
+        <double>1.5</double>
+        <int>2</int>
+        <double name="z">3</double>
+    </list>
+    <byte_array name="b"><int>1</int></byte_array>
+    <byte name="big">300</byte>
+    <short name="none"/>
+    <bogus name="x"><int/></bogus>
+</compound>"#
+            .as_bytes(),
+    )
+    .unwrap_err();
+
+    let problems = match err {
+        RuntimeErr::InvalidXml(problems) => problems,
+        other => panic!("unexpected error: {:?}", other),
+    };
+    let problems: Vec<_> = problems.into_iter().map(located).collect();
+    let summary: Vec<_> = problems
+        .iter()
+        .map(|(line, _, path, _)| (*line, path.as_str()))
+        .collect();
+    assert_eq!(
+        summary,
+        vec![
+            (2, ""),
+            (3, ""),
+            (5, "Pos"),
+            (6, "Pos"),
+            (8, "b"),
+            (9, "big"),
+            (10, "none"),
+            (11, ""),
+        ]
+    );
+
+    let errs: Vec<_> = problems.into_iter().map(|(_, _, _, err)| err).collect();
+    assert!(matches!(errs[0], RuntimeErr::MissingName));
+    assert!(matches!(&errs[1], RuntimeErr::UnknownAttribute(name) if name == "color"));
+    assert!(matches!(errs[2], RuntimeErr::MixedList { expected: "double", found: "int" }));
+    assert!(matches!(errs[3], RuntimeErr::UnexpectedName));
+    assert!(matches!(
+        errs[4],
+        RuntimeErr::UnexpectedElement { parent: "byte_array", child: "int" }
+    ));
+    assert!(matches!(errs[5], RuntimeErr::ParseInt(_)));
+    assert!(matches!(errs[6], RuntimeErr::ParseInt(_)));
+    assert!(matches!(&errs[7], RuntimeErr::UnknownElement(name) if name == "bogus"));
+}
+
+#[test]
+fn xml_compact_arrays() {
+    use nbtc::xml::validate::validate_xml;
+
+    let mut root = nbt::CompoundTag::new();
+    // Lengths around base64 groups, and past the 4096 values of a stream chunk
+    for len in [0, 1, 2, 3, 4, 5000] {
+        let bytes = (0..len).map(|i| (i * 7 % 256) as i8).collect();
+        root.push(format!("Bytes{}", len).into(), Tag::ByteArray(bytes));
+    }
+    root.push("Ints".into(), Tag::IntArray((-2500..2500).map(|i| i * 859).collect()));
+    root.push("Longs".into(), Tag::LongArray(vec![i64::MIN, 0, i64::MAX]));
+    let mut nbt = Vec::new();
+    write_nbt(&root, &mut nbt).unwrap();
+
+    let mut out = BufWriter::new(Vec::new());
+    let options = XmlOptions {
+        compact_arrays: true,
+        ..Default::default()
+    };
+    print_xml(&mut out, EventReader::new(nbt.as_slice()), &options).unwrap();
+    let xml = String::from_utf8(out.into_inner().unwrap()).unwrap();
+
+    assert!(xml.contains(r#"<byte_array name="Bytes3" encoding="base64">AAcO</byte_array>"#));
+    assert!(xml.contains(r#"<byte_array name="Bytes1" encoding="base64">AA==</byte_array>"#));
+    assert!(xml.contains(r#"<long_array name="Longs">-9223372036854775808 0 9223372036854775807</long_array>"#));
+    assert!(!xml.contains("<int>"));
+
+    validate_xml(xml.as_bytes()).unwrap();
+    let mut back = Vec::new();
+    write_nbt(&compile(&xml).unwrap(), &mut back).unwrap();
+    assert_eq!(back, nbt);
+
+    let bad = r#"<compound><byte_array name="b" encoding="base64">AA=</byte_array></compound>"#;
+    let (_, _, path, err) = located(compile(bad).unwrap_err());
+    assert_eq!(path, "b");
+    assert!(matches!(err, RuntimeErr::InvalidBase64));
+    assert!(validate_xml(bad.as_bytes()).is_err());
+
+    let hand_written = compile("<compound><int_array name=\"a\">\n  1 -2\n  3\n</int_array></compound>");
+    assert_eq!(hand_written.unwrap().get("a"), Some(&Tag::IntArray(vec![1, -2, 3])));
+}
+
+#[test]
+fn xml_empty_list_type() {
+    use nbtc::xml::validate::validate_xml;
+
+    let mut root = nbt::CompoundTag::new();
+    root.push("Items".into(), Tag::List(ListTag::with_type(nbt::consts::TAG_COMPOUND).unwrap()));
+    root.push("Untyped".into(), Tag::List(ListTag::new()));
+    let mut nbt = Vec::new();
+    write_nbt(&root, &mut nbt).unwrap();
+
+    let xml = decompile(&nbt, FloatFormat::Decimal);
+    assert!(xml.contains(r#"<list name="Items" type="compound">"#));
+    assert!(xml.contains(r#"<list name="Untyped">"#));
+
+    validate_xml(xml.as_bytes()).unwrap();
+    let mut back = Vec::new();
+    write_nbt(&compile(&xml).unwrap(), &mut back).unwrap();
+    assert_eq!(back, nbt);
+
+    let mixed = r#"<compound><list name="l" type="int"><byte>1</byte></list></compound>"#;
+    let (_, _, path, err) = located(compile(mixed).unwrap_err());
+    assert_eq!(path, "l");
+    assert!(matches!(err, RuntimeErr::MixedList { expected: "int", found: "byte" }));
+    assert!(validate_xml(mixed.as_bytes()).is_err());
+
+    let unknown = r#"<compound><list name="l" type="bogus"/></compound>"#;
+    let (_, _, _, err) = located(compile(unknown).unwrap_err());
+    assert!(matches!(&err, RuntimeErr::UnknownListType(value) if value == "bogus"));
+    assert!(validate_xml(unknown.as_bytes()).is_err());
+}
@@ -0,0 +1,110 @@
+use nbt::{consts, CompoundTag, ListTag, Tag};
+use nbtc::err::RuntimeErr;
+use nbtc::toml::{read_toml, write_toml};
+use nbtc::yaml::{read_yaml, write_yaml};
+
+use super::{player, to_nbt};
+
+/// [`player`] along with the values YAML and TOML need tags or quotes for.
+fn sample() -> CompoundTag {
+    let mut inner = nbt::Map::new();
+    inner.insert("id".into(), Tag::String("true".into()));
+    inner.insert("Count".into(), Tag::Byte(-3));
+    let mut items = ListTag::new();
+    items.push(Tag::Compound(inner)).unwrap();
+
+    let mut root = player();
+    root.push("Motion".into(), Tag::List(ListTag::try_from(vec![Tag::Double(-0.0)]).unwrap()));
+    root.push("Items".into(), Tag::List(items));
+    root.push("Empty".into(), Tag::List(ListTag::with_type(consts::TAG_COMPOUND).unwrap()));
+    root.push("Untyped".into(), Tag::List(ListTag::new()));
+    root.push("Bytes".into(), Tag::ByteArray(vec![]));
+    root.push("Time".into(), Tag::Long(i64::MIN));
+    root.push("Speed".into(), Tag::Float(0.1));
+    root.push("Odd".into(), Tag::Float(f32::from_bits(0x7fc0_0001)));
+    root.push("Far".into(), Tag::Double(f64::INFINITY));
+    root
+}
+
+#[test]
+fn yaml_round_trip() {
+    let root = sample();
+    let mut out = Vec::new();
+    write_yaml(&mut out, &root).unwrap();
+    let yaml = String::from_utf8(out).unwrap();
+
+    assert!(yaml.contains("HP: !short 20\n"));
+    assert!(yaml.contains("Name: Steve\n"));
+    assert!(yaml.contains("- !double -0.0\n"));
+    assert!(yaml.contains("Empty: !compound_list []\n"));
+    assert!(yaml.contains("Untyped: []\n"));
+    assert!(yaml.contains("Speed: !float 0.1\n"));
+    assert!(yaml.contains("Odd: !float '0x7fc00001'\n"));
+    assert!(yaml.contains("Far: !double .inf\n"));
+    // Strings that would read back as other YAML values are quoted
+    assert!(yaml.contains("id: 'true'\n"));
+
+    let back = read_yaml(yaml.as_bytes()).unwrap();
+    assert_eq!(to_nbt(&back), to_nbt(&root));
+}
+
+#[test]
+fn yaml_hand_written() {
+    let root = read_yaml("Level:\n  xPos: 3\n  Ratio: 0.5\n  Lit: true\n  Ids: !long_array [1, 2]\n".as_bytes()).unwrap();
+    let level = match root.get("Level") {
+        Some(Tag::Compound(level)) => level,
+        other => panic!("not a compound: {:?}", other),
+    };
+    assert_eq!(level.get("xPos"), Some(&Tag::Int(3)));
+    assert_eq!(level.get("Ratio"), Some(&Tag::Double(0.5)));
+    assert_eq!(level.get("Lit"), Some(&Tag::Byte(1)));
+    assert_eq!(level.get("Ids"), Some(&Tag::LongArray(vec![1, 2])));
+}
+
+#[test]
+fn yaml_errors() {
+    let located = |yaml: &str| match read_yaml(yaml.as_bytes()).unwrap_err() {
+        RuntimeErr::TagContext { path, source } => (path, *source),
+        other => panic!("error without location: {:?}", other),
+    };
+
+    let (path, err) = located("Level:\n  Sections:\n  - !int 1\n  - !byte 1\n");
+    assert_eq!(path, "Level.Sections[1]");
+    assert!(matches!(err, RuntimeErr::MixedList { expected: "int", found: "byte" }));
+
+    let (path, err) = located("b: !byte 300\n");
+    assert_eq!(path, "b");
+    assert!(matches!(err, RuntimeErr::InvalidValue("byte")));
+
+    let (path, err) = located("a: !int_array [1, x]\n");
+    assert_eq!(path, "a[1]");
+    assert!(matches!(err, RuntimeErr::InvalidValue("int_array")));
+
+    let (_, err) = located("u: !uint 1\n");
+    assert!(matches!(err, RuntimeErr::UnknownTag(tag) if tag == "uint"));
+
+    let (_, err) = located("n: null\n");
+    assert!(matches!(err, RuntimeErr::InvalidValue("null")));
+
+    assert!(matches!(read_yaml("- 1\n".as_bytes()), Err(RuntimeErr::NBTDecode(_))));
+    assert!(matches!(read_yaml("a: [\n".as_bytes()), Err(RuntimeErr::YamlError(_))));
+}
+
+#[test]
+fn toml_round_trip() {
+    let root = sample();
+    let mut out = Vec::new();
+    write_toml(&mut out, &root).unwrap();
+    let toml = String::from_utf8(out).unwrap();
+
+    assert!(toml.starts_with("type = \"compound\"\n"));
+    assert!(toml.contains("[value.HP]\ntype = \"short\"\nvalue = 20\n"));
+
+    let back = read_toml(toml.as_bytes()).unwrap();
+    assert_eq!(to_nbt(&back), to_nbt(&root));
+    let keys: Vec<_> = back.iter().map(|(key, _)| key.as_ref()).collect();
+    assert_eq!(keys[..3], ["HP", "Name", "Pos"]);
+
+    let err = read_toml("type = \"compound\"\n[value.b]\ntype = \"byte\"\nvalue = 300\n".as_bytes());
+    assert!(matches!(err, Err(RuntimeErr::JsonError(err)) if err.path() == Some("b")));
+}
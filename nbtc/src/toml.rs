@@ -0,0 +1,38 @@
+//! TOML form of NBT data.
+//!
+//! TOML has no tags to give types with, so it holds the typed JSON layout of
+//! [`nbt::json`]: every tag is a table with its `type` and `value`.
+//!
+//! ```toml
+//! type = "compound"
+//!
+//! [value.HP]
+//! type = "short"
+//! value = 20
+//! ```
+
+use std::io::{Read, Write};
+
+use nbt::json::JsonMode;
+use nbt::CompoundTag;
+
+use crate::err::{self, RuntimeErr};
+
+/// Writes `root` as TOML.
+pub fn write_toml<W: Write>(mut writer: W, root: &CompoundTag) -> err::Result<()> {
+    let text = ::toml::to_string_pretty(&nbt::json::to_value(root, JsonMode::Typed))?;
+    writer.write_all(text.as_bytes())?;
+
+    Ok(())
+}
+
+/// Reads a compound from TOML, as written by [`write_toml`].
+pub fn read_toml<R: Read>(mut reader: R) -> err::Result<CompoundTag> {
+    let mut text = String::new();
+    reader.read_to_string(&mut text)?;
+
+    // Tables keep their key order, unlike reading into JSON values right away
+    let table: ::toml::Table = ::toml::from_str(&text)?;
+    let value = serde_json::to_value(table).map_err(nbt::err::JsonError::Json)?;
+    nbt::json::from_value(&value).map_err(RuntimeErr::from)
+}
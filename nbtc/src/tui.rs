@@ -0,0 +1,661 @@
+//! Terminal UI of `nbtc edit`, on top of [`nbtc::edit::Document`].
+//!
+//! Draws with ratatui on crossterm, which only needs a terminal, so it works as well
+//! over SSH as it does locally. Keys are handled by [`Editor::handle`] apart from the
+//! terminal, so that editing can be tested without one.
+
+use std::collections::HashSet;
+
+use nbt::tree::icon;
+use nbt::Tag;
+use nbtc::edit::{parse_tag, summary, value_text, Document, Path, Step};
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout, Position};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{List, ListItem, ListState};
+use ratatui::Frame;
+
+use crate::cmd::Save;
+use crate::err::{self, RuntimeErr};
+use crate::util::DataFormat;
+use crate::xml::{element_of, type_id_of};
+
+const KEY_HINTS: &str = "←→ fold  e edit  a add  r rename  d delete  J/K move  u undo  / search  n/N next  s save  q quit";
+
+pub(crate) struct Editor {
+    doc: Document,
+    file_name: String,
+    format: DataFormat,
+    save: Save,
+    /// Lists and compounds showing their tags, the root always does
+    expanded: HashSet<Path>,
+    selected: Path,
+    list: ListState,
+    mode: Mode,
+    status: Option<Status>,
+    /// Last search, for `n` and `N`
+    search: Option<(String, usize)>,
+}
+
+enum Mode {
+    Browse,
+    Prompt(Prompt, Input),
+    Confirm(Confirm),
+}
+
+/// What the text typed in is for.
+enum Prompt {
+    Edit(Path),
+    /// New tag in the compound or list at `parent`, at `index`
+    Add { parent: Path, index: usize },
+    Rename(Path),
+    Search,
+}
+
+/// What a `y` answers.
+enum Confirm {
+    Delete(Path),
+    Quit,
+}
+
+enum Status {
+    Info(String),
+    Error(String),
+}
+
+/// One visible tag.
+struct Row {
+    path: Path,
+    depth: usize,
+}
+
+impl Editor {
+    pub(crate) fn new(doc: Document, file_name: &str, format: DataFormat, save: Save) -> Self {
+        Self {
+            doc,
+            file_name: file_name.to_string(),
+            format,
+            save,
+            expanded: HashSet::from([Path::root()]),
+            selected: Path::root(),
+            list: ListState::default(),
+            mode: Mode::Browse,
+            status: None,
+            search: None,
+        }
+    }
+
+    #[cfg(test)]
+    pub(crate) fn document(&self) -> &Document {
+        &self.doc
+    }
+
+    #[cfg(test)]
+    pub(crate) fn selected(&self) -> &Path {
+        &self.selected
+    }
+
+    /// Visible rows, in the order they are drawn.
+    fn rows(&self) -> Vec<Row> {
+        let mut rows = Vec::new();
+        self.push_rows(self.doc.root(), Path::root(), 0, &mut rows);
+        rows
+    }
+
+    fn push_rows(&self, tag: &Tag, path: Path, depth: usize, rows: &mut Vec<Row>) {
+        let expanded = self.expanded.contains(&path);
+        rows.push(Row {
+            path: path.clone(),
+            depth,
+        });
+        if !expanded {
+            return;
+        }
+
+        match tag {
+            Tag::Compound(map) => {
+                for (key, tag) in map {
+                    self.push_rows(tag, path.join(key.as_ref()), depth + 1, rows);
+                }
+            }
+            Tag::List(list) => {
+                for (index, tag) in list.iter().enumerate() {
+                    self.push_rows(tag, path.join(index), depth + 1, rows);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Handles a key press, returns `true` once the editor should close.
+    pub(crate) fn handle(&mut self, key: KeyEvent) -> bool {
+        if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
+            return self.quit();
+        }
+
+        match std::mem::replace(&mut self.mode, Mode::Browse) {
+            Mode::Browse => {
+                self.status = None;
+                return self.browse(key);
+            }
+            Mode::Prompt(prompt, mut input) => match key.code {
+                KeyCode::Enter => self.submit(prompt, &input.text),
+                KeyCode::Esc => {}
+                _ => {
+                    input.handle(key);
+                    self.mode = Mode::Prompt(prompt, input);
+                }
+            },
+            Mode::Confirm(confirm) => match (key.code, confirm) {
+                (KeyCode::Char('y' | 'Y'), Confirm::Delete(path)) => self.delete(&path),
+                (KeyCode::Char('y' | 'Y'), Confirm::Quit) => return true,
+                _ => {}
+            },
+        }
+
+        false
+    }
+
+    fn browse(&mut self, key: KeyEvent) -> bool {
+        let shift = key.modifiers.contains(KeyModifiers::SHIFT);
+        match key.code {
+            KeyCode::Up if shift => self.move_by(-1),
+            KeyCode::Down if shift => self.move_by(1),
+            KeyCode::Char('K') => self.move_by(-1),
+            KeyCode::Char('J') => self.move_by(1),
+            KeyCode::Up | KeyCode::Char('k') => self.select_by(-1),
+            KeyCode::Down | KeyCode::Char('j') => self.select_by(1),
+            KeyCode::PageUp => self.select_by(-(self.page() as isize)),
+            KeyCode::PageDown => self.select_by(self.page() as isize),
+            KeyCode::Home | KeyCode::Char('g') => self.select_by(isize::MIN),
+            KeyCode::End | KeyCode::Char('G') => self.select_by(isize::MAX),
+            KeyCode::Right | KeyCode::Char('l') => self.expand(),
+            KeyCode::Left | KeyCode::Char('h') => self.collapse(),
+            KeyCode::Enter | KeyCode::Char(' ') if self.is_container(&self.selected) => self.toggle(),
+            KeyCode::Enter | KeyCode::Char('e') => self.start_edit(),
+            KeyCode::Char('a') => self.start_add(),
+            KeyCode::Char('r') => self.start_rename(),
+            KeyCode::Char('d') | KeyCode::Delete => match self.selected.is_root() {
+                true => self.error(&RuntimeErr::RootTag("removed")),
+                false => self.mode = Mode::Confirm(Confirm::Delete(self.selected.clone())),
+            },
+            KeyCode::Char('/') => {
+                let last = self.search.as_ref().map(|(needle, _)| needle.as_str());
+                self.mode = Mode::Prompt(Prompt::Search, Input::new(last.unwrap_or_default()));
+            }
+            KeyCode::Char('n') => self.next_match(1),
+            KeyCode::Char('N') => self.next_match(-1),
+            KeyCode::Char('u') => match self.doc.undo() {
+                true => self.info("Undid the last change"),
+                false => self.info("Nothing to undo"),
+            },
+            KeyCode::Char('s') => self.save(),
+            KeyCode::Char('q') => return self.quit(),
+            _ => {}
+        }
+
+        false
+    }
+
+    fn quit(&mut self) -> bool {
+        if !self.doc.is_modified() {
+            return true;
+        }
+
+        self.mode = Mode::Confirm(Confirm::Quit);
+        false
+    }
+
+    fn select_by(&mut self, offset: isize) {
+        let rows = self.rows();
+        let current = self.row_of(&rows, &self.selected) as isize;
+        let row = current.saturating_add(offset).clamp(0, rows.len() as isize - 1);
+        self.selected = rows[row as usize].path.clone();
+    }
+
+    /// Rows PageUp and PageDown move by.
+    fn page(&self) -> usize {
+        ratatui::crossterm::terminal::size().map_or(20, |(_, height)| height.saturating_sub(4).max(1) as usize)
+    }
+
+    /// Index of the row of `path`, or of its closest visible ancestor.
+    fn row_of(&self, rows: &[Row], path: &Path) -> usize {
+        let mut path = path.clone();
+        loop {
+            if let Some(row) = rows.iter().position(|row| row.path == path) {
+                return row;
+            }
+            match path.parent() {
+                Some(parent) => path = parent,
+                None => return 0,
+            }
+        }
+    }
+
+    fn expand(&mut self) {
+        if !self.is_container(&self.selected) {
+            return;
+        }
+        // Already open, step inside instead
+        if !self.expanded.insert(self.selected.clone()) {
+            self.select_by(1);
+        }
+    }
+
+    fn toggle(&mut self) {
+        if !self.expanded.remove(&self.selected) {
+            self.expanded.insert(self.selected.clone());
+        }
+    }
+
+    fn collapse(&mut self) {
+        if self.is_container(&self.selected) && self.expanded.remove(&self.selected) {
+            return;
+        }
+        if let Some(parent) = self.selected.parent() {
+            self.selected = parent;
+        }
+    }
+
+    fn is_container(&self, path: &Path) -> bool {
+        matches!(self.doc.get(path), Some(Tag::Compound(_) | Tag::List(_)))
+    }
+
+    fn start_edit(&mut self) {
+        match self.doc.get(&self.selected).and_then(value_text) {
+            Some(text) => {
+                self.mode = Mode::Prompt(Prompt::Edit(self.selected.clone()), Input::new(&text))
+            }
+            None => self.info("Lists and compounds are edited tag by tag"),
+        }
+    }
+
+    /// Adds into the selected compound or list, or after the selected tag.
+    fn start_add(&mut self) {
+        let (parent, index) = match self.doc.get(&self.selected) {
+            Some(Tag::Compound(map)) => (self.selected.clone(), map.len()),
+            Some(Tag::List(list)) => (self.selected.clone(), list.len()),
+            _ => {
+                let Some(parent) = self.selected.parent() else { return };
+                let index = self.doc.position(&self.selected).map_or(0, |index| index + 1);
+                (parent, index)
+            }
+        };
+
+        // Lists start from the type they hold
+        let text = match self.doc.get(&parent) {
+            Some(Tag::List(list)) => element_of(list.element_type()).map(|name| format!("{} ", name)),
+            _ => None,
+        };
+        let input = Input::new(&text.unwrap_or_default());
+        self.mode = Mode::Prompt(Prompt::Add { parent, index }, input);
+    }
+
+    fn start_rename(&mut self) {
+        match self.selected.last() {
+            Some(Step::Key(key)) => {
+                let input = Input::new(key);
+                self.mode = Mode::Prompt(Prompt::Rename(self.selected.clone()), input)
+            }
+            Some(Step::Index(_)) => self.info("List elements have no name"),
+            None => self.error(&RuntimeErr::RootTag("renamed")),
+        }
+    }
+
+    fn submit(&mut self, prompt: Prompt, text: &str) {
+        let res = match prompt {
+            Prompt::Edit(path) => self.doc.set_value(&path, text),
+            Prompt::Add { parent, index } => self.add(&parent, index, text),
+            Prompt::Rename(path) => self.rename(&path, text),
+            Prompt::Search => {
+                self.search = Some((text.to_string(), 0));
+                self.next_match(0);
+                Ok(())
+            }
+        };
+
+        if let Err(err) = res {
+            self.error(&err);
+        }
+    }
+
+    /// Adds the tag typed as `TYPE NAME [VALUE]` in compounds, `TYPE [VALUE]` in lists.
+    fn add(&mut self, parent: &Path, index: usize, text: &str) -> err::Result<()> {
+        let (type_name, rest) = text.trim_start().split_once(' ').unwrap_or((text.trim(), ""));
+        let tag_type = type_id_of(type_name.as_bytes()).ok_or_else(|| RuntimeErr::UnknownTag(type_name.to_string()))?;
+
+        let path = match self.doc.get(parent) {
+            Some(Tag::List(_)) => {
+                let tag = parse_tag(tag_type, rest)?;
+                let path = self.doc.insert_element(parent, index, tag)?;
+                self.remap(parent, |step| match step {
+                    Step::Index(i) if *i >= index => Some(Step::Index(i + 1)),
+                    step => Some(step.clone()),
+                });
+                path
+            }
+            _ => {
+                let (key, value) = rest.trim_start().split_once(' ').unwrap_or((rest.trim(), ""));
+                if key.is_empty() {
+                    return Err(RuntimeErr::MissingName);
+                }
+                let tag = parse_tag(tag_type, value)?;
+                self.doc.insert_entry(parent, index, key, tag)?
+            }
+        };
+
+        self.expanded.insert(parent.clone());
+        self.selected = path;
+        Ok(())
+    }
+
+    fn rename(&mut self, path: &Path, key: &str) -> err::Result<()> {
+        let new_path = self.doc.rename(path, key)?;
+        if let (Some(parent), Some(old)) = (path.parent(), path.last()) {
+            self.remap(&parent, |step| match step == old {
+                true => Some(Step::Key(key.into())),
+                false => Some(step.clone()),
+            });
+        }
+
+        self.selected = new_path;
+        Ok(())
+    }
+
+    fn delete(&mut self, path: &Path) {
+        let Some(parent) = path.parent() else { return };
+        let position = self.doc.position(path).unwrap_or_default();
+        if let Err(err) = self.doc.remove(path) {
+            return self.error(&err);
+        }
+
+        match path.last() {
+            Some(Step::Index(index)) => {
+                let index = *index;
+                self.remap(&parent, |step| match step {
+                    Step::Index(i) if *i == index => None,
+                    Step::Index(i) if *i > index => Some(Step::Index(i - 1)),
+                    step => Some(step.clone()),
+                })
+            }
+            old => self.remap(&parent, |step| (Some(step) != old).then(|| step.clone())),
+        }
+
+        // The tag that took its place, else the one before, else the parent
+        self.selected = match self.doc.get(&parent) {
+            Some(Tag::Compound(map)) if !map.is_empty() => {
+                let (key, _) = map.get_index(position.min(map.len() - 1)).expect("in bounds");
+                parent.join(key.as_ref())
+            }
+            Some(Tag::List(list)) if !list.is_empty() => parent.join(position.min(list.len() - 1)),
+            _ => parent,
+        };
+        self.info(&format!("Deleted {}", path));
+    }
+
+    /// Moves the selected tag up or down among its siblings.
+    fn move_by(&mut self, offset: isize) {
+        let (Some(parent), Some(from)) = (self.selected.parent(), self.doc.position(&self.selected)) else {
+            return;
+        };
+        let len = match self.doc.get(&parent) {
+            Some(Tag::Compound(map)) => map.len(),
+            Some(Tag::List(list)) => list.len(),
+            _ => return,
+        };
+        let Some(to) = from.checked_add_signed(offset).filter(|to| *to < len) else {
+            return;
+        };
+
+        match self.doc.move_to(&self.selected, to) {
+            Ok(path) => {
+                if matches!(self.selected.last(), Some(Step::Index(_))) {
+                    self.remap(&parent, |step| match step {
+                        Step::Index(i) if *i == from => Some(Step::Index(to)),
+                        Step::Index(i) if *i == to => Some(Step::Index(from)),
+                        step => Some(step.clone()),
+                    });
+                }
+                self.selected = path;
+            }
+            Err(err) => self.error(&err),
+        }
+    }
+
+    /// Rewrites the expanded paths below `parent` after its tags changed places.
+    /// `remap` gives the new step into `parent`, `None` if the tag is gone.
+    fn remap(&mut self, parent: &Path, remap: impl Fn(&Step) -> Option<Step>) {
+        let depth = parent.steps().len();
+        self.expanded = std::mem::take(&mut self.expanded)
+            .into_iter()
+            .filter_map(|path| {
+                if path.steps().len() <= depth || !path.starts_with(parent) {
+                    return Some(path);
+                }
+                let steps = path.steps();
+                let step = remap(&steps[depth])?;
+                Some(steps[..depth].iter().cloned().chain([step]).chain(steps[depth + 1..].iter().cloned()).collect())
+            })
+            .collect();
+    }
+
+    /// Selects the match `offset` away from the last one, opening what it is in.
+    fn next_match(&mut self, offset: isize) {
+        let Some((needle, current)) = self.search.take() else {
+            return self.info("Nothing searched yet, search with /");
+        };
+
+        let matches = self.doc.search(&needle);
+        if matches.is_empty() {
+            self.info(&format!("No tag matches '{}'", needle));
+            self.search = Some((needle, 0));
+            return;
+        }
+
+        let index = (current as isize + offset).rem_euclid(matches.len() as isize) as usize;
+        let path = matches[index].clone();
+        let mut ancestor = path.parent();
+        while let Some(path) = ancestor {
+            ancestor = path.parent();
+            self.expanded.insert(path);
+        }
+
+        self.info(&format!("Match {} of {} for '{}'", index + 1, matches.len(), needle));
+        self.selected = path;
+        self.search = Some((needle, index));
+    }
+
+    fn save(&mut self) {
+        match (self.save)(&self.doc.to_compound()) {
+            Ok(()) => {
+                self.doc.mark_saved();
+                self.info(&format!("Saved {}", self.file_name));
+            }
+            Err(err) => self.error(&err),
+        }
+    }
+
+    fn info(&mut self, text: &str) {
+        self.status = Some(Status::Info(text.to_string()));
+    }
+
+    fn error(&mut self, err: &RuntimeErr) {
+        self.status = Some(Status::Error(message(err)));
+    }
+
+    pub(crate) fn draw(&mut self, frame: &mut Frame) {
+        let [title_area, tree_area, status_area, hints_area] = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Min(1),
+            Constraint::Length(1),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+
+        let modified = if self.doc.is_modified() { " [modified]" } else { "" };
+        let title = format!(" {} ({}){}", self.file_name, format_name(self.format), modified);
+        let title = Line::from(title).style(Style::new().add_modifier(Modifier::REVERSED));
+        frame.render_widget(title, title_area);
+
+        let rows = self.rows();
+        let items = rows.iter().map(|row| ListItem::new(self.line(row))).collect::<Vec<_>>();
+        self.list.select(Some(self.row_of(&rows, &self.selected)));
+        let list = List::new(items).highlight_style(Style::new().add_modifier(Modifier::REVERSED));
+        frame.render_stateful_widget(list, tree_area, &mut self.list);
+
+        let status = match (&self.mode, &self.status) {
+            (Mode::Prompt(prompt, input), _) => {
+                let label = self.prompt_label(prompt);
+                let cursor = label.chars().count() + input.text[..input.cursor].chars().count();
+                let x = status_area.x.saturating_add(cursor as u16).min(status_area.right().saturating_sub(1));
+                frame.set_cursor_position(Position::new(x, status_area.y));
+                Line::from(vec![Span::styled(label, Style::new().fg(Color::Cyan)), Span::raw(input.text.as_str())])
+            }
+            (Mode::Confirm(Confirm::Delete(path)), _) => Line::from(format!("Delete {}? (y/n)", path)),
+            (Mode::Confirm(Confirm::Quit), _) => Line::from("Quit without saving? (y/n)"),
+            (Mode::Browse, Some(Status::Info(text))) => Line::from(text.as_str()),
+            (Mode::Browse, Some(Status::Error(text))) => Line::from(text.as_str()).style(Style::new().fg(Color::Red)),
+            (Mode::Browse, None) => Line::from(self.selected.to_string()).style(Style::new().add_modifier(Modifier::DIM)),
+        };
+        frame.render_widget(status, status_area);
+        frame.render_widget(Line::from(KEY_HINTS).style(Style::new().add_modifier(Modifier::DIM)), hints_area);
+    }
+
+    fn prompt_label(&self, prompt: &Prompt) -> String {
+        match prompt {
+            Prompt::Edit(path) => {
+                let type_name = self.doc.get(path).and_then(|tag| element_of(tag.tag_type()));
+                format!("{} ({}): ", path, type_name.unwrap_or_default())
+            }
+            Prompt::Add { parent, .. } => match self.doc.get(parent) {
+                Some(Tag::List(_)) => "Add TYPE [VALUE]: ".to_string(),
+                _ => "Add TYPE NAME [VALUE]: ".to_string(),
+            },
+            Prompt::Rename(path) => format!("Rename {} to: ", path),
+            Prompt::Search => "Search keys and values: ".to_string(),
+        }
+    }
+
+    /// Line of a row, shaped like the lines of `nbtc tree`.
+    fn line(&self, row: &Row) -> Line<'static> {
+        let Some(tag) = self.doc.get(&row.path) else {
+            return Line::default();
+        };
+
+        let fold = match tag {
+            Tag::Compound(_) | Tag::List(_) if self.expanded.contains(&row.path) => "▾ ",
+            Tag::Compound(_) | Tag::List(_) => "▸ ",
+            _ => "  ",
+        };
+        let mut spans = vec![
+            Span::raw(format!("{}{}", "  ".repeat(row.depth), fold)),
+            Span::styled(format!("{:<3} ", icon(tag.tag_type())), Style::new().add_modifier(Modifier::DIM)),
+        ];
+
+        match row.path.last() {
+            Some(Step::Key(key)) => {
+                spans.push(Span::styled(key.to_string(), Style::new().fg(Color::Blue).add_modifier(Modifier::BOLD)));
+                spans.push(Span::raw(": "));
+            }
+            Some(Step::Index(index)) => {
+                spans.push(Span::styled(format!("[{}]", index), Style::new().add_modifier(Modifier::DIM)));
+                spans.push(Span::raw(" "));
+            }
+            None => {
+                if let Some(name) = self.doc.name().filter(|name| !name.is_empty()) {
+                    spans.push(Span::styled(name.to_string(), Style::new().fg(Color::Blue).add_modifier(Modifier::BOLD)));
+                    spans.push(Span::raw(": "));
+                }
+            }
+        }
+
+        let color = match tag {
+            Tag::String(_) => Color::Green,
+            Tag::Compound(_) | Tag::List(_) => Color::Cyan,
+            _ => Color::Yellow,
+        };
+        spans.push(Span::styled(summary(tag), Style::new().fg(color)));
+
+        Line::from(spans)
+    }
+}
+
+fn format_name(format: DataFormat) -> &'static str {
+    match format {
+        DataFormat::Gzip => "gzip",
+        DataFormat::Zlib => "zlib",
+        _ => "uncompressed",
+    }
+}
+
+/// Text of an error for the status line, the lines telling where it was found are
+/// joined onto the first.
+fn message(err: &RuntimeErr) -> String {
+    err.to_string().lines().map(str::trim).collect::<Vec<_>>().join(", ")
+}
+
+/// Line of text being typed in, also by the shell.
+pub(crate) struct Input {
+    pub(crate) text: String,
+    /// Byte offset of the cursor
+    pub(crate) cursor: usize,
+}
+
+impl Input {
+    pub(crate) fn new(text: &str) -> Self {
+        Self {
+            text: text.to_string(),
+            cursor: text.len(),
+        }
+    }
+
+    pub(crate) fn handle(&mut self, key: KeyEvent) {
+        let prev = self.text[..self.cursor].char_indices().last().map(|(i, _)| i);
+        let next = self.text[self.cursor..].chars().next().map(|c| self.cursor + c.len_utf8());
+
+        match key.code {
+            KeyCode::Char(c) => {
+                self.text.insert(self.cursor, c);
+                self.cursor += c.len_utf8();
+            }
+            KeyCode::Backspace => {
+                if let Some(prev) = prev {
+                    self.text.remove(prev);
+                    self.cursor = prev;
+                }
+            }
+            KeyCode::Delete if next.is_some() => {
+                self.text.remove(self.cursor);
+            }
+            KeyCode::Left => self.cursor = prev.unwrap_or(self.cursor),
+            KeyCode::Right => self.cursor = next.unwrap_or(self.cursor),
+            KeyCode::Home => self.cursor = 0,
+            KeyCode::End => self.cursor = self.text.len(),
+            _ => {}
+        }
+    }
+}
+
+/// Runs the editor until it is closed, the terminal is given back even on errors.
+pub(crate) fn run(mut editor: Editor) -> err::Result<()> {
+    let mut terminal = ratatui::try_init()?;
+
+    let res = loop {
+        if let Err(err) = terminal.draw(|frame| editor.draw(frame)) {
+            break Err(err.into());
+        }
+        match event::read() {
+            Ok(Event::Key(key)) if key.kind == KeyEventKind::Press => {
+                if editor.handle(key) {
+                    break Ok(());
+                }
+            }
+            Ok(_) => {}
+            Err(err) => break Err(err.into()),
+        }
+    };
+
+    ratatui::restore();
+    res
+}
@@ -1,14 +1,21 @@
+use std::collections::HashMap;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+
 use crate::err::RuntimeErr;
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Default)]
 #[repr(u8)]
+#[allow(clippy::upper_case_acronyms)]
 pub enum DataFormat {
     Gzip = 1,
     Zlib = 2,
     NBT = 3,
     LZ4 = 4,
     Custom = 127,
-    Unknown
+    #[default]
+    Unknown,
 }
 
 impl DataFormat {
@@ -30,10 +37,7 @@ impl DataFormat {
 
     #[inline]
     pub fn is_default(&self) -> bool {
-        match self {
-            Self::Unknown => true,
-            _ => false,
-        }
+        matches!(self, Self::Unknown)
     }
 }
 
@@ -51,8 +55,107 @@ impl TryFrom<u8> for DataFormat {
     }
 }
 
-impl Default for DataFormat {
-    fn default() -> Self {
-        Self::Unknown
+/// How often [`Watcher::poll`] is meant to be called.
+pub const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// How long a changed file has to stay the same before it is handed out. Editors and
+/// copies write files in several steps, this waits until they are done.
+pub const DEBOUNCE: Duration = Duration::from_millis(300);
+
+/// When a file was last written and how long it was then.
+type Stamp = (SystemTime, u64);
+
+/// Finds files that changed by polling when they were modified, since the standard library
+/// has no file change notifications.
+///
+/// Watches a single file, or every file with one of `extensions` anywhere in a directory.
+/// Files that exist when watching starts count as changed.
+pub struct Watcher {
+    root: PathBuf,
+    extensions: &'static [&'static str],
+    /// Stamps of the files last handed out
+    seen: HashMap<PathBuf, Stamp>,
+    /// Changed files, along with since when they have stayed the same
+    pending: HashMap<PathBuf, (Stamp, Instant)>,
+}
+
+impl Watcher {
+    pub fn new(root: &Path, extensions: &'static [&'static str]) -> Self {
+        Self {
+            root: root.to_path_buf(),
+            extensions,
+            seen: HashMap::new(),
+            pending: HashMap::new(),
+        }
+    }
+
+    /// Files which changed and then stayed the same for [`DEBOUNCE`] until `now`, sorted.
+    pub fn poll(&mut self, now: Instant) -> io::Result<Vec<PathBuf>> {
+        let mut stamps = HashMap::new();
+        if self.root.is_dir() {
+            self.scan(&self.root, &mut stamps)?;
+        } else if let Some(stamp) = stamp_of(&self.root)? {
+            stamps.insert(self.root.clone(), stamp);
+        }
+
+        // Removed files are forgotten, so they count as changed when they come back
+        self.seen.retain(|path, _| stamps.contains_key(path));
+        self.pending.retain(|path, _| stamps.contains_key(path));
+
+        let mut ready = Vec::new();
+        for (path, stamp) in stamps {
+            if self.seen.get(&path) == Some(&stamp) {
+                self.pending.remove(&path);
+                continue;
+            }
+
+            match self.pending.get(&path) {
+                Some((pending, since)) if *pending == stamp => {
+                    if now.saturating_duration_since(*since) >= DEBOUNCE {
+                        self.pending.remove(&path);
+                        self.seen.insert(path.clone(), stamp);
+                        ready.push(path);
+                    }
+                }
+                _ => {
+                    self.pending.insert(path, (stamp, now));
+                }
+            }
+        }
+
+        ready.sort();
+        Ok(ready)
+    }
+
+    fn scan(&self, dir: &Path, stamps: &mut HashMap<PathBuf, Stamp>) -> io::Result<()> {
+        for entry in std::fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                self.scan(&path, stamps)?;
+                continue;
+            }
+
+            let watched = path
+                .extension()
+                .and_then(|ext| ext.to_str())
+                .is_some_and(|ext| self.extensions.contains(&ext));
+            if watched {
+                if let Some(stamp) = stamp_of(&path)? {
+                    stamps.insert(path, stamp);
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Stamp of the file at `path`, `None` when there is none.
+fn stamp_of(path: &Path) -> io::Result<Option<Stamp>> {
+    match std::fs::metadata(path) {
+        Ok(meta) if meta.is_file() => Ok(Some((meta.modified()?, meta.len()))),
+        Ok(_) => Ok(None),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(err),
     }
 }
@@ -1,20 +1,41 @@
 pub mod read;
+pub mod validate;
 pub mod write;
 
+mod base64;
+mod position;
+
+pub use nbt::float::FloatFormat;
+
+/// XML Schema of the dialect [`write::print_xml`] writes and [`read::read_xml`] reads.
+pub const XSD: &str = include_str!("xml/nbt.xsd");
+
+/// Options for writing XML with [`write::print_xml`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct XmlOptions {
+    pub float_format: FloatFormat,
+    /// Write arrays as text instead of one element per value: numbers separated by
+    /// spaces, or base64 for byte arrays.
+    pub compact_arrays: bool,
+}
+
 mod consts {
-    pub(super) const TAG_BYTE: &'static str = "byte";
-    pub(super) const TAG_SHORT: &'static str = "short";
-    pub(super) const TAG_INT: &'static str = "int";
-    pub(super) const TAG_LONG: &'static str = "long";
-    pub(super) const TAG_FLOAT: &'static str = "float";
-    pub(super) const TAG_DOUBLE: &'static str = "double";
-    pub(super) const TAG_STRING: &'static str = "string";
-    pub(super) const TAG_BYTE_ARR: (&'static str, &'static str) = ("byte_array", TAG_BYTE);
-    pub(super) const TAG_INT_ARR: (&'static str, &'static str) = ("int_array", TAG_INT);
-    pub(super) const TAG_LONG_ARR: (&'static str, &'static str) = ("long_array", TAG_LONG);
-    pub(super) const TAG_LIST: &'static str = "list";
-    pub(super) const TAG_COMPOUND: &'static str = "compound";
-    pub(super) const TAG_NAME_ATTR: &'static str = "name";
+    pub(super) const TAG_BYTE: &str = "byte";
+    pub(super) const TAG_SHORT: &str = "short";
+    pub(super) const TAG_INT: &str = "int";
+    pub(super) const TAG_LONG: &str = "long";
+    pub(super) const TAG_FLOAT: &str = "float";
+    pub(super) const TAG_DOUBLE: &str = "double";
+    pub(super) const TAG_STRING: &str = "string";
+    pub(super) const TAG_BYTE_ARR: (&str, &str) = ("byte_array", TAG_BYTE);
+    pub(super) const TAG_INT_ARR: (&str, &str) = ("int_array", TAG_INT);
+    pub(super) const TAG_LONG_ARR: (&str, &str) = ("long_array", TAG_LONG);
+    pub(super) const TAG_LIST: &str = "list";
+    pub(super) const TAG_COMPOUND: &str = "compound";
+    pub(super) const TAG_NAME_ATTR: &str = "name";
+    pub(super) const TAG_ENCODING_ATTR: &str = "encoding";
+    pub(super) const TAG_TYPE_ATTR: &str = "type";
+    pub(super) const ENCODING_BASE64: &str = "base64";
 
     pub(super) const TAG_BYTE_B: &[u8] = b"byte";
     pub(super) const TAG_SHORT_B: &[u8] = b"short";
@@ -30,6 +51,35 @@ mod consts {
     pub(super) const TAG_LONG_ARR_B: &[u8] = b"long_array";
 }
 
+/// Element name of the tag type id, `None` for `TAG_End`.
+pub fn element_of(type_id: u8) -> Option<&'static str> {
+    use self::consts::*;
+
+    let element = match type_id {
+        nbt::consts::TAG_BYTE => TAG_BYTE,
+        nbt::consts::TAG_SHORT => TAG_SHORT,
+        nbt::consts::TAG_INT => TAG_INT,
+        nbt::consts::TAG_LONG => TAG_LONG,
+        nbt::consts::TAG_FLOAT => TAG_FLOAT,
+        nbt::consts::TAG_DOUBLE => TAG_DOUBLE,
+        nbt::consts::TAG_BYTE_ARRAY => TAG_BYTE_ARR.0,
+        nbt::consts::TAG_STRING => TAG_STRING,
+        nbt::consts::TAG_LIST => TAG_LIST,
+        nbt::consts::TAG_COMPOUND => TAG_COMPOUND,
+        nbt::consts::TAG_INT_ARRAY => TAG_INT_ARR.0,
+        nbt::consts::TAG_LONG_ARRAY => TAG_LONG_ARR.0,
+        _ => return None,
+    };
+
+    Some(element)
+}
+
+/// Tag type id of the element name, the reverse of [`element_of`].
+pub fn type_id_of(element: &[u8]) -> Option<u8> {
+    (nbt::consts::TAG_BYTE..=nbt::consts::TAG_LONG_ARRAY)
+        .find(|id| element_of(*id).map(str::as_bytes) == Some(element))
+}
+
 trait TagExtras {
     fn type_name(&self) -> &'static str;
 }
@@ -0,0 +1,65 @@
+//! Standard base64 with padding, for compact byte arrays.
+
+const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+const PAD: u8 = b'=';
+
+/// Encodes `bytes` into `out`, padding the last group.
+///
+/// Only the last call for a document may take a length that isn't a multiple of 3.
+pub(super) fn encode(bytes: &[u8], out: &mut String) {
+    out.reserve(bytes.len().div_ceil(3) * 4);
+
+    for group in bytes.chunks(3) {
+        let b = [group[0], *group.get(1).unwrap_or(&0), *group.get(2).unwrap_or(&0)];
+        let n = (b[0] as u32) << 16 | (b[1] as u32) << 8 | b[2] as u32;
+
+        for i in 0..4 {
+            let c = match i <= group.len() {
+                true => ALPHABET[(n >> (18 - 6 * i) & 0x3f) as usize],
+                false => PAD,
+            };
+            out.push(c as char);
+        }
+    }
+}
+
+/// Decodes `text`, skipping whitespace. Returns `None` on malformed input.
+pub(super) fn decode(text: &str) -> Option<Vec<u8>> {
+    let digits: Vec<u8> = text.bytes().filter(|b| !b.is_ascii_whitespace()).collect();
+    if !digits.len().is_multiple_of(4) {
+        return None;
+    }
+
+    let mut out = Vec::with_capacity(digits.len() / 4 * 3);
+    let groups = digits.len() / 4;
+    for (i, group) in digits.chunks(4).enumerate() {
+        let pad = group.iter().rev().take_while(|b| **b == PAD).count();
+        if pad > 2 || (pad > 0 && i + 1 != groups) {
+            return None;
+        }
+
+        let mut n = 0u32;
+        for b in &group[..4 - pad] {
+            n = n << 6 | value(*b)? as u32;
+        }
+        n <<= 6 * pad as u32;
+
+        out.extend_from_slice(&[(n >> 16) as u8, (n >> 8) as u8, n as u8][..3 - pad]);
+    }
+
+    Some(out)
+}
+
+#[inline]
+fn value(digit: u8) -> Option<u8> {
+    let value = match digit {
+        b'A'..=b'Z' => digit - b'A',
+        b'a'..=b'z' => digit - b'a' + 26,
+        b'0'..=b'9' => digit - b'0' + 52,
+        b'+' => 62,
+        b'/' => 63,
+        _ => return None,
+    };
+
+    Some(value)
+}
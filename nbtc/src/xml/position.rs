@@ -0,0 +1,102 @@
+//! Line and column numbers for errors in XML documents.
+
+use std::collections::VecDeque;
+use std::io::{self, BufRead, Read};
+
+use quick_xml::events::Event;
+
+/// Byte offset an event starts at, given the offset right past it.
+///
+/// Text is trimmed, so its start isn't known.
+pub(super) fn event_start(event: &Event, end: usize) -> Option<usize> {
+    let markup = match event {
+        Event::Start(ev) => ev.len() + 2,
+        Event::Empty(ev) => ev.len() + 3,
+        Event::End(ev) => ev.len() + 3,
+        Event::Comment(ev) => ev.len() + 7,
+        Event::CData(ev) => ev.len() + 12,
+        Event::Decl(ev) => ev.len() + 4,
+        Event::PI(ev) => ev.len() + 4,
+        Event::DocType(ev) => ev.len() + 10,
+        Event::Text(_) | Event::Eof => return None,
+    };
+
+    end.checked_sub(markup)
+}
+
+/// Number of line starts [`LineReader`] remembers.
+const LINE_HISTORY: usize = 64;
+
+/// Reader keeping track of where the last few lines start, to turn byte offsets near
+/// the read position into line and column numbers.
+pub(super) struct LineReader<B> {
+    inner: B,
+    consumed: usize,
+    /// Lines started so far, not counting the first one.
+    lines: usize,
+    /// Offsets of the most recent line starts, oldest first.
+    starts: VecDeque<usize>,
+}
+
+impl<B: BufRead> LineReader<B> {
+    pub(super) fn new(inner: B) -> Self {
+        Self {
+            inner,
+            consumed: 0,
+            lines: 0,
+            starts: VecDeque::with_capacity(LINE_HISTORY),
+        }
+    }
+}
+
+impl<B> LineReader<B> {
+    /// One-based line and column of `offset`, which has to be within the last
+    /// [`LINE_HISTORY`] lines.
+    pub(super) fn line_column(&self, offset: usize) -> (usize, usize) {
+        let later = self.starts.iter().rev().take_while(|start| **start > offset).count();
+        let line_start = self
+            .starts
+            .iter()
+            .rev()
+            .nth(later)
+            .copied()
+            .unwrap_or(0);
+
+        (self.lines - later + 1, offset - line_start + 1)
+    }
+}
+
+impl<B: BufRead> Read for LineReader<B> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = {
+            let mut available = self.fill_buf()?;
+            available.read(buf)?
+        };
+        self.consume(read);
+        Ok(read)
+    }
+}
+
+impl<B: BufRead> BufRead for LineReader<B> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        self.inner.fill_buf()
+    }
+
+    fn consume(&mut self, amt: usize) {
+        // The bytes handed out by the last `fill_buf` are still buffered
+        if let Ok(buf) = self.inner.fill_buf() {
+            for (i, byte) in buf.iter().take(amt).enumerate() {
+                if *byte == b'\n' {
+                    if self.starts.len() == LINE_HISTORY {
+                        self.starts.pop_front();
+                    }
+                    self.starts.push_back(self.consumed + i + 1);
+                    self.lines += 1;
+                }
+            }
+        }
+
+        self.consumed += amt;
+        self.inner.consume(amt);
+    }
+}
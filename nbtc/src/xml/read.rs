@@ -1,90 +1,200 @@
-use std::collections::VecDeque;
 use std::io::{BufReader, Read};
-use std::str::from_utf8;
+use std::num::ParseIntError;
+use std::str::{from_utf8, FromStr};
 
-use nbt::{CompoundTag, Tag};
-use quick_xml::events::{BytesStart, BytesText, Event};
+use nbt::err::ListTagError;
+use nbt::float::parse_float;
+use nbt::{CompoundTag, ListTag, Tag};
+use quick_xml::events::{BytesStart, Event};
 use quick_xml::Reader;
 
-use super::{consts::*, TagExtras};
+use super::base64;
+use super::position::{event_start, LineReader};
+use super::{consts::*, element_of, type_id_of, TagExtras};
 use crate::err::{self, RuntimeErr};
 
+/// Reads an NBT compound from XML.
+///
+/// XML declarations, comments and processing instructions are skipped. CDATA sections
+/// are read as text, so they can hold strings without escaping. Floats and doubles may
+/// be given in any form [`FloatFormat`](super::FloatFormat) writes, arrays also in the
+/// compact form of [`XmlOptions`](super::XmlOptions).
+///
+/// # Errors
+///
+/// Errors about the document are wrapped in [`RuntimeErr::XmlContext`], giving the line
+/// and column they were found at along with the path of the enclosing tag.
 pub fn read_xml<F: Read>(reader: &mut BufReader<F>) -> err::Result<CompoundTag> {
-    let mut reader = Reader::from_reader(reader);
-    reader.trim_text(true);
+    // Text isn't trimmed, strings keep their leading and trailing whitespace
+    let mut reader = Reader::from_reader(LineReader::new(reader));
 
     let mut buf = Vec::new();
-    let mut el_buf: VecDeque<NbtElement> = VecDeque::with_capacity(10);
-    let mut head = 0usize;
+    let mut el_buf: Vec<NbtElement> = Vec::with_capacity(10);
 
     loop {
-        match reader.read_event_into(&mut buf)? {
-            Event::Eof => break,
-            Event::Start(ev) => {
-                let el = process_event(&ev);
-                el_buf.push_back(el);
-            }
-            Event::End(_) => {
-                if el_buf.len() < 2 {
-                    break;
-                }
+        let before = reader.buffer_position();
+        let event = reader.read_event_into(&mut buf);
+        let start = match &event {
+            Ok(event) => event_start(event, reader.buffer_position()).unwrap_or(before),
+            Err(_) => reader.buffer_position(),
+        };
 
-                let temp = el_buf.pop_back().unwrap();
-                head = el_buf.len() - 1;
-                add_to_parent(&mut el_buf[head], temp)?;
-            }
-            Event::Text(text) => parse_text(text, &mut el_buf[head])?,
-            _ => unimplemented!(),
+        let done = event
+            .map_err(RuntimeErr::from)
+            .and_then(|event| process_event(event, &mut el_buf))
+            .map_err(|err| locate(err, reader.get_ref(), start, &el_buf))?;
+        if done {
+            break;
         }
 
-        head = el_buf.len() - 1;
         buf.clear();
     }
 
-    match el_buf.pop_front().unwrap().tag {
-        Tag::Compound(ctag) => Ok(CompoundTag::with(ctag)),
+    match el_buf.pop().map(|el| el.tag) {
+        Some(Tag::Compound(ctag)) => Ok(CompoundTag::with(ctag)),
         _ => Err(RuntimeErr::NBTDecode(
             nbt::err::TagDecodeError::RootMustBeCompound,
         )),
     }
 }
 
-#[inline]
-fn parse_text(text: BytesText, el: &mut NbtElement) -> Result<(), RuntimeErr> {
-    let inner = from_utf8(&text)?;
+/// Applies a single XML event, returns `true` once the root element is closed.
+fn process_event(event: Event, el_buf: &mut Vec<NbtElement>) -> err::Result<bool> {
+    match event {
+        Event::Start(ev) => {
+            open_element(&ev, el_buf)?;
+            Ok(false)
+        }
+        Event::Empty(ev) => {
+            open_element(&ev, el_buf)?;
+            close_element(el_buf)
+        }
+        Event::End(_) => close_element(el_buf),
+        Event::Text(text) => {
+            let text = text.unescape()?;
+            match el_buf.last_mut() {
+                Some(el) => parse_text(&text, el).map(|_| false),
+                // Line breaks around the root element
+                None if is_blank(&text) => Ok(false),
+                None => Err(RuntimeErr::UnexpectedText),
+            }
+        }
+        // CDATA holds text that isn't escaped, e.g. strings full of `<` and `&`
+        Event::CData(data) => match el_buf.last_mut() {
+            Some(el) => parse_text(from_utf8(&data)?, el).map(|_| false),
+            None => Err(RuntimeErr::UnexpectedText),
+        },
+        Event::Eof => Err(RuntimeErr::UnexpectedEof),
+        Event::Decl(_) if !el_buf.is_empty() => {
+            Err(RuntimeErr::UnsupportedXml("XML declaration inside the root element"))
+        }
+        // Notes for whoever edits the file, nothing to compile
+        Event::Decl(_) | Event::Comment(_) | Event::PI(_) => Ok(false),
+        Event::DocType(_) => Err(RuntimeErr::UnsupportedXml("DOCTYPE")),
+    }
+}
+
+fn open_element(ev: &BytesStart, el_buf: &mut Vec<NbtElement>) -> err::Result<()> {
+    let el = new_element(ev)?;
 
+    match el_buf.last() {
+        None if !matches!(el.tag, Tag::Compound(_)) => {
+            return Err(RuntimeErr::NBTDecode(
+                nbt::err::TagDecodeError::RootMustBeCompound,
+            ));
+        }
+        Some(NbtElement {
+            tag: Tag::Compound(_),
+            ..
+        }) if el.name.is_none() => return Err(RuntimeErr::MissingName),
+        _ => {}
+    }
+
+    el_buf.push(el);
+    Ok(())
+}
+
+fn close_element(el_buf: &mut Vec<NbtElement>) -> err::Result<bool> {
+    if el_buf.len() < 2 {
+        return Ok(true);
+    }
+
+    let temp = el_buf.pop().unwrap();
+    let head = el_buf.len() - 1;
+    add_to_parent(&mut el_buf[head], temp)?;
+
+    Ok(false)
+}
+
+#[inline]
+fn parse_text(inner: &str, el: &mut NbtElement) -> Result<(), RuntimeErr> {
     match &mut el.tag {
-        Tag::String(text) => *text = inner.into(),
-        Tag::Byte(byte) => *byte = inner.parse()?,
-        Tag::Short(short) => *short = inner.parse()?,
-        Tag::Int(int) => *int = inner.parse()?,
-        Tag::Long(long) => *long = inner.parse()?,
-        Tag::Float(float) => *float = inner.parse()?,
-        Tag::Double(double) => *double = inner.parse()?,
-        _ => unreachable!(),
+        // Compact arrays, see `XmlOptions::compact_arrays`
+        Tag::ByteArray(array) if el.base64 => {
+            let bytes = base64::decode(inner).ok_or(RuntimeErr::InvalidBase64)?;
+            array.extend(bytes.into_iter().map(|b| b as i8));
+        }
+        Tag::ByteArray(array) => parse_numbers(inner, array)?,
+        Tag::IntArray(array) => parse_numbers(inner, array)?,
+        Tag::LongArray(array) => parse_numbers(inner, array)?,
+        // Text and CDATA sections following each other make up a single string
+        Tag::String(text) if text.is_empty() => *text = inner.into(),
+        Tag::String(text) => *text = [text.as_ref(), inner].concat().into(),
+        // Indentation between elements, or around comments in a number
+        _ if is_blank(inner) => {}
+        Tag::Byte(byte) => *byte = inner.trim().parse()?,
+        Tag::Short(short) => *short = inner.trim().parse()?,
+        Tag::Int(int) => *int = inner.trim().parse()?,
+        Tag::Long(long) => *long = inner.trim().parse()?,
+        Tag::Float(float) => *float = parse_float(inner.trim())?,
+        Tag::Double(double) => *double = parse_float(inner.trim())?,
+        _ => return Err(RuntimeErr::UnexpectedText),
     };
 
     Ok(())
 }
 
-fn process_event(ev: &BytesStart) -> NbtElement {
-    let name: Option<Box<str>> = ev
-        .attributes()
-        .filter(|at| {
-            let at = match at {
-                Ok(at) => at,
-                Err(_) => return false,
-            };
-
-            if at.key.as_ref() == TAG_NAME_ATTR.as_bytes() {
-                return true;
-            }
+/// Whether `text` is only whitespace, like the indentation between elements.
+#[inline]
+pub(super) fn is_blank(text: &str) -> bool {
+    text.bytes().all(|b| b.is_ascii_whitespace())
+}
+
+#[inline]
+fn parse_numbers<T: FromStr<Err = ParseIntError>>(text: &str, array: &mut Vec<T>) -> err::Result<()> {
+    for number in text.split_ascii_whitespace() {
+        array.push(number.parse()?);
+    }
 
-            false
-        })
-        .take(1)
-        .map(|at| from_utf8(at.unwrap().value.as_ref()).unwrap().into())
-        .next();
+    Ok(())
+}
+
+fn new_element(ev: &BytesStart) -> err::Result<NbtElement> {
+    let mut name: Option<Box<str>> = None;
+    let mut base64 = false;
+    let mut list_type = None;
+    for at in ev.attributes() {
+        let at = at.map_err(quick_xml::Error::from)?;
+        match at.key.as_ref() {
+            key if key == TAG_NAME_ATTR.as_bytes() => {
+                name = Some(at.unescape_value()?.into());
+            }
+            key if key == TAG_ENCODING_ATTR.as_bytes() => {
+                if at.value.as_ref() != ENCODING_BASE64.as_bytes() {
+                    return Err(RuntimeErr::UnsupportedXml("array encoding other than base64"));
+                }
+                base64 = true;
+            }
+            key if key == TAG_TYPE_ATTR.as_bytes() => match type_id_of(at.value.as_ref()) {
+                Some(id) => list_type = Some(id),
+                None => {
+                    let value = String::from_utf8_lossy(at.value.as_ref()).into_owned();
+                    return Err(RuntimeErr::UnknownListType(value));
+                }
+            },
+            _ => {}
+        }
+    }
 
     let tag = match ev.name().as_ref() {
         TAG_BYTE_B => Tag::Byte(0),
@@ -94,15 +204,23 @@ fn process_event(ev: &BytesStart) -> NbtElement {
         TAG_FLOAT_B => Tag::Float(0.0),
         TAG_DOUBLE_B => Tag::Double(0.0),
         TAG_STRING_B => Tag::String("".into()),
-        TAG_LIST_B => Tag::List([].into()),
+        TAG_LIST_B => Tag::List(match list_type {
+            // Ids from `type_id_of` are always valid list element types
+            Some(id) => ListTag::with_type(id).unwrap(),
+            None => ListTag::new(),
+        }),
         TAG_COMPOUND_B => Tag::Compound(Default::default()),
         TAG_BYTE_ARR_B => Tag::ByteArray([].into()),
         TAG_INT_ARR_B => Tag::IntArray([].into()),
         TAG_LONG_ARR_B => Tag::LongArray([].into()),
-        _ => unreachable!(),
+        other => {
+            return Err(RuntimeErr::UnknownElement(
+                String::from_utf8_lossy(other).into_owned(),
+            ))
+        }
     };
 
-    NbtElement { tag, name }
+    Ok(NbtElement { tag, name, base64 })
 }
 
 #[inline]
@@ -111,9 +229,17 @@ fn add_to_parent(parent: &mut NbtElement, el: NbtElement) -> err::Result<()> {
 
     match (&mut parent.tag, el.tag) {
         (Tag::Compound(ctag), tag) => {
-            ctag.insert(el.name.unwrap(), tag);
+            ctag.insert(el.name.ok_or(RuntimeErr::MissingName)?, tag);
         }
-        (Tag::List(ltag), tag) => ltag.push(tag),
+        (Tag::List(ltag), tag) => ltag.push(tag).map_err(|err| match err {
+            ListTagError::TypeMismatch { expected, .. } => RuntimeErr::MixedList {
+                expected: element_of(expected).unwrap_or_default(),
+                found: current_tag_tname,
+            },
+            ListTagError::UnknownTagType(_) => RuntimeErr::XmlError(
+                quick_xml::Error::UnexpectedToken(format!("<{}>", current_tag_tname)),
+            ),
+        })?,
         (Tag::ByteArray(array), Tag::Byte(val)) => array.push(val),
         (Tag::IntArray(array), Tag::Int(val)) => array.push(val),
         (Tag::LongArray(array), Tag::Long(val)) => array.push(val),
@@ -127,8 +253,35 @@ fn add_to_parent(parent: &mut NbtElement, el: NbtElement) -> err::Result<()> {
     Ok(())
 }
 
+/// Wraps `err` with the line and column of `offset` and the path of the open tags.
+fn locate<B>(err: RuntimeErr, lines: &LineReader<B>, offset: usize, el_buf: &[NbtElement]) -> RuntimeErr {
+    let mut path = String::new();
+    for pair in el_buf.windows(2) {
+        match (&pair[0].tag, pair[1].name.as_deref()) {
+            (Tag::Compound(_), name) => {
+                if !path.is_empty() {
+                    path.push('.');
+                }
+                path.push_str(name.unwrap_or_default());
+            }
+            (Tag::List(ltag), _) => path.push_str(&format!("[{}]", ltag.len())),
+            _ => {}
+        }
+    }
+
+    let (line, column) = lines.line_column(offset);
+    RuntimeErr::XmlContext {
+        line,
+        column,
+        path,
+        source: Box::new(err),
+    }
+}
+
 #[derive(Debug, Clone)]
 struct NbtElement {
     tag: Tag,
     name: Option<Box<str>>,
+    /// Text of the byte array is base64
+    base64: bool,
 }
@@ -0,0 +1,428 @@
+//! Checks documents against the rules of [`XSD`](super::XSD) before they are compiled.
+
+use std::io::BufRead;
+use std::str::from_utf8;
+
+use nbt::float::parse_float;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+
+use super::base64;
+use super::consts::*;
+use super::position::{event_start, LineReader};
+use super::read::is_blank;
+use super::{element_of, type_id_of};
+use crate::err::{self, RuntimeErr};
+
+/// Checks that `reader` holds a document nbtc can compile.
+///
+/// Unlike [`read_xml`](super::read::read_xml), validation carries on past the first
+/// problem: unknown elements and attributes, compound entries without a `name`, names
+/// on list elements, lists mixing element types, misplaced elements or text and values
+/// out of range. Arrays may be written in the compact form.
+///
+/// # Errors
+///
+/// Returns [`RuntimeErr::InvalidXml`] holding every problem found, each wrapped in
+/// [`RuntimeErr::XmlContext`]. Malformed XML ends validation early.
+pub fn validate_xml<B: BufRead>(reader: B) -> err::Result<()> {
+    // Like `read_xml`, text isn't trimmed so strings keep their whitespace
+    let mut reader = Reader::from_reader(LineReader::new(reader));
+
+    let mut validator = Validator::default();
+    let mut buf = Vec::new();
+
+    loop {
+        let before = reader.buffer_position();
+        let event = reader.read_event_into(&mut buf);
+        let start = match &event {
+            Ok(event) => event_start(event, reader.buffer_position()).unwrap_or(before),
+            Err(_) => reader.buffer_position(),
+        };
+
+        let done = match event {
+            Ok(event) => validator.event(event),
+            Err(err) => {
+                validator.report(err.into());
+                true
+            }
+        };
+
+        let lines = reader.get_ref();
+        validator.locate_new(|| lines.line_column(start));
+        if done {
+            break;
+        }
+
+        buf.clear();
+    }
+
+    match validator.problems.is_empty() {
+        true => Ok(()),
+        false => Err(RuntimeErr::InvalidXml(validator.problems)),
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Kind {
+    Compound,
+    /// Element type of the list, once known
+    List(Option<&'static str>),
+    /// Element type held by the array, `base64` for compact byte arrays
+    Array { item: &'static str, base64: bool },
+    Scalar { tag: &'static str, has_value: bool },
+    /// Unknown element, nothing inside it is checked
+    Unknown,
+}
+
+#[derive(Debug)]
+struct Frame {
+    kind: Kind,
+    /// Key or `[index]` of the element inside its parent
+    segment: String,
+    children: usize,
+}
+
+#[derive(Debug, Default)]
+struct Validator {
+    stack: Vec<Frame>,
+    /// Problems found so far, the ones at the end may not be located yet
+    problems: Vec<RuntimeErr>,
+    /// Number of problems wrapped in [`RuntimeErr::XmlContext`]
+    located: usize,
+    /// Paths of the problems not located yet
+    paths: Vec<String>,
+    root_seen: bool,
+}
+
+impl Validator {
+    /// Checks a single event, returns `true` once validation is over.
+    fn event(&mut self, event: Event) -> bool {
+        match event {
+            Event::Start(ev) => self.open(&ev),
+            Event::Empty(ev) => {
+                self.open(&ev);
+                self.close();
+            }
+            Event::End(_) => self.close(),
+            Event::Text(text) => match text.unescape() {
+                Ok(text) => self.text(&text),
+                Err(err) => self.report(err.into()),
+            },
+            Event::CData(data) => match from_utf8(&data) {
+                Ok(text) => self.text(text),
+                Err(err) => self.report(err.into()),
+            },
+            Event::Eof => {
+                self.report(RuntimeErr::UnexpectedEof);
+                return true;
+            }
+            Event::Decl(_) if !self.stack.is_empty() => self.report(RuntimeErr::UnsupportedXml(
+                "XML declaration inside the root element",
+            )),
+            Event::Decl(_) | Event::Comment(_) | Event::PI(_) => {}
+            Event::DocType(_) => self.report(RuntimeErr::UnsupportedXml("DOCTYPE")),
+        }
+
+        self.root_seen && self.stack.is_empty()
+    }
+
+    fn open(&mut self, ev: &BytesStart) {
+        let parent = self.stack.last().map(|frame| frame.kind);
+        if let Some(Kind::Unknown) = parent {
+            self.push(Kind::Unknown, String::new());
+            return;
+        }
+
+        let tag = tag_type(ev.name().as_ref());
+        let (name, base64, list_type) = self.attributes(ev, tag);
+
+        let tag = match tag {
+            Some(tag) => tag,
+            None => {
+                let element = String::from_utf8_lossy(ev.name().as_ref()).into_owned();
+                self.report(RuntimeErr::UnknownElement(element));
+                self.push(Kind::Unknown, String::new());
+                return;
+            }
+        };
+
+        let mut problem = None;
+        let segment = match parent {
+            None => {
+                self.root_seen = true;
+                if tag != TAG_COMPOUND {
+                    problem = Some(RuntimeErr::NBTDecode(
+                        nbt::err::TagDecodeError::RootMustBeCompound,
+                    ));
+                }
+                String::new()
+            }
+            Some(Kind::Compound) => {
+                if name.is_none() {
+                    problem = Some(RuntimeErr::MissingName);
+                }
+                name.unwrap_or_default()
+            }
+            Some(Kind::List(expected)) => {
+                match expected {
+                    Some(expected) if expected != tag => {
+                        problem = Some(RuntimeErr::MixedList {
+                            expected,
+                            found: tag,
+                        });
+                    }
+                    _ if name.is_some() => problem = Some(RuntimeErr::UnexpectedName),
+                    _ => {}
+                }
+                self.index_segment()
+            }
+            Some(Kind::Array { item, .. }) => {
+                if item != tag {
+                    problem = Some(RuntimeErr::UnexpectedElement {
+                        parent: array_type(item),
+                        child: tag,
+                    });
+                } else if name.is_some() {
+                    problem = Some(RuntimeErr::UnexpectedName);
+                }
+                self.index_segment()
+            }
+            Some(Kind::Scalar { tag: parent, .. }) => {
+                problem = Some(RuntimeErr::UnexpectedElement {
+                    parent,
+                    child: tag,
+                });
+                String::new()
+            }
+            Some(Kind::Unknown) => unreachable!("handled above"),
+        };
+
+        if let Some(problem) = problem {
+            self.report(problem);
+        }
+
+        if let Some(Frame {
+            kind: Kind::List(expected @ None),
+            ..
+        }) = self.stack.last_mut()
+        {
+            *expected = Some(tag);
+        }
+        if let Some(parent) = self.stack.last_mut() {
+            parent.children += 1;
+        }
+
+        let kind = match tag {
+            TAG_COMPOUND => Kind::Compound,
+            TAG_LIST => Kind::List(list_type),
+            _ if tag == TAG_BYTE_ARR.0 => Kind::Array {
+                item: TAG_BYTE_ARR.1,
+                base64,
+            },
+            _ if tag == TAG_INT_ARR.0 => Kind::Array {
+                item: TAG_INT_ARR.1,
+                base64,
+            },
+            _ if tag == TAG_LONG_ARR.0 => Kind::Array {
+                item: TAG_LONG_ARR.1,
+                base64,
+            },
+            _ => Kind::Scalar {
+                tag,
+                has_value: false,
+            },
+        };
+        self.push(kind, segment);
+    }
+
+    fn close(&mut self) {
+        if let Some(Frame {
+            kind: Kind::Scalar {
+                tag,
+                has_value: false,
+            },
+            ..
+        }) = self.stack.last()
+        {
+            // Numbers can't be empty, strings can
+            if let Err(err) = check_value(tag, "") {
+                self.report(err);
+            }
+        }
+
+        self.stack.pop();
+    }
+
+    fn text(&mut self, text: &str) {
+        let problem = match self.stack.last_mut() {
+            Some(Frame {
+                kind: Kind::Scalar { tag, has_value },
+                ..
+            }) if *tag == TAG_STRING || !is_blank(text) => {
+                *has_value = true;
+                check_value(tag, text.trim()).err()
+            }
+            // Indentation between elements, or around comments in a number
+            _ if is_blank(text) => None,
+            // Compact arrays, see `XmlOptions::compact_arrays`
+            Some(Frame {
+                kind: Kind::Array { item, base64 },
+                ..
+            }) => match base64 {
+                true => base64::decode(text).map(drop).ok_or(RuntimeErr::InvalidBase64),
+                false => text
+                    .split_ascii_whitespace()
+                    .try_for_each(|number| check_value(item, number)),
+            }
+            .err(),
+            Some(Frame {
+                kind: Kind::Unknown,
+                ..
+            }) => None,
+            _ => Some(RuntimeErr::UnexpectedText),
+        };
+
+        if let Some(problem) = problem {
+            self.report(problem);
+        }
+    }
+
+    /// Value of the `name` attribute, whether a byte array is base64 and the element
+    /// type declared by a list, reporting every other attribute.
+    fn attributes(
+        &mut self,
+        ev: &BytesStart,
+        tag: Option<&str>,
+    ) -> (Option<String>, bool, Option<&'static str>) {
+        let mut name = None;
+        let mut base64 = false;
+        let mut list_type = None;
+        for at in ev.attributes() {
+            match at {
+                Ok(at) if at.key.as_ref() == TAG_NAME_ATTR.as_bytes() => {
+                    match at.unescape_value() {
+                        Ok(value) => name = Some(value.into_owned()),
+                        Err(err) => self.report(err.into()),
+                    }
+                }
+                Ok(at)
+                    if at.key.as_ref() == TAG_ENCODING_ATTR.as_bytes()
+                        && tag == Some(TAG_BYTE_ARR.0) =>
+                {
+                    match at.value.as_ref() == ENCODING_BASE64.as_bytes() {
+                        true => base64 = true,
+                        false => self.report(RuntimeErr::UnsupportedXml(
+                            "array encoding other than base64",
+                        )),
+                    }
+                }
+                Ok(at) if at.key.as_ref() == TAG_TYPE_ATTR.as_bytes() && tag == Some(TAG_LIST) => {
+                    match type_id_of(at.value.as_ref()).and_then(element_of) {
+                        Some(element) => list_type = Some(element),
+                        None => self.report(RuntimeErr::UnknownListType(
+                            String::from_utf8_lossy(at.value.as_ref()).into_owned(),
+                        )),
+                    }
+                }
+                Ok(at) => self.report(RuntimeErr::UnknownAttribute(
+                    String::from_utf8_lossy(at.key.as_ref()).into_owned(),
+                )),
+                Err(err) => self.report(quick_xml::Error::from(err).into()),
+            }
+        }
+
+        (name, base64, list_type)
+    }
+
+    fn index_segment(&self) -> String {
+        let index = self.stack.last().map_or(0, |frame| frame.children);
+        format!("[{}]", index)
+    }
+
+    fn push(&mut self, kind: Kind, segment: String) {
+        self.stack.push(Frame {
+            kind,
+            segment,
+            children: 0,
+        });
+    }
+
+    /// Records `problem` along with the path of the open elements.
+    fn report(&mut self, problem: RuntimeErr) {
+        let mut path = String::new();
+        for frame in self.stack.iter().skip(1) {
+            if !frame.segment.starts_with('[') && !path.is_empty() {
+                path.push('.');
+            }
+            path.push_str(&frame.segment);
+        }
+
+        self.problems.push(problem);
+        self.paths.push(path);
+    }
+
+    /// Wraps the problems reported for the last event with its line and column.
+    fn locate_new(&mut self, line_column: impl Fn() -> (usize, usize)) {
+        if self.located == self.problems.len() {
+            return;
+        }
+
+        let (line, column) = line_column();
+        let new = self.problems.split_off(self.located);
+        for (source, path) in new.into_iter().zip(self.paths.drain(..)) {
+            self.problems.push(RuntimeErr::XmlContext {
+                line,
+                column,
+                path,
+                source: Box::new(source),
+            });
+        }
+        self.located = self.problems.len();
+    }
+}
+
+/// Type name of the element, if it stands for an NBT tag.
+fn tag_type(name: &[u8]) -> Option<&'static str> {
+    let tag = match name {
+        TAG_BYTE_B => TAG_BYTE,
+        TAG_SHORT_B => TAG_SHORT,
+        TAG_INT_B => TAG_INT,
+        TAG_LONG_B => TAG_LONG,
+        TAG_FLOAT_B => TAG_FLOAT,
+        TAG_DOUBLE_B => TAG_DOUBLE,
+        TAG_STRING_B => TAG_STRING,
+        TAG_LIST_B => TAG_LIST,
+        TAG_COMPOUND_B => TAG_COMPOUND,
+        TAG_BYTE_ARR_B => TAG_BYTE_ARR.0,
+        TAG_INT_ARR_B => TAG_INT_ARR.0,
+        TAG_LONG_ARR_B => TAG_LONG_ARR.0,
+        _ => return None,
+    };
+
+    Some(tag)
+}
+
+/// Array element name holding items of type `item`.
+fn array_type(item: &str) -> &'static str {
+    match item {
+        TAG_BYTE => TAG_BYTE_ARR.0,
+        TAG_INT => TAG_INT_ARR.0,
+        _ => TAG_LONG_ARR.0,
+    }
+}
+
+/// Checks that `text` is a valid value for the scalar element `tag`.
+fn check_value(tag: &str, text: &str) -> err::Result<()> {
+    match tag {
+        TAG_BYTE => text.parse::<i8>().map(drop)?,
+        TAG_SHORT => text.parse::<i16>().map(drop)?,
+        TAG_INT => text.parse::<i32>().map(drop)?,
+        TAG_LONG => text.parse::<i64>().map(drop)?,
+        TAG_FLOAT => parse_float::<f32>(text).map(drop)?,
+        TAG_DOUBLE => parse_float::<f64>(text).map(drop)?,
+        _ => {}
+    }
+
+    Ok(())
+}
@@ -1,208 +1,190 @@
-use std::collections::VecDeque;
-use std::fmt::{Debug, Display};
-use std::io::{BufWriter, Write};
+use std::fmt::Display;
+use std::io::{BufWriter, Read, Write};
 
-use quick_xml::events::{BytesEnd, BytesStart, BytesText, Event};
+use quick_xml::events::{BytesEnd, BytesStart, BytesText, Event as XmlEvent};
 use quick_xml::writer::Writer;
 
-use nbt::{CompoundTag, Map, Tag};
+use nbt::consts::{TAG_BYTE_ARRAY, TAG_INT_ARRAY};
+use nbt::float::format_float;
+use nbt::stream::{ArrayChunk, Event, EventReader};
+use nbt::Tag;
 
+use super::base64;
 use super::consts::*;
+use super::{element_of, TagExtras, XmlOptions};
 use crate::err;
 
-type TagMap<'a> = (&'a Box<str>, &'a Tag);
-
-pub fn print_xml<'a, T: Write>(
-    stream: &'a mut BufWriter<T>,
-    rtag: &'a CompoundTag,
+/// Writes NBT read from `events` as XML.
+///
+/// The document is streamed, so only the currently open elements are kept in memory.
+pub fn print_xml<T: Write, R: Read>(
+    stream: &mut BufWriter<T>,
+    events: EventReader<R>,
+    options: &XmlOptions,
 ) -> err::Result<()> {
-    let mut tag_stack: VecDeque<NbtIter> = VecDeque::with_capacity(512);
-    tag_stack.push_back(NbtIter::from_compound(rtag));
-
     let mut writer = Writer::new_with_indent(stream, b' ', 4);
-
-    writer.write_event(Event::Start({
-        let mut elem = BytesStart::new(TAG_COMPOUND);
-        if let Some(rtag_name) = &rtag.name {
-            elem.push_attribute((TAG_NAME_ATTR, rtag_name.as_ref()));
-        }
-
-        elem
-    }))?;
-
-    traverse_nbt(&mut writer, &mut tag_stack)?;
-
-    writer.write_event(Event::Eof)?;
-    writeln!(writer.get_mut())?;
-
-    Ok(())
-}
-
-fn traverse_nbt<W: Write>(
-    writer: &mut Writer<W>,
-    tag_stack: &mut VecDeque<NbtIter>,
-) -> err::Result<()> {
-    while !tag_stack.is_empty() {
-        let head = tag_stack.len() - 1;
-
-        let (tname, tag) = match tag_stack[head].next() {
-            None => {
-                let xml_el = match tag_stack[head] {
-                    NbtIter::List(_) => TAG_LIST,
-                    NbtIter::Compound(_) => TAG_COMPOUND,
-                };
-                writer.write_event(Event::End(BytesEnd::new(xml_el)))?;
-
-                tag_stack.pop_back();
-                continue;
-            }
-            Some(NbtElement::Tag(tag)) => (None, tag),
-            Some(NbtElement::TagMap(tname, tag)) => (Some(tname), tag),
-        };
-
-        let mut attr = None;
-        if let Some(tname) = tname {
-            attr = Some((TAG_NAME_ATTR, tname));
-        }
-
-        match tag {
-            Tag::Byte(b) => {
-                write_xml_el(writer, TAG_BYTE, attr, format!("{}", b).as_str())?;
-            }
-            Tag::Short(s) => {
-                write_xml_el(writer, TAG_SHORT, attr, format!("{}", s).as_str())?;
-            }
-            Tag::Int(i) => {
-                write_xml_el(writer, TAG_INT, attr, format!("{}", i).as_str())?;
+    let mut open_el: Vec<&'static str> = Vec::with_capacity(512);
+    let mut key: Option<Box<str>> = None;
+    // Compact arrays: whether a value was written yet, and bytes waiting to be base64
+    // encoded in groups of 3
+    let mut array_started = false;
+    let mut base64_carry: Vec<u8> = Vec::with_capacity(2);
+
+    for event in events {
+        match event? {
+            Event::Key(name) => key = Some(name),
+            Event::StartCompound(name) => {
+                let name = name.or(key.take());
+                write_xml_start(&mut writer, TAG_COMPOUND, name.as_deref())?;
+                open_el.push(TAG_COMPOUND);
             }
-            Tag::Long(l) => {
-                write_xml_el(writer, TAG_LONG, attr, format!("{}", l).as_str())?;
+            Event::StartList(element_type, len) => {
+                let mut elem = BytesStart::new(TAG_LIST);
+                if let Some(name) = key.take() {
+                    elem.push_attribute((TAG_NAME_ATTR, name.as_ref()));
+                }
+                // Elements give the type of other lists away
+                if let (0, Some(element)) = (len, element_of(element_type)) {
+                    elem.push_attribute((TAG_TYPE_ATTR, element));
+                }
+                writer.write_event(XmlEvent::Start(elem))?;
+                open_el.push(TAG_LIST);
             }
-            Tag::Float(f) => {
-                write_xml_el(writer, TAG_FLOAT, attr, format!("{}", f).as_str())?;
-            }
-            Tag::Double(d) => {
-                write_xml_el(writer, TAG_DOUBLE, attr, format!("{}", d).as_str())?;
-            }
-            Tag::String(s) => {
-                write_xml_el(writer, TAG_STRING, attr, format!("{}", s).as_str())?;
-            }
-
-            Tag::ByteArray(vb) => {
-                write_xml_array(writer, TAG_BYTE_ARR, attr, &vb)?;
-            }
-            Tag::IntArray(vi) => {
-                write_xml_array(writer, TAG_INT_ARR, attr, &vi)?;
+            Event::StartArray(atype, _) => {
+                let xml_el = match atype {
+                    TAG_BYTE_ARRAY => TAG_BYTE_ARR.0,
+                    TAG_INT_ARRAY => TAG_INT_ARR.0,
+                    _ => TAG_LONG_ARR.0,
+                };
+                let mut elem = BytesStart::new(xml_el);
+                if let Some(name) = key.take() {
+                    elem.push_attribute((TAG_NAME_ATTR, name.as_ref()));
+                }
+                if options.compact_arrays && atype == TAG_BYTE_ARRAY {
+                    elem.push_attribute((TAG_ENCODING_ATTR, ENCODING_BASE64));
+                }
+                writer.write_event(XmlEvent::Start(elem))?;
+                open_el.push(xml_el);
+                array_started = false;
             }
-            Tag::LongArray(vl) => {
-                write_xml_array(writer, TAG_LONG_ARR, attr, &vl)?;
+            Event::ArrayChunk(chunk) if options.compact_arrays => {
+                let mut text = String::new();
+                match chunk {
+                    ArrayChunk::Byte(vb) => {
+                        base64_carry.extend(vb.iter().map(|b| *b as u8));
+                        let whole = base64_carry.len() / 3 * 3;
+                        base64::encode(&base64_carry[..whole], &mut text);
+                        base64_carry.drain(..whole);
+                    }
+                    ArrayChunk::Int(vi) => join_numbers(&mut text, &vi, array_started),
+                    ArrayChunk::Long(vl) => join_numbers(&mut text, &vl, array_started),
+                }
+                array_started = true;
+                write_xml_text(&mut writer, &text)?;
             }
-
-            Tag::List(v) => {
-                tag_stack.push_back(NbtIter::List(v.iter()));
-
-                writer.write_event(Event::Start(
-                    BytesStart::new(TAG_LIST).with_attributes(attr),
-                ))?;
+            Event::ArrayChunk(chunk) => match chunk {
+                ArrayChunk::Byte(vb) => write_xml_array(&mut writer, TAG_BYTE_ARR.1, &vb)?,
+                ArrayChunk::Int(vi) => write_xml_array(&mut writer, TAG_INT_ARR.1, &vi)?,
+                ArrayChunk::Long(vl) => write_xml_array(&mut writer, TAG_LONG_ARR.1, &vl)?,
+            },
+            Event::Scalar(tag) => {
+                let content = match &tag {
+                    Tag::Byte(b) => format!("{}", b),
+                    Tag::Short(s) => format!("{}", s),
+                    Tag::Int(i) => format!("{}", i),
+                    Tag::Long(l) => format!("{}", l),
+                    Tag::Float(f) => format_float(*f, options.float_format),
+                    Tag::Double(d) => format_float(*d, options.float_format),
+                    Tag::String(s) => format!("{}", s),
+                    _ => unreachable!("libnbt only emits numbers and strings as scalars"),
+                };
+                let attr = key.take();
+                write_xml_el(&mut writer, tag.type_name(), attr.as_deref(), &content)?;
             }
-            Tag::Compound(c) => {
-                tag_stack.push_back(NbtIter::from_map(c));
-
-                writer.write_event(Event::Start(
-                    BytesStart::new(TAG_COMPOUND).with_attributes(attr),
-                ))?;
+            Event::End => {
+                if !base64_carry.is_empty() {
+                    let mut text = String::new();
+                    base64::encode(&base64_carry, &mut text);
+                    base64_carry.clear();
+                    write_xml_text(&mut writer, &text)?;
+                }
+                if let Some(xml_el) = open_el.pop() {
+                    writer.write_event(XmlEvent::End(BytesEnd::new(xml_el)))?;
+                }
             }
         }
     }
 
+    writer.write_event(XmlEvent::Eof)?;
+    writeln!(writer.get_mut())?;
+
     Ok(())
 }
 
 #[inline(always)]
-fn write_xml_el<'a, W: Write>(
-    writer: &'a mut Writer<W>,
+fn write_xml_start<W: Write>(
+    writer: &mut Writer<W>,
     ttype: &'static str,
-    attr: Option<(&'static str, &'a str)>,
-    content: &'a str,
+    name: Option<&str>,
 ) -> err::Result<()> {
-    let mut elem_w = writer.create_element(ttype);
-
-    if let Some(attr) = attr {
-        elem_w = elem_w.with_attributes([attr].into_iter());
+    let mut elem = BytesStart::new(ttype);
+    if let Some(name) = name {
+        elem.push_attribute((TAG_NAME_ATTR, name));
     }
 
-    elem_w.write_text_content(BytesText::new(content))?;
+    writer.write_event(XmlEvent::Start(elem))?;
 
     Ok(())
 }
 
 #[inline(always)]
-fn write_xml_array<'a, W: Write, T: Display>(
+fn write_xml_el<'a, W: Write>(
     writer: &'a mut Writer<W>,
-    atypes: (&'static str, &'static str),
-    attr: Option<(&'static str, &'a str)>,
-    content: &'a [T],
+    ttype: &'static str,
+    name: Option<&'a str>,
+    content: &'a str,
 ) -> err::Result<()> {
-    writer.write_event(Event::Start({
-        let mut elem = BytesStart::new(atypes.0);
-        if let Some(attr) = attr {
-            elem = elem.with_attributes([attr].into_iter());
-        }
-
-        elem
-    }))?;
+    let mut elem_w = writer.create_element(ttype);
 
-    for c in content {
-        write_xml_el(writer, atypes.1, None, format!("{}", c).as_str())?;
+    if let Some(name) = name {
+        elem_w = elem_w.with_attributes([(TAG_NAME_ATTR, name)]);
     }
 
-    writer.write_event(Event::End(BytesEnd::new(atypes.0)))?;
+    elem_w.write_text_content(BytesText::new(content))?;
 
     Ok(())
 }
 
-enum NbtIter<'a> {
-    Compound(Box<dyn DoubleEndedIterator<Item = TagMap<'a>> + 'a>),
-    List(core::slice::Iter<'a, Tag>),
-}
+/// Appends `values` to `text` separated by spaces, `continued` when values were
+/// written before.
+fn join_numbers<T: Display>(text: &mut String, values: &[T], continued: bool) {
+    use std::fmt::Write;
 
-#[derive(Debug)]
-enum NbtElement<'a> {
-    TagMap(&'a str, &'a Tag),
-    Tag(&'a Tag),
-}
-
-impl<'a> NbtIter<'a> {
-    fn from_compound(ctag: &'a CompoundTag) -> Self {
-        Self::Compound(Box::new(ctag.iter()))
-    }
-
-    fn from_map(map: &'a Map) -> Self {
-        Self::Compound(Box::new(map.iter()))
+    for (i, value) in values.iter().enumerate() {
+        if continued || i > 0 {
+            text.push(' ');
+        }
+        let _ = write!(text, "{}", value);
     }
 }
 
-impl<'a> Iterator for NbtIter<'a> {
-    type Item = NbtElement<'a>;
-
-    fn next(&mut self) -> Option<Self::Item> {
-        use NbtIter::*;
+/// Writes text that needs no escaping, such as numbers or base64.
+#[inline(always)]
+fn write_xml_text<W: Write>(writer: &mut Writer<W>, text: &str) -> err::Result<()> {
+    writer.write_event(XmlEvent::Text(BytesText::from_escaped(text)))?;
 
-        match self {
-            Compound(i) => i
-                .next()
-                .map(|(name, tag)| NbtElement::TagMap(name.as_ref(), tag)),
-            List(i) => i.next().map(|el| NbtElement::Tag(el)),
-        }
-    }
+    Ok(())
 }
 
-impl Debug for NbtIter<'_> {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let res = match self {
-            Self::List(_) => "List",
-            Self::Compound(_) => "Compound",
-        };
-        write!(f, "Nbt::{}", res)
+#[inline(always)]
+fn write_xml_array<W: Write, T: Display>(
+    writer: &mut Writer<W>,
+    ttype: &'static str,
+    content: &[T],
+) -> err::Result<()> {
+    for c in content {
+        write_xml_el(writer, ttype, None, format!("{}", c).as_str())?;
     }
+
+    Ok(())
 }
@@ -0,0 +1,204 @@
+//! YAML form of NBT data, YAML tags keep the NBT types.
+//!
+//! Compounds are mappings and strings are plain YAML strings. Every other tag carries
+//! its type as a YAML tag named after the XML element:
+//!
+//! ```yaml
+//! HP: !short 20
+//! Name: Steve
+//! Pos:
+//! - !double 1.5
+//! - !double -0.0
+//! UUID: !int_array [1, 2, 3, 4]
+//! Items: !compound_list []
+//! ```
+//!
+//! Lists are sequences. Empty lists are tagged with their element type, e.g.
+//! `!compound_list`, any list may be. Floats and doubles use `.nan` and `.inf`, NaNs
+//! other than the usual one are given as bits, e.g. `!float '0x7fc00001'`.
+//!
+//! Untagged numbers are read as `TAG_Int` or `TAG_Double` and booleans as `TAG_Byte`,
+//! for hand written files. nbtc always writes the tags.
+
+use std::io::{Read, Write};
+
+use nbt::consts::*;
+use nbt::float::{nan_bits, parse_float, short_float};
+use nbt::{CompoundTag, ListTag, Map, Tag};
+use serde_yaml::value::{Tag as YamlTag, TaggedValue};
+use serde_yaml::{Mapping, Value};
+
+use crate::err::{self, RuntimeErr};
+use crate::xml::{element_of, type_id_of};
+
+/// Suffix of the tag of lists giving their element type, e.g. `!compound_list`.
+const LIST_SUFFIX: &str = "_list";
+
+/// Writes `root` as YAML.
+pub fn write_yaml<W: Write>(writer: W, root: &CompoundTag) -> err::Result<()> {
+    let root = compound(root.iter());
+    serde_yaml::to_writer(writer, &root).map_err(RuntimeErr::from)
+}
+
+/// Reads a compound from YAML, as written by [`write_yaml`].
+///
+/// # Errors
+///
+/// Errors about a tag are wrapped in [`RuntimeErr::TagContext`], giving the path of the
+/// tag.
+pub fn read_yaml<R: Read>(reader: R) -> err::Result<CompoundTag> {
+    let value: Value = serde_yaml::from_reader(reader)?;
+    match tag_from(&value)? {
+        Tag::Compound(map) => Ok(CompoundTag::with(map)),
+        _ => Err(RuntimeErr::NBTDecode(
+            nbt::err::TagDecodeError::RootMustBeCompound,
+        )),
+    }
+}
+
+fn tag_to(tag: &Tag) -> Value {
+    let value = match tag {
+        Tag::String(text) => return Value::from(text.as_ref()),
+        Tag::Compound(map) => return compound(map.iter()),
+        Tag::List(list) if !list.is_empty() => {
+            return Value::Sequence(list.into_iter().map(tag_to).collect())
+        }
+        Tag::List(list) => {
+            return match element_of(list.element_type()) {
+                Some(element) => tagged(format!("{}{}", element, LIST_SUFFIX), Value::Sequence(vec![])),
+                None => Value::Sequence(vec![]),
+            };
+        }
+        Tag::Byte(v) => Value::from(*v),
+        Tag::Short(v) => Value::from(*v),
+        Tag::Int(v) => Value::from(*v),
+        Tag::Long(v) => Value::from(*v),
+        Tag::Float(v) => nan_bits(*v).map_or_else(|| Value::from(short_float(*v)), Value::from),
+        Tag::Double(v) => nan_bits(*v).map_or_else(|| Value::from(*v), Value::from),
+        Tag::ByteArray(array) => Value::from(array.as_slice()),
+        Tag::IntArray(array) => Value::from(array.as_slice()),
+        Tag::LongArray(array) => Value::from(array.as_slice()),
+    };
+
+    let element = element_of(tag.tag_type()).expect("tags have an element name");
+    tagged(element.to_string(), value)
+}
+
+fn compound<'a>(entries: impl Iterator<Item = (&'a Box<str>, &'a Tag)>) -> Value {
+    let map = entries
+        .map(|(key, tag)| (Value::from(key.as_ref()), tag_to(tag)))
+        .collect::<Mapping>();
+    Value::Mapping(map)
+}
+
+fn tagged(tag: String, value: Value) -> Value {
+    Value::Tagged(Box::new(TaggedValue {
+        tag: YamlTag::new(tag),
+        value,
+    }))
+}
+
+fn tag_from(value: &Value) -> err::Result<Tag> {
+    let tag = match value {
+        Value::Tagged(tagged) => return typed_from(tagged),
+        Value::String(text) => Tag::String(text.as_str().into()),
+        Value::Mapping(map) => {
+            let mut compound = Map::with_capacity(map.len());
+            for (key, entry) in map {
+                let key = key.as_str().ok_or(RuntimeErr::InvalidValue("compound key"))?;
+                let tag = tag_from(entry).map_err(|err| err.in_key(key))?;
+                compound.insert(key.into(), tag);
+            }
+            Tag::Compound(compound)
+        }
+        Value::Sequence(elements) => Tag::List(list_from(ListTag::new(), elements)?),
+        // Hand written numbers and booleans
+        Value::Bool(b) => Tag::Byte(*b as i8),
+        Value::Number(number) => match number.as_i64() {
+            Some(int) => Tag::Int(int.try_into().map_err(|_| RuntimeErr::InvalidValue("int"))?),
+            None => Tag::Double(number.as_f64().ok_or(RuntimeErr::InvalidValue("double"))?),
+        },
+        Value::Null => return Err(RuntimeErr::InvalidValue("null")),
+    };
+
+    Ok(tag)
+}
+
+fn typed_from(tagged: &TaggedValue) -> err::Result<Tag> {
+    let name = tagged.tag.to_string();
+    let name = name.trim_start_matches('!');
+    let value = &tagged.value;
+
+    // Lists giving their element type
+    if let Some(element) = name.strip_suffix(LIST_SUFFIX) {
+        let element_type = type_id_of(element.as_bytes())
+            .ok_or_else(|| RuntimeErr::UnknownTag(name.to_string()))?;
+        let elements = value.as_sequence().ok_or(RuntimeErr::InvalidValue("list"))?;
+        let list = ListTag::with_type(element_type).expect("known element type");
+        return list_from(list, elements).map(Tag::List);
+    }
+
+    let tag_type = type_id_of(name.as_bytes()).ok_or_else(|| RuntimeErr::UnknownTag(name.to_string()))?;
+    let type_name = element_of(tag_type).expect("known tag type");
+    let invalid = || RuntimeErr::InvalidValue(type_name);
+
+    let tag = match tag_type {
+        TAG_BYTE => Tag::Byte(int_from(value).ok_or_else(invalid)?),
+        TAG_SHORT => Tag::Short(int_from(value).ok_or_else(invalid)?),
+        TAG_INT => Tag::Int(int_from(value).ok_or_else(invalid)?),
+        TAG_LONG => Tag::Long(int_from(value).ok_or_else(invalid)?),
+        TAG_FLOAT => Tag::Float(match value {
+            Value::String(text) => parse_float(text).ok(),
+            _ => value.as_f64().map(|v| v as f32),
+        }
+        .ok_or_else(invalid)?),
+        TAG_DOUBLE => Tag::Double(match value {
+            Value::String(text) => parse_float(text).ok(),
+            _ => value.as_f64(),
+        }
+        .ok_or_else(invalid)?),
+        TAG_BYTE_ARRAY => Tag::ByteArray(array_from(value, type_name)?),
+        TAG_INT_ARRAY => Tag::IntArray(array_from(value, type_name)?),
+        TAG_LONG_ARRAY => Tag::LongArray(array_from(value, type_name)?),
+        // `!string`, `!list` and `!compound` only repeat what the value says
+        _ => match (tag_type, value) {
+            (TAG_STRING, Value::String(_))
+            | (TAG_LIST, Value::Sequence(_))
+            | (TAG_COMPOUND, Value::Mapping(_)) => tag_from(value)?,
+            _ => return Err(invalid()),
+        },
+    };
+
+    Ok(tag)
+}
+
+fn list_from(mut list: ListTag, elements: &[Value]) -> err::Result<ListTag> {
+    for (index, element) in elements.iter().enumerate() {
+        tag_from(element)
+            .and_then(|tag| {
+                let found = element_of(tag.tag_type()).unwrap_or_default();
+                list.push(tag).map_err(|_| RuntimeErr::MixedList {
+                    expected: element_of(list.element_type()).unwrap_or_default(),
+                    found,
+                })
+            })
+            .map_err(|err| err.in_index(index))?;
+    }
+
+    Ok(list)
+}
+
+fn int_from<T: TryFrom<i64>>(value: &Value) -> Option<T> {
+    value.as_i64().and_then(|v| T::try_from(v).ok())
+}
+
+fn array_from<T: TryFrom<i64>>(value: &Value, type_name: &'static str) -> err::Result<Vec<T>> {
+    let elements = value.as_sequence().ok_or(RuntimeErr::InvalidValue(type_name))?;
+    elements
+        .iter()
+        .enumerate()
+        .map(|(index, element)| {
+            int_from(element).ok_or_else(|| RuntimeErr::InvalidValue(type_name).in_index(index))
+        })
+        .collect()
+}